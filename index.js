@@ -15,7 +15,8 @@ async function keygen(m, arg) {
 }
 
 async function sign(m, arg, key_store) {
-  return await m.gg18_sign(t, n, key_store, "Hello Eigen");
+  // "Hello Eigen", hex encoded
+  return await m.gg18_sign(t, n, key_store, "48656c6c6f20456967656e", "sha256");
 }
 
 thsig.then((m) => {
@@ -8,6 +8,9 @@ use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, RequestMode, Response};
 
 
+use crate::bitcoin;
+use crate::ethereum::{public_key_address, to_checksum_address, Transaction};
+use crate::gg_2018::message::MessageHashMode;
 use crate::gg_2018::mta::*;
 use crate::gg_2018::party_i::*;
 use reqwest::Client;
@@ -16,7 +19,9 @@ use crate::curv::{
     arithmetic::traits::Converter,
     arithmetic::traits::ConvertFrom,
     cryptographic_primitives::{
+        hashing::transcript::Transcript,
         proofs::sigma_dlog::DLogProof, secret_sharing::feldman_vss::VerifiableSS,
+        secret_sharing::pvss::PubliclyVerifiableSS,
         proofs::sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof,
     },
     elliptic::curves::{secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar}},
@@ -25,7 +30,7 @@ use crate::curv::{
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 use crate::paillier::traits::EncryptWithChosenRandomness;
 
-use crate::paillier::EncryptionKey;
+use crate::paillier::{EncryptionKey, KeyGenProgress};
 use sha2::Sha256;
 use std::{fs, time};
 
@@ -48,6 +53,82 @@ pub async fn signup(client: &Client) -> Result<PartySignup, ()> {
 
 #[wasm_bindgen]
 pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
+    keygen(t, n, save_path, None, false).await
+}
+
+/// Keygen with publicly verifiable secret sharing: the shares are encrypted to keys each party
+/// publishes for the purpose and broadcast with proofs, instead of being sent point-to-point.
+/// Every party checks every dealing, so a bad share is caught in the open.
+#[wasm_bindgen]
+pub async fn gg18_keygen_pvss(t: usize, n: usize, save_path: String) -> String {
+    keygen(t, n, save_path, None, true).await
+}
+
+/// Generates the keygen pre-parameters as JSON, to be stored and passed to
+/// `gg18_keygen_with_preparams`. Slow: meant to run in a Web Worker.
+#[wasm_bindgen]
+pub fn gg18_generate_preparams() -> Result<String, JsValue> {
+    serde_json::to_string(&PreParams::generate())
+        .map_err(|e| JsValue::from_str(&format!("cannot serialize preparams: {}", e)))
+}
+
+/// Same as `gg18_generate_preparams`, yielding to the event loop while the primes are searched
+/// for so that it can run on the main thread. `on_progress`, if given, is called with the
+/// fraction of the current search done, once for the Paillier key and once for N_tilde.
+#[wasm_bindgen]
+pub async fn gg18_generate_preparams_async(
+    on_progress: Option<js_sys::Function>,
+) -> Result<String, JsValue> {
+    let preparams = PreParams::generate_async(
+        2048,
+        |progress: &KeyGenProgress| report_progress(&on_progress, progress),
+        yield_to_event_loop,
+    )
+    .await;
+    serde_json::to_string(&preparams)
+        .map_err(|e| JsValue::from_str(&format!("cannot serialize preparams: {}", e)))
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+}
+
+/// Resolves from a `setTimeout` callback, a macrotask, so that the page renders and handles
+/// events before the key generation picks up again. Available in windows and workers alike.
+async fn yield_to_event_loop() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        set_timeout(&resolve, 0);
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+fn report_progress(on_progress: &Option<js_sys::Function>, progress: &KeyGenProgress) {
+    if let Some(on_progress) = on_progress {
+        let _ = on_progress.call1(&JsValue::NULL, &JsValue::from_f64(progress.fraction()));
+    }
+}
+
+#[wasm_bindgen]
+pub async fn gg18_keygen_with_preparams(
+    t: usize,
+    n: usize,
+    save_path: String,
+    preparams: String,
+) -> Result<String, JsValue> {
+    let preparams: PreParams = serde_json::from_str(&preparams)
+        .map_err(|e| JsValue::from_str(&format!("invalid preparams: {}", e)))?;
+    Ok(keygen(t, n, save_path, Some(preparams), false).await)
+}
+
+async fn keygen(
+    t: usize,
+    n: usize,
+    save_path: String,
+    preparams: Option<PreParams>,
+    pvss: bool,
+) -> String {
     let client = reqwest::Client::new();
     //let delay = time::Duration::from_millis(25);
     let params = Parameters {
@@ -62,8 +143,14 @@ pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
         PartySignup {number, uuid} => (number, uuid),
     };
 
-    let party_keys = Keys::create(party_num_int as usize);
-    let (bc_i, decom_i) = party_keys.phase1_broadcast_phase3_proof_of_correct_key();
+    let party_keys: Keys = match preparams {
+        Some(preparams) => Keys::create_with_preparams(party_num_int as usize, &preparams),
+        None => {
+            Keys::create_async(party_num_int as usize, |_: &KeyGenProgress| (), yield_to_event_loop)
+                .await
+        }
+    };
+    let (bc_i, decom_i) = party_keys.phase1_broadcast_phase3_proof_of_correct_key(uuid.as_bytes());
     console_log!("broadcast");
     assert!(broadcast(
             &client,
@@ -90,13 +177,21 @@ pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
 
     bc1_vec.insert(party_num_int as usize - 1, bc_i);
 
+    // in PVSS mode the key this party receives its shares under goes along with the decommitment
+    let (pvss_dk, pvss_ek) = Keys::<Point>::create_pvss_keypair();
+
     console_log!("broadcast round 2");
     // send ephemeral public keys and check commitments correctness
+    let round2_msg = if pvss {
+        serde_json::to_string(&(&decom_i, &pvss_ek)).unwrap()
+    } else {
+        serde_json::to_string(&decom_i).unwrap()
+    };
     assert!(broadcast(
             &client,
             party_num_int,
             "round2",
-            serde_json::to_string(&decom_i).unwrap(),
+            round2_msg,
             uuid.clone()
     ).await.is_ok());
     console_log!("poll_for_broadcasts round 2");
@@ -112,13 +207,22 @@ pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
     let mut j = 0;
     let mut point_vec: Vec<Point> = Vec::new();
     let mut decom_vec: Vec<KeyGenDecommitMessage1> = Vec::new();
+    let mut pvss_ek_vec: Vec<Point> = Vec::new();
     let mut enc_keys: Vec<Vec<u8>> = Vec::new();
     for i in 1..=PARTIES {
         if i == party_num_int {
             point_vec.push(decom_i.y_i.clone());
             decom_vec.push(decom_i.clone());
+            pvss_ek_vec.push(pvss_ek.clone());
         } else {
-            let decom_j: KeyGenDecommitMessage1 = serde_json::from_str(&round2_ans_vec[j]).unwrap();
+            let decom_j: KeyGenDecommitMessage1 = if pvss {
+                let (decom_j, pvss_ek_j): (KeyGenDecommitMessage1, Point) =
+                    serde_json::from_str(&round2_ans_vec[j]).unwrap();
+                pvss_ek_vec.push(pvss_ek_j);
+                decom_j
+            } else {
+                serde_json::from_str(&round2_ans_vec[j]).unwrap()
+            };
             point_vec.push(decom_j.y_i.clone());
             decom_vec.push(decom_j.clone());
             let key_bn: BigInt = (decom_j.y_i.clone() * party_keys.u_i.clone())
@@ -135,103 +239,159 @@ pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
     let (head, tail) = point_vec.split_at(1);
     let y_sum = tail.iter().fold(head[0].clone(), |acc, x| acc + x);
 
-    console_log!("phase1_verify_com_phase3_verify_correct_key_phase2_distribute");
-    let (vss_scheme, secret_shares, _index) = party_keys
-        .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
-            &params, &decom_vec, &bc1_vec,
-        )
-        .expect("invalid key");
-
-    //////////////////////////////////////////////////////////////////////////////
+    let (shared_keys, dlog_proof, vss_scheme_vec) = if pvss {
+        console_log!("phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss");
+        let (pvss_scheme, _index) = party_keys
+            .phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss(
+                &params, uuid.as_bytes(), &decom_vec, &bc1_vec, &pvss_ek_vec,
+            )
+            .expect("invalid key");
 
-    let mut j = 0;
-    for (k, i) in (1..=PARTIES).enumerate() {
-        if i != party_num_int {
-            // prepare encrypted ss for party i:
-            let key_i = &enc_keys[j];
-            let plaintext = BigInt::to_vec(&secret_shares[k].to_big_int());
-            let aead_pack_i = aes_encrypt(key_i, &plaintext);
-            assert!(sendp2p(
-                    &client,
-                    party_num_int,
-                    i,
-                    "round3",
-                    serde_json::to_string(&aead_pack_i).unwrap(),
-                    uuid.clone()
-            ).await.is_ok());
-            j += 1;
+        // round 3: broadcast the dealing, checked by every party
+        assert!(broadcast(
+                &client,
+                party_num_int,
+                "round3",
+                serde_json::to_string(&pvss_scheme).unwrap(),
+                uuid.clone()
+        ).await.is_ok());
+        let round3_ans_vec = poll_for_broadcasts(
+            &client,
+            party_num_int,
+            PARTIES,
+            "round3",
+            uuid.clone(),
+        ).await;
+
+        let mut j = 0;
+        let mut pvss_scheme_vec: Vec<PubliclyVerifiableSS> = Vec::new();
+        for i in 1..=PARTIES {
+            if i == party_num_int {
+                pvss_scheme_vec.push(pvss_scheme.clone());
+            } else {
+                let pvss_scheme_j: PubliclyVerifiableSS =
+                    serde_json::from_str(&round3_ans_vec[j]).unwrap();
+                pvss_scheme_vec.push(pvss_scheme_j);
+                j += 1;
+            }
         }
-    }
 
-    console_log!("poll_for_p2p");
-    let round3_ans_vec = poll_for_p2p(
-        &client,
-        party_num_int,
-        PARTIES,
-        //delay,
-        "round3",
-        uuid.clone(),
-    ).await;
-
-    let mut j = 0;
-    let mut party_shares: Vec<Scalar> = Vec::new();
-    for i in 1..=PARTIES {
-        if i == party_num_int {
-            party_shares.push(secret_shares[(i - 1) as usize].clone());
-        } else {
-            let aead_pack: AEAD = serde_json::from_str(&round3_ans_vec[j]).unwrap();
-            let key_i = &enc_keys[j];
-            let out = aes_decrypt(key_i, aead_pack);
-            let out_bn = BigInt::from_bytes_be(&out[..]);
-            let out_fe = ECScalar::from(&out_bn);
-            party_shares.push(out_fe);
+        console_log!("phase2_verify_pvss_construct_keypair_phase3_pok_dlog");
+        let (shared_keys, dlog_proof) = party_keys
+            .phase2_verify_pvss_construct_keypair_phase3_pok_dlog(
+                &params,
+                &point_vec,
+                &pvss_ek_vec,
+                &pvss_scheme_vec,
+                &(party_num_int as usize),
+                &pvss_dk,
+            )
+            .expect("invalid dealing");
+        let vss_scheme_vec = pvss_scheme_vec
+            .into_iter()
+            .map(|pvss_scheme| pvss_scheme.vss)
+            .collect::<Vec<VerifiableSS>>();
+        (shared_keys, dlog_proof, vss_scheme_vec)
+    } else {
+        console_log!("phase1_verify_com_phase3_verify_correct_key_phase2_distribute");
+        let (vss_scheme, secret_shares, _index) = party_keys
+            .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
+                &params, uuid.as_bytes(), &decom_vec, &bc1_vec,
+            )
+            .expect("invalid key");
+
+        //////////////////////////////////////////////////////////////////////////////
+
+        let mut j = 0;
+        for (k, i) in (1..=PARTIES).enumerate() {
+            if i != party_num_int {
+                // prepare encrypted ss for party i:
+                let key_i = &enc_keys[j];
+                let plaintext = BigInt::to_vec(&secret_shares[k].to_big_int());
+                let aead_pack_i = aes_encrypt(key_i, &plaintext);
+                assert!(sendp2p(
+                        &client,
+                        party_num_int,
+                        i,
+                        "round3",
+                        serde_json::to_string(&aead_pack_i).unwrap(),
+                        uuid.clone()
+                ).await.is_ok());
+                j += 1;
+            }
+        }
 
-            j += 1;
+        console_log!("poll_for_p2p");
+        let round3_ans_vec = poll_for_p2p(
+            &client,
+            party_num_int,
+            PARTIES,
+            //delay,
+            "round3",
+            uuid.clone(),
+        ).await;
+
+        let mut j = 0;
+        let mut party_shares: Vec<Scalar> = Vec::new();
+        for i in 1..=PARTIES {
+            if i == party_num_int {
+                party_shares.push(secret_shares[(i - 1) as usize].clone());
+            } else {
+                let aead_pack: AEAD = serde_json::from_str(&round3_ans_vec[j]).unwrap();
+                let key_i = &enc_keys[j];
+                let out = aes_decrypt(key_i, aead_pack);
+                let out_bn = BigInt::from_bytes_be(&out[..]);
+                let out_fe = ECScalar::from(&out_bn);
+                party_shares.push(out_fe);
+
+                j += 1;
+            }
         }
-    }
 
-    // round 4: send vss commitments
-    console_log!("broadcast round 4");
-    assert!(broadcast(
+        // round 4: send vss commitments
+        console_log!("broadcast round 4");
+        assert!(broadcast(
+                &client,
+                party_num_int,
+                "round4",
+                serde_json::to_string(&vss_scheme).unwrap(),
+                uuid.clone()
+        ).await.is_ok());
+        console_log!("poll_for_broadcasts round 4");
+        let round4_ans_vec = poll_for_broadcasts(
             &client,
             party_num_int,
+            PARTIES,
+            //delay,
             "round4",
-            serde_json::to_string(&vss_scheme).unwrap(),
-            uuid.clone()
-    ).await.is_ok());
-    console_log!("poll_for_broadcasts round 4");
-    let round4_ans_vec = poll_for_broadcasts(
-        &client,
-        party_num_int,
-        PARTIES,
-        //delay,
-        "round4",
-        uuid.clone(),
-    ).await;
-
-    let mut j = 0;
-    let mut vss_scheme_vec: Vec<VerifiableSS> = Vec::new();
-    for i in 1..=PARTIES {
-        if i == party_num_int {
-            vss_scheme_vec.push(vss_scheme.clone());
-        } else {
-            let vss_scheme_j: VerifiableSS =
-                serde_json::from_str(&round4_ans_vec[j]).unwrap();
-            vss_scheme_vec.push(vss_scheme_j);
-            j += 1;
+            uuid.clone(),
+        ).await;
+
+        let mut j = 0;
+        let mut vss_scheme_vec: Vec<VerifiableSS> = Vec::new();
+        for i in 1..=PARTIES {
+            if i == party_num_int {
+                vss_scheme_vec.push(vss_scheme.clone());
+            } else {
+                let vss_scheme_j: VerifiableSS =
+                    serde_json::from_str(&round4_ans_vec[j]).unwrap();
+                vss_scheme_vec.push(vss_scheme_j);
+                j += 1;
+            }
         }
-    }
 
-    console_log!("phase2_verify_vss_construct_keypair_phase3_pok_dlog");
-    let (shared_keys, dlog_proof) = party_keys
-        .phase2_verify_vss_construct_keypair_phase3_pok_dlog(
-            &params,
-            &point_vec,
-            &party_shares,
-            &vss_scheme_vec,
-            &(party_num_int.clone() as usize), // FIXME
-        )
-        .expect("invalid vss");
+        console_log!("phase2_verify_vss_construct_keypair_phase3_pok_dlog");
+        let (shared_keys, dlog_proof) = party_keys
+            .phase2_verify_vss_construct_keypair_phase3_pok_dlog(
+                &params,
+                &point_vec,
+                &party_shares,
+                &vss_scheme_vec,
+                &(party_num_int.clone() as usize), // FIXME
+            )
+            .expect("invalid vss");
+        (shared_keys, dlog_proof, vss_scheme_vec)
+    };
 
     // round 5: send dlog proof
     assert!(broadcast(
@@ -279,12 +439,171 @@ pub async fn gg18_keygen(t: usize, n: usize, save_path: String) -> String {
 }
 
 #[wasm_bindgen]
-pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: String) -> String {
-    let message = match hex::decode(message_str.clone()) {
-        Ok(x) => x,
-        Err(_e) => message_str.as_bytes().to_vec(),
+pub async fn gg18_sign(
+    t: usize,
+    n: usize,
+    key_store: String,
+    message_str: String,
+    hash_mode: String,
+) -> Result<String, JsValue> {
+    // the message is always hex encoded, `hash_mode` picks how it is turned into the signed digest
+    let message = hex::decode(message_str.trim_start_matches("0x"))
+        .map_err(|_| JsValue::from_str("message is not hex"))?;
+    let hash_mode: MessageHashMode = hash_mode
+        .parse()
+        .map_err(|_| JsValue::from_str("unknown hash mode"))?;
+    let message_bn = hash_mode
+        .hash(&message)
+        .map_err(|_| JsValue::from_str("invalid message for hash mode"))?;
+
+    let sig = sign_message_bn(t, n, key_store, message_bn).await;
+
+    let sign_json = serde_json::to_string(&(
+        "r",
+        BigInt::from_bytes_be(sig.r.to_big_int().to_bytes_be().as_ref()).to_str_radix(16),
+        "s",
+        BigInt::from_bytes_be(sig.s.to_big_int().to_bytes_be().as_ref()).to_str_radix(16),
+        "recid",
+        sig.recid,
+    ))
+    .unwrap();
+
+    //fs::write("signature".to_string(), sign_json).expect("Unable to save !");
+    Ok(sign_json)
+}
+
+/// Signs an Ethereum transaction given as JSON (see `ethereum::Transaction`) and
+/// returns the `0x` prefixed raw signed transaction.
+///
+/// Fails on a malformed transaction, including a `to` that is not a 20 byte address, and
+/// when the signature's R.x overflowed the group order; signing again is then needed.
+#[wasm_bindgen]
+pub async fn gg18_sign_eth_tx(
+    t: usize,
+    n: usize,
+    key_store: String,
+    tx_json: String,
+) -> Result<String, JsValue> {
+    let tx: Transaction = serde_json::from_str(&tx_json)
+        .map_err(|e| JsValue::from_str(&format!("invalid transaction: {}", e)))?;
+    let message_bn = MessageHashMode::Prehashed
+        .hash(&tx.signing_hash())
+        .map_err(|_| JsValue::from_str("invalid signing hash"))?;
+
+    let sig = sign_message_bn(t, n, key_store, message_bn).await;
+
+    let raw_tx = tx
+        .encode_signed(&sig)
+        .map_err(|_| JsValue::from_str("R.x overflowed the group order, sign again"))?;
+    Ok(format!("0x{}", hex::encode(raw_tx)))
+}
+
+/// EIP-55 address of the shared key stored in `key_store`.
+#[wasm_bindgen]
+pub fn gg18_eth_address(key_store: String) -> Result<String, JsValue> {
+    Ok(to_checksum_address(&public_key_address(&read_y_sum(&key_store)?)))
+}
+
+/// Signs input `input_index` of the unsigned hex transaction `raw_tx`. `kind` is
+/// "p2wpkh" (spending `value` satoshis) or "p2pkh". Inputs are signed with SIGHASH_ALL.
+///
+/// Returns `{ "script_sig": .., "witness": [..] }` with hex encoded fields, whatever the input
+/// kind: a p2wpkh input has an empty scriptSig and a p2pkh input an empty witness.
+#[wasm_bindgen]
+pub async fn gg18_sign_btc_input(
+    t: usize,
+    n: usize,
+    key_store: String,
+    raw_tx: String,
+    input_index: usize,
+    value: u64,
+    kind: String,
+) -> Result<String, JsValue> {
+    let raw_tx = hex::decode(raw_tx).map_err(|_| JsValue::from_str("transaction is not hex"))?;
+    let tx = bitcoin::Transaction::from_bytes(&raw_tx)
+        .map_err(|_| JsValue::from_str("invalid transaction"))?;
+    if input_index >= tx.inputs.len() {
+        return Err(JsValue::from_str("input index out of range"));
+    }
+    let y_sum = read_y_sum(&key_store)?;
+    let script_code = bitcoin::p2pkh_script_code(&bitcoin::hash160(
+        &bitcoin::compressed_public_key(&y_sum),
+    ));
+    let sighash = match kind.as_str() {
+        "p2wpkh" => tx.bip143_sighash(input_index, &script_code, value, bitcoin::SIGHASH_ALL),
+        "p2pkh" => tx.legacy_sighash(input_index, &script_code, bitcoin::SIGHASH_ALL),
+        _ => return Err(JsValue::from_str("unknown input kind")),
     };
-    let message = &message[..];
+    let message_bn = MessageHashMode::Prehashed
+        .hash(&sighash)
+        .map_err(|_| JsValue::from_str("invalid signing hash"))?;
+
+    let sig = sign_message_bn(t, n, key_store, message_bn).await;
+
+    let signed = if kind == "p2wpkh" {
+        SignedBtcInput {
+            script_sig: String::new(),
+            witness: bitcoin::p2wpkh_witness(&sig, bitcoin::SIGHASH_ALL, &y_sum)
+                .iter()
+                .map(hex::encode)
+                .collect(),
+        }
+    } else {
+        SignedBtcInput {
+            script_sig: hex::encode(bitcoin::p2pkh_script_sig(&sig, bitcoin::SIGHASH_ALL, &y_sum)),
+            witness: Vec::new(),
+        }
+    };
+    Ok(serde_json::to_string(&signed).unwrap())
+}
+
+#[derive(Serialize)]
+struct SignedBtcInput {
+    script_sig: String,
+    witness: Vec<String>,
+}
+
+/// "p2wpkh" or "p2pkh" address of the shared key on "mainnet" or "testnet".
+#[wasm_bindgen]
+pub fn gg18_btc_address(key_store: String, kind: String, network: String) -> Result<String, JsValue> {
+    let y_sum = read_y_sum(&key_store)?;
+    let network = match network.as_str() {
+        "mainnet" => bitcoin::Network::Mainnet,
+        "testnet" => bitcoin::Network::Testnet,
+        _ => return Err(JsValue::from_str("unknown network")),
+    };
+    match kind.as_str() {
+        "p2wpkh" => Ok(bitcoin::p2wpkh_address(&y_sum, network)),
+        "p2pkh" => Ok(bitcoin::p2pkh_address(&y_sum, network)),
+        _ => Err(JsValue::from_str("unknown address kind")),
+    }
+}
+
+fn read_y_sum(key_store: &str) -> Result<Point, JsValue> {
+    let (_, _, _, _, _, y_sum): (
+        Keys,
+        SharedKeys,
+        u16,
+        Vec<VerifiableSS>,
+        Vec<EncryptionKey>,
+        Point,
+    ) = serde_json::from_str(key_store)
+        .map_err(|e| JsValue::from_str(&format!("invalid key store: {}", e)))?;
+    Ok(y_sum)
+}
+
+/// Transcript of the MtA messages sent by the signer holding key share `index`.
+fn mta_transcript(session_id: &str, index: usize) -> Transcript {
+    Transcript::for_party(b"gg18/sign/mta", session_id.as_bytes(), index as u64)
+}
+
+/// Runs the signing rounds on an already hashed message.
+async fn sign_message_bn(
+    t: usize,
+    _n: usize,
+    key_store: String,
+    message_bn: BigInt,
+) -> Signature {
     let client = Client::new();
     // delay:
     //let delay = time::Duration::from_millis(25);
@@ -353,7 +672,13 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     let xi_com_vec = Keys::get_commitments_to_xi(&vss_scheme_vec);
     //////////////////////////////////////////////////////////////////////////////
     let (com, decommit) = sign_keys.phase1_broadcast();
-    let (m_a_k, _) = MessageA::a(&sign_keys.k_i, &party_keys.ek, &[]);
+    let own_index = signers_vec[usize::from(party_num_int - 1)];
+    let (m_a_k, _) = MessageA::a(
+        &mta_transcript(&uuid, own_index),
+        &sign_keys.k_i,
+        &party_keys.ek,
+        &[],
+    );
     assert!(broadcast(
         &client,
         party_num_int,
@@ -401,7 +726,11 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     let mut j = 0;
     for i in 1..THRESHOLD + 2 {
         if i != party_num_int {
+            let alice_transcript = mta_transcript(&uuid, signers_vec[usize::from(i - 1)]);
+            let bob_transcript = mta_transcript(&uuid, own_index);
             let (m_b_gamma, beta_gamma, _, _) = MessageB::b(
+                &alice_transcript,
+                &bob_transcript,
                 &sign_keys.gamma_i,
                 &paillier_key_vector[usize::from(signers_vec[usize::from(i - 1)])],
                 m_a_vec[j].clone(),
@@ -409,6 +738,8 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
             )
             .unwrap();
             let (m_b_w, beta_wi, _, _) = MessageB::b(
+                &alice_transcript,
+                &bob_transcript,
                 &sign_keys.w_i,
                 &paillier_key_vector[usize::from(signers_vec[usize::from(i - 1)])],
                 m_a_vec[j].clone(),
@@ -469,14 +800,15 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     let mut j = 0;
     for i in 1..THRESHOLD + 2 {
         if i != party_num_int {
+            let bob_transcript = mta_transcript(&uuid, signers_vec[usize::from(i - 1)]);
             let m_b = m_b_gamma_rec_vec[j].clone();
 
             let alpha_ij_gamma = m_b
-                .verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)
+                .verify_proofs_get_alpha(&bob_transcript, &party_keys.dk, &sign_keys.k_i)
                 .expect("wrong dlog or m_b");
             let m_b = m_b_w_rec_vec[j].clone();
             let alpha_ij_wi = m_b
-                .verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)
+                .verify_proofs_get_alpha(&bob_transcript, &party_keys.dk, &sign_keys.k_i)
                 .expect("wrong dlog or m_b");
             alpha_vec.push(alpha_ij_gamma.0);
             miu_vec.push(alpha_ij_wi.0);
@@ -517,7 +849,7 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
         delta_i,
         &mut delta_vec,
     );
-    let delta_inv = SignKeys::phase3_reconstruct_delta(&delta_vec);
+    let delta_inv = SignKeys::<Point>::phase3_reconstruct_delta(&delta_vec);
 
     
     console_log!("round3 is OK");
@@ -564,14 +896,12 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     // adding local g_gamma_i
     let R = R + decomm_i.g_gamma_i * delta_inv;
 
-    // we assume the message is already hashed (by the signer).
-    let message_bn = BigInt::from_bytes_be(message);
     let local_sig =
         LocalSignature::phase5_local_sig(&sign_keys.k_i, &message_bn, &R, &sigma, &y_sum);
     console_log!("phase5_local_sig");
 
     let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
-        local_sig.phase5a_broadcast_5b_zkproof();
+        local_sig.phase5a_broadcast_5b_zkproof(uuid.as_bytes(), own_index);
     console_log!("phase5a_broadcast_5b_zkproof");
 
     //phase (5A)  broadcast commit
@@ -652,8 +982,12 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     let phase_5a_dlog_vec = (0..THRESHOLD)
         .map(|i| decommit5a_and_elgamal_and_dlog_vec[i as usize].2.clone())
         .collect::<Vec<DLogProof>>();
+    let mut other_indices = signers_vec.clone();
+    other_indices.remove(usize::from(party_num_int - 1));
     let (phase5_com2, phase_5d_decom2) = local_sig
         .phase5c(
+            uuid.as_bytes(),
+            &other_indices,
             &phase_5a_decomm_vec,
             &commit5a_vec,
             &phase_5a_elgamal_vec,
@@ -758,16 +1092,7 @@ pub async fn gg18_sign(t: usize, n: usize, key_store: String,  message_str: Stri
     println!("recid: {:?} \n", sig.recid.clone());
     */
 
-    let sign_json = serde_json::to_string(&(
-        "r",
-        BigInt::from_bytes_be(sig.r.to_big_int().to_bytes_be().as_ref()).to_str_radix(16),
-        "s",
-        BigInt::from_bytes_be(sig.s.to_big_int().to_bytes_be().as_ref()).to_str_radix(16),
-    ))
-    .unwrap();
-
-    //fs::write("signature".to_string(), sign_json).expect("Unable to save !");
-    sign_json
+    sig
 }
 
 fn format_vec_from_reads<'a, T: serde::Deserialize<'a> + Clone>(
@@ -0,0 +1,478 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Bitcoin inputs signed with the threshold key: legacy and BIP143 (segwit v0)
+//! sighash computation, P2PKH / P2WPKH addresses of the compressed `y_sum`, and
+//! the scriptSig / witness items once `gg_2018` produced the signature.
+
+use bech32::{ToBase32, Variant};
+use ripemd160::{Digest, Ripemd160};
+
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::ECPoint;
+use crate::gg_2018::message::sha256;
+use crate::gg_2018::party_i::Signature;
+use crate::Error::{self, InvalidMessage};
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+const OP_DUP: u8 = 0x76;
+const OP_HASH160: u8 = 0xa9;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_CHECKSIG: u8 = 0xac;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    fn p2pkh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet => 0x6f,
+        }
+    }
+
+    fn bech32_hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TxIn {
+    /// previous transaction id in internal (little-endian) byte order
+    pub prev_txid: Vec<u8>,
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TxOut {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Transaction {
+    pub version: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+impl Transaction {
+    /// Parses the non-witness serialization of an (unsigned) transaction.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Transaction, Error> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let version = reader.u32()?;
+        let input_count = reader.var_int()?;
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let prev_txid = reader.take(32)?.to_vec();
+            let prev_vout = reader.u32()?;
+            let script_len = reader.var_int()? as usize;
+            let script_sig = reader.take(script_len)?.to_vec();
+            let sequence = reader.u32()?;
+            inputs.push(TxIn {
+                prev_txid,
+                prev_vout,
+                script_sig,
+                sequence,
+            });
+        }
+        let output_count = reader.var_int()?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            let value = reader.u64()?;
+            let script_len = reader.var_int()? as usize;
+            let script_pubkey = reader.take(script_len)?.to_vec();
+            outputs.push(TxOut {
+                value,
+                script_pubkey,
+            });
+        }
+        let lock_time = reader.u32()?;
+        if reader.pos != bytes.len() {
+            return Err(InvalidMessage);
+        }
+        Ok(Transaction {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    /// Non-witness serialization.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.version.to_le_bytes());
+        write_var_int(&mut out, self.inputs.len() as u64);
+        for input in &self.inputs {
+            write_outpoint(&mut out, input);
+            write_var_bytes(&mut out, &input.script_sig);
+            out.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        write_var_int(&mut out, self.outputs.len() as u64);
+        for output in &self.outputs {
+            write_output(&mut out, output);
+        }
+        out.extend_from_slice(&self.lock_time.to_le_bytes());
+        out
+    }
+
+    /// Original (pre-segwit) signature hash of input `input_index`, `script_code` being the
+    /// script of the output it spends.
+    pub fn legacy_sighash(&self, input_index: usize, script_code: &[u8], sighash_type: u32) -> Vec<u8> {
+        assert!(input_index < self.inputs.len());
+        let base_type = sighash_type & 0x1f;
+        if base_type == SIGHASH_SINGLE && input_index >= self.outputs.len() {
+            // consensus quirk: the "hash" signed is the integer one
+            let mut one = vec![0u8; 32];
+            one[0] = 1;
+            return one;
+        }
+
+        let mut tx = self.clone();
+        for (i, input) in tx.inputs.iter_mut().enumerate() {
+            input.script_sig = if i == input_index {
+                script_code.to_vec()
+            } else {
+                vec![]
+            };
+            if i != input_index && (base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE) {
+                input.sequence = 0;
+            }
+        }
+        if base_type == SIGHASH_NONE {
+            tx.outputs.clear();
+        } else if base_type == SIGHASH_SINGLE {
+            tx.outputs.truncate(input_index + 1);
+            for output in tx.outputs.iter_mut().take(input_index) {
+                output.value = u64::MAX;
+                output.script_pubkey.clear();
+            }
+        }
+        if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+            tx.inputs = vec![tx.inputs[input_index].clone()];
+        }
+
+        let mut preimage = tx.to_bytes();
+        preimage.extend_from_slice(&sighash_type.to_le_bytes());
+        sha256(&sha256(&preimage))
+    }
+
+    /// BIP143 signature hash of segwit v0 input `input_index` spending `value` satoshis.
+    /// For P2WPKH `script_code` is `p2pkh_script_code` of the key hash.
+    pub fn bip143_sighash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        value: u64,
+        sighash_type: u32,
+    ) -> Vec<u8> {
+        assert!(input_index < self.inputs.len());
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let zero = vec![0u8; 32];
+
+        let hash_prevouts = if !anyone_can_pay {
+            let mut prevouts = Vec::new();
+            for input in &self.inputs {
+                write_outpoint(&mut prevouts, input);
+            }
+            sha256(&sha256(&prevouts))
+        } else {
+            zero.clone()
+        };
+        let hash_sequence =
+            if !anyone_can_pay && base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+                let mut sequences = Vec::new();
+                for input in &self.inputs {
+                    sequences.extend_from_slice(&input.sequence.to_le_bytes());
+                }
+                sha256(&sha256(&sequences))
+            } else {
+                zero.clone()
+            };
+        let hash_outputs = if base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+            let mut outputs = Vec::new();
+            for output in &self.outputs {
+                write_output(&mut outputs, output);
+            }
+            sha256(&sha256(&outputs))
+        } else if base_type == SIGHASH_SINGLE && input_index < self.outputs.len() {
+            let mut output = Vec::new();
+            write_output(&mut output, &self.outputs[input_index]);
+            sha256(&sha256(&output))
+        } else {
+            zero
+        };
+
+        let input = &self.inputs[input_index];
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&self.version.to_le_bytes());
+        preimage.extend_from_slice(&hash_prevouts);
+        preimage.extend_from_slice(&hash_sequence);
+        write_outpoint(&mut preimage, input);
+        write_var_bytes(&mut preimage, script_code);
+        preimage.extend_from_slice(&value.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&hash_outputs);
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&sighash_type.to_le_bytes());
+        sha256(&sha256(&preimage))
+    }
+}
+
+/// 33 byte SEC1 compressed encoding, the only form used by segwit.
+pub fn compressed_public_key(public_key: &GE) -> Vec<u8> {
+    public_key.to_bytes(true)
+}
+
+/// RIPEMD-160(SHA-256(data))
+pub fn hash160(data: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(&sha256(data)).to_vec()
+}
+
+/// `OP_DUP OP_HASH160 <pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG`, both the P2PKH output script
+/// and the BIP143 script code of a P2WPKH input.
+pub fn p2pkh_script_code(pubkey_hash: &[u8]) -> Vec<u8> {
+    let mut script = vec![OP_DUP, OP_HASH160, pubkey_hash.len() as u8];
+    script.extend_from_slice(pubkey_hash);
+    script.push(OP_EQUALVERIFY);
+    script.push(OP_CHECKSIG);
+    script
+}
+
+pub fn p2pkh_address(public_key: &GE, network: Network) -> String {
+    let mut payload = vec![network.p2pkh_version()];
+    payload.extend_from_slice(&hash160(&compressed_public_key(public_key)));
+    let checksum = sha256(&sha256(&payload));
+    payload.extend_from_slice(&checksum[..4]);
+    bs58::encode(payload).into_string()
+}
+
+pub fn p2wpkh_address(public_key: &GE, network: Network) -> String {
+    let program = hash160(&compressed_public_key(public_key));
+    let mut data = vec![bech32::u5::try_from_u8(0).unwrap()];
+    data.extend_from_slice(&program.to_base32());
+    bech32::encode(network.bech32_hrp(), data, Variant::Bech32).unwrap()
+}
+
+/// DER signature followed by the sighash type byte, as pushed on the stack.
+pub fn encode_signature(sig: &Signature, sighash_type: u32) -> Vec<u8> {
+    let mut encoded = sig.to_der();
+    encoded.push(sighash_type as u8);
+    encoded
+}
+
+/// Witness stack `[signature, pubkey]` of a P2WPKH input.
+pub fn p2wpkh_witness(sig: &Signature, sighash_type: u32, public_key: &GE) -> Vec<Vec<u8>> {
+    vec![
+        encode_signature(sig, sighash_type),
+        compressed_public_key(public_key),
+    ]
+}
+
+/// scriptSig `<signature> <pubkey>` of a P2PKH input.
+pub fn p2pkh_script_sig(sig: &Signature, sighash_type: u32, public_key: &GE) -> Vec<u8> {
+    let mut script = Vec::new();
+    let sig = encode_signature(sig, sighash_type);
+    script.push(sig.len() as u8);
+    script.extend_from_slice(&sig);
+    let public_key = compressed_public_key(public_key);
+    script.push(public_key.len() as u8);
+    script.extend_from_slice(&public_key);
+    script
+}
+
+fn write_outpoint(out: &mut Vec<u8>, input: &TxIn) {
+    out.extend_from_slice(&input.prev_txid);
+    out.extend_from_slice(&input.prev_vout.to_le_bytes());
+}
+
+fn write_output(out: &mut Vec<u8>, output: &TxOut) {
+    out.extend_from_slice(&output.value.to_le_bytes());
+    write_var_bytes(out, &output.script_pubkey);
+}
+
+fn write_var_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_var_int(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_var_int(out: &mut Vec<u8>, n: u64) {
+    if n < 0xfd {
+        out.push(n as u8);
+    } else if n <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(n as u16).to_le_bytes());
+    } else if n <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(n as u32).to_le_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&n.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() - self.pos < len {
+            return Err(InvalidMessage);
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let mut buf = [0u8; 2];
+        buf.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn var_int(&mut self) -> Result<u64, Error> {
+        match self.u8()? {
+            0xfd => Ok(self.u16()? as u64),
+            0xfe => Ok(self.u32()? as u64),
+            0xff => self.u64(),
+            n => Ok(n as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::arithmetic::num_bigint::{from, BigInt};
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+    use crate::curv::elliptic::curves::traits::ECScalar;
+    use crate::gg_2018::party_i::verify;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // the first bitcoin transaction between two people, block 170
+    const TX_170: &str = "0100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd3704000000004847304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d0901ffffffff0200ca9a3b00000000434104ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c1b7303b8a0626f1baded5c72a704f7e6cd84cac00286bee0000000043410411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3ac00000000";
+    const TX_170_PREV_SCRIPT: &str = "410411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3ac";
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_legacy_sighash_verifies_tx_170() {
+        let raw = hex::decode(TX_170).unwrap();
+        let tx = Transaction::from_bytes(&raw).unwrap();
+        assert_eq!(tx.to_bytes(), raw);
+
+        let script_code = hex::decode(TX_170_PREV_SCRIPT).unwrap();
+        let sighash = tx.legacy_sighash(0, &script_code, SIGHASH_ALL);
+        assert_eq!(
+            hex::encode(&sighash),
+            "7a05c6145f10101e9d6325494245adf1297d80f8f38d4d576d57cdba220bcb19"
+        );
+
+        // the signature in the scriptSig checks out against our sighash
+        let der = &tx.inputs[0].script_sig[1..71];
+        let r: FE = ECScalar::from(&from(&der[4..36]));
+        let s: FE = ECScalar::from(&from(&der[38..70]));
+        let pk: GE = ECPoint::from_coor(&from(&script_code[2..34]), &from(&script_code[34..66]));
+        let sig = Signature { r, s, recid: 0 };
+        assert!(verify(&sig, &pk, &from(&sighash[..])).is_ok());
+        assert_eq!(encode_signature(&sig, SIGHASH_ALL), &tx.inputs[0].script_sig[1..72]);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_bip143_native_p2wpkh() {
+        let raw = hex::decode("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").unwrap();
+        let tx = Transaction::from_bytes(&raw).unwrap();
+        let script_code = p2pkh_script_code(&hex::decode("1d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap());
+        let sighash = tx.bip143_sighash(1, &script_code, 600_000_000, SIGHASH_ALL);
+        assert_eq!(
+            hex::encode(sighash),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_sighash_single_without_output() {
+        let raw = hex::decode(TX_170).unwrap();
+        let mut tx = Transaction::from_bytes(&raw).unwrap();
+        tx.outputs.clear();
+        let sighash = tx.legacy_sighash(0, &[], SIGHASH_SINGLE);
+        assert_eq!(from(&sighash[..]), BigInt::from(1u32) << 248);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_addresses_of_generator() {
+        let g: GE = ECPoint::generator();
+        assert_eq!(
+            hex::encode(hash160(&compressed_public_key(&g))),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert_eq!(
+            p2pkh_address(&g, Network::Mainnet),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            p2wpkh_address(&g, Network::Mainnet),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_truncated_transaction() {
+        let raw = hex::decode(TX_170).unwrap();
+        assert_eq!(
+            Transaction::from_bytes(&raw[..raw.len() - 1]),
+            Err(InvalidMessage)
+        );
+    }
+}
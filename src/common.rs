@@ -7,7 +7,7 @@
 use crate::gg_2018::party_i::Signature;
 use aes_gcm::aead::{Aead, NewAead};
 use aes_gcm::{Aes256Gcm, Nonce};
-use rand::{rngs::OsRng, RngCore};
+use rand::{CryptoRng, RngCore};
 
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
@@ -16,7 +16,7 @@ use wasm_bindgen::JsValue;
 use crate::log;
 
 use crate::curv::{
-    arithmetic::traits::Converter,
+    arithmetic::traits::{os_rng, Converter},
     elliptic::curves::secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar},
     arithmetic::num_bigint::BigInt,
 };
@@ -61,11 +61,19 @@ pub struct Params {
 
 #[allow(dead_code)]
 pub fn aes_encrypt(key: &[u8], plaintext: &[u8]) -> AEAD {
+    aes_encrypt_with_rng(key, plaintext, &mut os_rng())
+}
+
+#[allow(dead_code)]
+pub fn aes_encrypt_with_rng(
+    key: &[u8],
+    plaintext: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> AEAD {
     let aes_key = aes_gcm::Key::from_slice(key);
     let cipher = Aes256Gcm::new(aes_key);
 
     let mut nonce = [0u8; 12];
-    let mut rng = OsRng::new().unwrap();
     rng.fill_bytes(&mut nonce);
     let nonce = Nonce::from_slice(&nonce);
 
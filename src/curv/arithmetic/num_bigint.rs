@@ -1,7 +1,7 @@
 use super::traits::*;
 use num_bigint::BigUint;
 use num_integer::Integer;
-use rand::thread_rng;
+use rand::{CryptoRng, RngCore};
 
 use num_bigint::BigInt as BN;
 use num_bigint::RandBigInt;
@@ -15,18 +15,19 @@ use std::ops::{BitAnd, BitOr, Shl};
 use num_bigint::Sign;
 
 impl Samplable for BigUint {
-    fn sample_below(upper: &Self) -> Self {
-        let mut rng = thread_rng();
+    fn sample_below_with_rng(upper: &Self, rng: &mut (impl RngCore + CryptoRng)) -> Self {
         rng.gen_biguint_below(upper)
     }
 
-    fn sample(bitsize: usize) -> Self {
-        let mut rng = thread_rng();
+    fn sample_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self {
         rng.gen_biguint(bitsize)
     }
 
-    fn sample_range(lower: &Self, upper: &Self) -> Self {
-        let mut rng = thread_rng();
+    fn sample_range_with_rng(
+        lower: &Self,
+        upper: &Self,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
         rng.gen_biguint_range(lower, upper)
     }
 }
@@ -16,6 +16,8 @@
 
 use std::marker::Sized;
 use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
 
 pub trait Converter {
     fn to_vec(n: &Self) -> Vec<u8>;
@@ -34,10 +36,27 @@ pub trait Modulo {
     fn mod_inv(a: &Self, modulus: &Self) -> Self;
 }
 
-pub trait Samplable {
-    fn sample_below(upper: &Self) -> Self;
-    fn sample_range(lower: &Self, upper: &Self) -> Self;
-    fn sample(bitsize: usize) -> Self;
+/// The operating system's random source, which every API not taking an RNG draws from.
+pub fn os_rng() -> OsRng {
+    OsRng::new().expect("the OS random source is unavailable")
+}
+
+pub trait Samplable: Sized {
+    fn sample_below(upper: &Self) -> Self {
+        Self::sample_below_with_rng(upper, &mut os_rng())
+    }
+
+    fn sample_range(lower: &Self, upper: &Self) -> Self {
+        Self::sample_range_with_rng(lower, upper, &mut os_rng())
+    }
+
+    fn sample(bitsize: usize) -> Self {
+        Self::sample_with_rng(bitsize, &mut os_rng())
+    }
+
+    fn sample_below_with_rng(upper: &Self, rng: &mut (impl RngCore + CryptoRng)) -> Self;
+    fn sample_range_with_rng(lower: &Self, upper: &Self, rng: &mut (impl RngCore + CryptoRng)) -> Self;
+    fn sample_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self;
 }
 
 pub trait NumberTests {
@@ -19,6 +19,7 @@ use crate::curv::arithmetic::traits::Converter;
 use crate::curv::arithmetic::traits::Samplable;
 use cryptoxide::digest::Digest;
 use cryptoxide::sha3::Sha3;
+use rand::{CryptoRng, RngCore};
 
 use crate::console_log;
 use crate::log;
@@ -41,8 +42,11 @@ impl Commitment<BigInt> for HashCommitment {
         from(result.as_ref())
     }
 
-    fn create_commitment(message: &BigInt) -> (BigInt, BigInt) {
-        let blinding_factor = BigInt::sample(SECURITY_BITS);
+    fn create_commitment_with_rng(
+        message: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (BigInt, BigInt) {
+        let blinding_factor = BigInt::sample_with_rng(SECURITY_BITS, rng);
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             message,
             &blinding_factor,
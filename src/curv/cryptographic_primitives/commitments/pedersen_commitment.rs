@@ -0,0 +1,122 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use std::marker::PhantomData;
+
+use super::traits::Commitment;
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::Samplable;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::*;
+use rand::{CryptoRng, RngCore};
+
+/// Pedersen commitment c = mG + rH, where G is the curve generator and H is
+/// `base_point2()`, whose discrete log relative to G is unknown. The commitment is perfectly
+/// hiding and computationally binding, and additively homomorphic:
+/// commit(m1, r1) + commit(m2, r2) = commit(m1 + m2, r1 + r2).
+///
+/// m and r are taken mod the group order q.
+pub struct PedersenCommitment<P: ECPoint = GE> {
+    _curve: PhantomData<P>,
+}
+
+impl<P: ECPoint> Commitment<P> for PedersenCommitment<P> {
+    fn create_commitment_with_user_defined_randomness(
+        message: &BigInt,
+        blinding_factor: &BigInt,
+    ) -> P {
+        let message_fe: P::Scalar = ECScalar::from(message);
+        let blinding_fe: P::Scalar = ECScalar::from(blinding_factor);
+        Self::commit_scalars(&message_fe, &blinding_fe)
+    }
+
+    fn create_commitment_with_rng(
+        message: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (P, BigInt) {
+        let blinding_factor = BigInt::sample_below_with_rng(&P::Scalar::q(), rng);
+        let com = Self::create_commitment_with_user_defined_randomness(message, &blinding_factor);
+        (com, blinding_factor)
+    }
+}
+
+impl<P: ECPoint> PedersenCommitment<P> {
+    /// mG + rH for messages and blinding factors that already live in the scalar field.
+    /// Both scalars are secret, so this takes two constant-time scalar multiplications
+    /// rather than the variable-time `multi_scalar_mul`.
+    pub fn commit_scalars(message: &P::Scalar, blinding_factor: &P::Scalar) -> P {
+        P::generator() * message.clone() + P::base_point2() * blinding_factor.clone()
+    }
+
+    /// Commitment to the sum of the committed messages. It opens with the sum of the
+    /// messages and the sum of the blinding factors.
+    pub fn add(lhs: &P, rhs: &P) -> P {
+        lhs.clone() + rhs.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PedersenCommitment;
+    use crate::curv::arithmetic::num_bigint::BigInt;
+    use crate::curv::arithmetic::traits::Samplable;
+    use crate::curv::cryptographic_primitives::commitments::traits::Commitment;
+    use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+    use crate::curv::elliptic::curves::traits::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_commitment_binds_message_and_randomness() {
+        let message = BigInt::sample_below(&FE::q());
+        let (com, blinding_factor): (GE, BigInt) = PedersenCommitment::create_commitment(&message);
+        let com2: GE = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &message,
+            &blinding_factor,
+        );
+        assert_eq!(com, com2);
+        let com3: GE = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &(message.clone() + 1u32),
+            &blinding_factor,
+        );
+        assert_ne!(com, com3);
+        let (com4, _): (GE, BigInt) = PedersenCommitment::create_commitment(&message);
+        assert_ne!(com, com4);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_commitment_is_homomorphic() {
+        let m1: FE = ECScalar::new_random();
+        let m2: FE = ECScalar::new_random();
+        let r1: FE = ECScalar::new_random();
+        let r2: FE = ECScalar::new_random();
+        let c1 = PedersenCommitment::<GE>::commit_scalars(&m1, &r1);
+        let c2 = PedersenCommitment::<GE>::commit_scalars(&m2, &r2);
+        let sum = PedersenCommitment::<GE>::add(&c1, &c2);
+        assert_eq!(
+            sum,
+            PedersenCommitment::<GE>::commit_scalars(&(m1 + m2), &(r1 + r2))
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_commitment_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let message = BigInt::from(42u32);
+        let (com, blinding_factor): (p256::GE, BigInt) =
+            PedersenCommitment::create_commitment(&message);
+        let g = p256::GE::generator();
+        let h = p256::GE::base_point2();
+        let m: p256::FE = ECScalar::from(&message);
+        let r: p256::FE = ECScalar::from(&blinding_factor);
+        assert_eq!(com, g * m + h * r);
+    }
+}
@@ -6,6 +6,8 @@
 */
 
 use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::os_rng;
+use rand::{CryptoRng, RngCore};
 
 pub trait Commitment<T> {
     fn create_commitment_with_user_defined_randomness(
@@ -13,5 +15,12 @@ pub trait Commitment<T> {
         blinding_factor: &BigInt,
     ) -> T;
 
-    fn create_commitment(message: &BigInt) -> (T, BigInt);
+    fn create_commitment(message: &BigInt) -> (T, BigInt) {
+        Self::create_commitment_with_rng(message, &mut os_rng())
+    }
+
+    fn create_commitment_with_rng(
+        message: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (T, BigInt);
 }
@@ -1,17 +1,20 @@
-use digest::Digest;
+use digest::generic_array::ArrayLength;
+use digest::{BlockInput, Digest, FixedOutput, Reset, Update};
 use hmac::crypto_mac::MacError;
 use hmac::{Hmac, Mac, NewMac};
 use typenum::Unsigned;
 
 use crate::curv::arithmetic::*;
-use crate::curv::elliptic::curves::secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar};
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 use crate::curv::arithmetic::num_bigint::*;
+use crate::curv::cryptographic_primitives::hashing::hash_to_curve::hash_to_scalar;
+
+const RESULT_SCALAR_DST: &[u8] = b"emerald-city/digest-ext/result-scalar";
 
 pub trait DigestExt {
     fn input_bigint(&mut self, n: &BigInt);
-    fn input_point(&mut self, point: &Point);
-    fn input_scalar(&mut self, scalar: &Scalar);
+    fn input_point<P: ECPoint>(&mut self, point: &P);
+    fn input_scalar<S: ECScalar>(&mut self, scalar: &S);
 
     fn chain_bigint(mut self, n: &BigInt) -> Self
     where
@@ -20,14 +23,14 @@ pub trait DigestExt {
         self.input_bigint(n);
         self
     }
-    fn chain_point(mut self, point: &Point) -> Self
+    fn chain_point<P: ECPoint>(mut self, point: &P) -> Self
     where
         Self: Sized,
     {
         self.input_point(point);
         self
     }
-    fn chain_points<'p>(mut self, points: impl IntoIterator<Item = &'p Point>) -> Self
+    fn chain_points<'p, P: ECPoint + 'p>(mut self, points: impl IntoIterator<Item = &'p P>) -> Self
     where
         Self: Sized,
     {
@@ -36,16 +39,16 @@ pub trait DigestExt {
         }
         self
     }
-    fn chain_scalar(mut self, scalar: &Scalar) -> Self
+    fn chain_scalar<S: ECScalar>(mut self, scalar: &S) -> Self
     where
         Self: Sized,
     {
         self.input_scalar(scalar);
         self
     }
-    fn chain_scalars<'s>(
+    fn chain_scalars<'s, S: ECScalar + 's>(
         mut self,
-        scalars: impl IntoIterator<Item = &'s Scalar>,
+        scalars: impl IntoIterator<Item = &'s S>,
     ) -> Self
     where
         Self: Sized,
@@ -57,7 +60,7 @@ pub trait DigestExt {
     }
 
     fn result_bigint(self) -> BigInt;
-    //fn result_scalar(self) -> Scalar;
+    fn result_scalar<S: ECScalar>(self) -> S;
 
     fn digest_bigint(bytes: &[u8]) -> BigInt;
 }
@@ -70,11 +73,11 @@ where
         self.update(&n.to_bytes_be())
     }
 
-    fn input_point(&mut self, point: &Point) {
+    fn input_point<P: ECPoint>(&mut self, point: &P) {
         self.update(&point.to_bytes(false)[..])
     }
 
-    fn input_scalar(&mut self, scalar: &Scalar) {
+    fn input_scalar<S: ECScalar>(&mut self, scalar: &S) {
         self.update(&scalar.to_big_int().to_bytes_be())
     }
 
@@ -83,33 +86,16 @@ where
         BigInt::from_bytes_be(&result)
     }
 
-    /*
-    fn result_scalar(self) -> Scalar {
-        let scalar_len = <dyn ECScalar>::ScalarLength as Unsigned>::to_usize();
-        assert!(
-            Self::output_size() >= scalar_len,
-            "Output size of the hash({}) is smaller than the scalar length({})",
-            Self::output_size(),
-            scalar_len
-        );
-        // Try and increment.
-        for i in 0u32.. {
-            let starting_state = self.clone();
-            let hash = starting_state.chain(i.to_be_bytes()).finalize();
-            if let Ok(scalar) = Scalar::from_bytes(&hash[..scalar_len]) {
-                return scalar;
-            }
-        }
-        unreachable!("The probably of this reaching is extremely small ((2^n-q)/(2^n))^(2^32)")
+    fn result_scalar<S: ECScalar>(self) -> S {
+        // same wide reduction as every other hash into the scalar field
+        hash_to_scalar(RESULT_SCALAR_DST, &self.finalize())
     }
-    */
 
     fn digest_bigint(bytes: &[u8]) -> BigInt {
         Self::new().chain(bytes).result_bigint()
     }
 }
 
-/*
 /// [Hmac] extension allowing to use bigints to instantiate hmac, update, and finalize it.
 pub trait HmacExt: Sized {
     fn new_bigint(key: &BigInt) -> Self;
@@ -130,23 +116,33 @@ pub trait HmacExt: Sized {
 
 impl<D> HmacExt for Hmac<D>
 where
-    D: digest::Update + digest::BlockInput + digest::FixedOutput + digest::Reset + Default + Clone,
+    D: Update + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
 {
     fn new_bigint(key: &BigInt) -> Self {
-        let bytes = key.to_byte();
+        let bytes = key.to_bytes_be();
         Self::new_from_slice(&bytes).expect("HMAC must take a key of any length")
     }
 
     fn input_bigint(&mut self, n: &BigInt) {
-        self.update(&n.to_bytes())
+        self.update(&n.to_bytes_be())
     }
 
     fn result_bigint(self) -> BigInt {
         BigInt::from_bytes_be(&self.finalize().into_bytes())
     }
 
+    /// The tag is compared in constant time. As a BigInt drops leading zero bytes, it is
+    /// padded back to the output size of the hash first.
     fn verify_bigint(self, code: &BigInt) -> Result<(), MacError> {
-        self.verify(&code.to_bytes())
+        let code_bytes = code.to_bytes_be();
+        let output_size = D::OutputSize::to_usize();
+        if code_bytes.len() > output_size {
+            return Err(MacError);
+        }
+        let mut padded = vec![0u8; output_size - code_bytes.len()];
+        padded.extend_from_slice(&code_bytes);
+        self.verify(&padded)
     }
 }
 
@@ -158,9 +154,15 @@ mod test {
     use sha2::{Sha256, Sha512};
 
     use super::*;
+    use crate::curv::arithmetic::traits::{Converter, Samplable};
+    use crate::curv::elliptic::curves::{p256, secp256_k1};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
 
     // Test Vectors taken from:
     // https://csrc.nist.gov/projects/cryptographic-algorithm-validation-program/secure-hashing#shavs
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn vector_sha256_test() {
         // Empty Message
@@ -172,12 +174,9 @@ mod test {
 
         // 256 bit message
         let result: BigInt = Sha256::new()
-            .chain_bigint(
-                &BigInt::from_hex(
-                    "09fc1accc230a205e4a208e64a8f204291f581a12756392da4b8c0cf5ef02b95",
-                )
-                .unwrap(),
-            )
+            .chain_bigint(&BigInt::from_hex(
+                "09fc1accc230a205e4a208e64a8f204291f581a12756392da4b8c0cf5ef02b95",
+            ))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -186,8 +185,8 @@ mod test {
 
         // 2x128 bit messages
         let result: BigInt = Sha256::new()
-            .chain_bigint(&BigInt::from_hex("09fc1accc230a205e4a208e64a8f2042").unwrap())
-            .chain_bigint(&BigInt::from_hex("91f581a12756392da4b8c0cf5ef02b95").unwrap())
+            .chain_bigint(&BigInt::from_hex("09fc1accc230a205e4a208e64a8f2042"))
+            .chain_bigint(&BigInt::from_hex("91f581a12756392da4b8c0cf5ef02b95"))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -196,7 +195,7 @@ mod test {
 
         // 512 bit message
         let result: BigInt = Sha256::new()
-            .chain_bigint(&BigInt::from_hex("5a86b737eaea8ee976a0a24da63e7ed7eefad18a101c1211e2b3650c5187c2a8a650547208251f6d4237e661c7bf4c77f335390394c37fa1a9f9be836ac28509").unwrap())
+            .chain_bigint(&BigInt::from_hex("5a86b737eaea8ee976a0a24da63e7ed7eefad18a101c1211e2b3650c5187c2a8a650547208251f6d4237e661c7bf4c77f335390394c37fa1a9f9be836ac28509"))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -204,6 +203,7 @@ mod test {
         );
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     // Test Vectors taken from:
     // https://csrc.nist.gov/projects/cryptographic-algorithm-validation-program/secure-hashing#shavs
@@ -217,18 +217,12 @@ mod test {
 
         // 2x256 bit message
         let result: BigInt = Sha512::new()
-            .chain_bigint(
-                &BigInt::from_hex(
-                    "c1ca70ae1279ba0b918157558b4920d6b7fba8a06be515170f202fafd36fb7f7",
-                )
-                .unwrap(),
-            )
-            .chain_bigint(
-                &BigInt::from_hex(
-                    "9d69fad745dba6150568db1e2b728504113eeac34f527fc82f2200b462ecbf5d",
-                )
-                .unwrap(),
-            )
+            .chain_bigint(&BigInt::from_hex(
+                "c1ca70ae1279ba0b918157558b4920d6b7fba8a06be515170f202fafd36fb7f7",
+            ))
+            .chain_bigint(&BigInt::from_hex(
+                "9d69fad745dba6150568db1e2b728504113eeac34f527fc82f2200b462ecbf5d",
+            ))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -238,7 +232,7 @@ mod test {
         // 512 bit message
         let result: BigInt = Sha512::new()
             .chain_bigint(&BigInt::from_hex(
-                "c1ca70ae1279ba0b918157558b4920d6b7fba8a06be515170f202fafd36fb7f79d69fad745dba6150568db1e2b728504113eeac34f527fc82f2200b462ecbf5d").unwrap())
+                "c1ca70ae1279ba0b918157558b4920d6b7fba8a06be515170f202fafd36fb7f79d69fad745dba6150568db1e2b728504113eeac34f527fc82f2200b462ecbf5d"))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -247,7 +241,7 @@ mod test {
 
         // 1024 bit message
         let result: BigInt = Sha512::new()
-            .chain_bigint(&BigInt::from_hex("fd2203e467574e834ab07c9097ae164532f24be1eb5d88f1af7748ceff0d2c67a21f4e4097f9d3bb4e9fbf97186e0db6db0100230a52b453d421f8ab9c9a6043aa3295ea20d2f06a2f37470d8a99075f1b8a8336f6228cf08b5942fc1fb4299c7d2480e8e82bce175540bdfad7752bc95b577f229515394f3ae5cec870a4b2f8").unwrap())
+            .chain_bigint(&BigInt::from_hex("fd2203e467574e834ab07c9097ae164532f24be1eb5d88f1af7748ceff0d2c67a21f4e4097f9d3bb4e9fbf97186e0db6db0100230a52b453d421f8ab9c9a6043aa3295ea20d2f06a2f37470d8a99075f1b8a8336f6228cf08b5942fc1fb4299c7d2480e8e82bce175540bdfad7752bc95b577f229515394f3ae5cec870a4b2f8"))
             .result_bigint();
         assert_eq!(
             result.to_hex(),
@@ -255,28 +249,35 @@ mod test {
         );
     }
 
-    crate::test_for_all_curves_and_hashes!(create_hash_from_ge_test);
-    fn create_hash_from_ge_test<E: Curve, H: Digest + Clone>() {
-        let generator = Point::<E>::generator();
-        let base_point2 = Point::<E>::base_point2();
-        let result1 = H::new()
+    fn create_hash_from_ge_test<P: ECPoint, H: Digest + Clone>() {
+        let generator = P::generator();
+        let base_point2 = P::base_point2();
+        let result1: P::Scalar = H::new()
             .chain_point(&generator)
-            .chain_point(base_point2)
-            .result_scalar::<E>();
-        assert!(result1.to_bigint().bit_length() > 240);
-        let result2 = H::new()
-            .chain_point(base_point2)
+            .chain_point(&base_point2)
+            .result_scalar();
+        assert!(result1.to_big_int().bits() > 240);
+        let result2: P::Scalar = H::new()
+            .chain_point(&base_point2)
             .chain_point(&generator)
-            .result_scalar::<E>();
+            .result_scalar();
         assert_ne!(result1, result2);
-        let result3 = H::new()
-            .chain_point(base_point2)
+        let result3: P::Scalar = H::new()
+            .chain_point(&base_point2)
             .chain_point(&generator)
-            .result_scalar::<E>();
+            .result_scalar();
         assert_eq!(result2, result3);
     }
 
-    crate::test_for_all_hashes!(create_hmac_test);
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hash_from_ge_all_curves_and_hashes() {
+        create_hash_from_ge_test::<secp256_k1::GE, Sha256>();
+        create_hash_from_ge_test::<secp256_k1::GE, Sha512>();
+        create_hash_from_ge_test::<p256::GE, Sha256>();
+        create_hash_from_ge_test::<p256::GE, Sha512>();
+    }
+
     fn create_hmac_test<H>()
     where
         H: Update + BlockInput + FixedOutput + Reset + Default + Clone,
@@ -285,30 +286,56 @@ mod test {
     {
         let key = BigInt::sample(512);
         let result1 = Hmac::<H>::new_bigint(&key)
-            .chain_bigint(&BigInt::from(10))
+            .chain_bigint(&BigInt::from(10u32))
             .result_bigint();
         assert!(Hmac::<H>::new_bigint(&key)
-            .chain_bigint(&BigInt::from(10))
+            .chain_bigint(&BigInt::from(10u32))
             .verify_bigint(&result1)
             .is_ok());
+        assert!(Hmac::<H>::new_bigint(&key)
+            .chain_bigint(&BigInt::from(10u32))
+            .verify_bigint(&(result1.clone() + 1u32))
+            .is_err());
 
         let key2 = BigInt::sample(512);
         // same data , different key
         let result2 = Hmac::<H>::new_bigint(&key2)
-            .chain_bigint(&BigInt::from(10))
+            .chain_bigint(&BigInt::from(10u32))
             .result_bigint();
         assert_ne!(result1, result2);
         // same key , different data
         let result3 = Hmac::<H>::new_bigint(&key)
-            .chain_bigint(&BigInt::from(10))
-            .chain_bigint(&BigInt::from(11))
+            .chain_bigint(&BigInt::from(10u32))
+            .chain_bigint(&BigInt::from(11u32))
             .result_bigint();
         assert_ne!(result1, result3);
         // same key, same data
         let result4 = Hmac::<H>::new_bigint(&key)
-            .chain_bigint(&BigInt::from(10))
+            .chain_bigint(&BigInt::from(10u32))
             .result_bigint();
         assert_eq!(result1, result4)
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hmac_all_hashes() {
+        create_hmac_test::<Sha256>();
+        create_hmac_test::<Sha512>();
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn hmac_matches_keyed_hash() {
+        use crate::curv::cryptographic_primitives::hashing::hmac_sha512::HMacSha512;
+        use crate::curv::cryptographic_primitives::hashing::traits::KeyedHash;
+
+        let key = BigInt::sample(256);
+        let data = [BigInt::sample(256), BigInt::sample(256)];
+        let expected = HMacSha512::create_hmac(&key, &[&data[0], &data[1]]);
+        let result = Hmac::<Sha512>::new_bigint(&key)
+            .chain_bigint(&data[0])
+            .chain_bigint(&data[1])
+            .result_bigint();
+        assert_eq!(result, expected);
+    }
 }
-*/
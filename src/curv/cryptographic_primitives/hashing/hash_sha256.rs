@@ -9,7 +9,6 @@ use super::traits::Hash;
 use cryptoxide::digest::Digest;
 use cryptoxide::sha2::Sha256;
 use crate::curv::arithmetic::num_bigint::{from, BigInt};
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
 use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
 
 pub struct HSha256;
@@ -28,7 +27,7 @@ impl Hash for HSha256 {
         from(result.as_ref())
     }
 
-    fn create_hash_from_ge(ge_vec: &[&GE]) -> FE {
+    fn create_hash_from_ge<P: ECPoint>(ge_vec: &[&P]) -> P::Scalar {
         let mut hasher = Sha256::new();
 
         for value in ge_vec {
@@ -38,7 +37,7 @@ impl Hash for HSha256 {
         let mut result = [0; 32];
         hasher.result(&mut result);
         let result = from(result.as_ref());
-        ECScalar::from(&result)
+        <P::Scalar as ECScalar>::from(&result)
     }
 }
 
@@ -76,4 +75,15 @@ mod tests {
         let result3 = HSha256::create_hash_from_ge(&vec![&GE::generator(), &point]);
         assert_eq!(result2, result3);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hash_from_ge_p256_test() {
+        use crate::curv::elliptic::curves::p256;
+        let point = p256::GE::base_point2();
+        let result1 = HSha256::create_hash_from_ge(&[&point, &p256::GE::generator()]);
+        let result2 = HSha256::create_hash_from_ge(&[&p256::GE::generator(), &point]);
+        assert_ne!(result1, result2);
+        assert!(result1.to_big_int() < p256::FE::q());
+    }
 }
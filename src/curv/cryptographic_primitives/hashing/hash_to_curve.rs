@@ -0,0 +1,162 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Domain separated hashing into the scalar field and onto the curve.
+//!
+//! Both functions sit on `expand_message_xmd` (RFC 9380, section 5.3.1) instantiated with
+//! SHA-256. The domain separation tag `dst` names the protocol and the purpose of the value,
+//! e.g. `b"emerald-city/dlog-proof/challenge"`, so that no two uses can collide.
+
+use digest::Digest;
+use sha2::Sha256;
+use typenum::Unsigned;
+
+use crate::curv::arithmetic::num_bigint::from;
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+
+const SHA256_OUTPUT_LEN: usize = 32;
+const SHA256_BLOCK_LEN: usize = 64;
+/// Extra bytes drawn beyond the scalar length, making the bias of the reduction mod q
+/// at most 2^-128.
+const WIDE_REDUCTION_EXTRA_BYTES: usize = 16;
+
+/// `expand_message_xmd` with SHA-256: derives `len` pseudo-random bytes from `msg`,
+/// bound to `dst`. Panics if `dst` is longer than 255 bytes or `len` exceeds 255 * 32.
+pub fn expand_message_xmd(dst: &[u8], msg: &[u8], len: usize) -> Vec<u8> {
+    assert!(dst.len() <= 255, "domain separation tag longer than 255 bytes");
+    let ell = len.div_ceil(SHA256_OUTPUT_LEN);
+    assert!(ell <= 255, "requested output too long for expand_message_xmd");
+
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+
+    let b_0 = Sha256::new()
+        .chain(&[0u8; SHA256_BLOCK_LEN][..])
+        .chain(msg)
+        .chain((len as u16).to_be_bytes())
+        .chain([0u8])
+        .chain(&dst_prime)
+        .finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * SHA256_OUTPUT_LEN);
+    let mut b_i = Sha256::new()
+        .chain(b_0)
+        .chain([1u8])
+        .chain(&dst_prime)
+        .finalize();
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let xored = b_0
+            .iter()
+            .zip(b_i.iter())
+            .map(|(a, b)| a ^ b)
+            .collect::<Vec<u8>>();
+        b_i = Sha256::new()
+            .chain(&xored)
+            .chain([i as u8])
+            .chain(&dst_prime)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len);
+    uniform_bytes
+}
+
+/// Hashes `data` to a scalar. The expanded output is 16 bytes longer than a scalar before
+/// it is reduced mod q (the `hash_to_field` construction of RFC 9380), so the result is
+/// statistically close to uniform on any curve, P-256 included.
+pub fn hash_to_scalar<S: ECScalar>(dst: &[u8], data: &[u8]) -> S {
+    let len = S::ScalarLength::to_usize() + WIDE_REDUCTION_EXTRA_BYTES;
+    let uniform_bytes = expand_message_xmd(dst, data, len);
+    let n = from(&uniform_bytes[..]) % S::q();
+    ECScalar::from(&n)
+}
+
+/// Hashes `data` onto the curve by try-and-increment: a counter is appended until the
+/// expanded bytes are the x coordinate of a curve point, whose parity is taken from one
+/// more expanded byte. Nobody knows the discrete log of the result with respect to any
+/// other point, which makes it suitable for deriving NUMS generators from a label.
+///
+/// The number of iterations depends on the input, so `data` must not be secret.
+pub fn hash_to_curve<P: ECPoint>(dst: &[u8], data: &[u8]) -> P {
+    let coor_len = P::CompressedPointLength::to_usize() - 1;
+    for counter in 0u32.. {
+        let mut msg = data.to_vec();
+        msg.extend_from_slice(&counter.to_be_bytes());
+        let uniform_bytes = expand_message_xmd(dst, &msg, coor_len + 1);
+        let mut candidate = vec![2 | (uniform_bytes[0] & 1)];
+        candidate.extend_from_slice(&uniform_bytes[1..]);
+        if let Ok(point) = P::from_bytes(&candidate) {
+            return point;
+        }
+    }
+    unreachable!("every candidate has probability about 1/2 of being on the curve")
+}
+
+/// Deterministic generator for `label`, independent of `P::generator()` and of
+/// `P::base_point2()`.
+pub fn nums_generator<P: ECPoint>(label: &[u8]) -> P {
+    hash_to_curve(b"emerald-city/nums-generator", label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::arithmetic::traits::Converter;
+    use crate::curv::elliptic::curves::{p256, secp256_k1};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // RFC 9380, appendix K.1
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_expand_message_xmd_vectors() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let uniform_bytes = expand_message_xmd(dst, b"", 0x20);
+        assert_eq!(
+            from(&uniform_bytes[..]).to_hex(),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+        let uniform_bytes = expand_message_xmd(dst, b"abc", 0x20);
+        assert_eq!(
+            from(&uniform_bytes[..]).to_hex(),
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_hash_to_scalar() {
+        let s1: secp256_k1::FE = hash_to_scalar(b"test/a", b"data");
+        let s2: secp256_k1::FE = hash_to_scalar(b"test/a", b"data");
+        let s3: secp256_k1::FE = hash_to_scalar(b"test/b", b"data");
+        let s4: secp256_k1::FE = hash_to_scalar(b"test/a", b"datb");
+        assert_eq!(s1, s2);
+        assert_ne!(s1, s3);
+        assert_ne!(s1, s4);
+        let s: p256::FE = hash_to_scalar(b"test/a", b"data");
+        assert!(s.to_big_int() < p256::FE::q());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_hash_to_curve() {
+        let p1: secp256_k1::GE = hash_to_curve(b"test/a", b"data");
+        let p2: secp256_k1::GE = hash_to_curve(b"test/a", b"data");
+        let p3: secp256_k1::GE = hash_to_curve(b"test/b", b"data");
+        assert_eq!(p1, p2);
+        assert_ne!(p1, p3);
+        assert_ne!(p1, secp256_k1::GE::generator());
+
+        let h1: p256::GE = nums_generator(b"pedersen/h");
+        let h2: p256::GE = nums_generator(b"pedersen/h2");
+        assert_ne!(h1, h2);
+        assert_ne!(h1, p256::GE::base_point2());
+        assert_eq!(p256::GE::from_bytes(&h1.to_bytes(true)).unwrap(), h1);
+    }
+}
@@ -0,0 +1,47 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use super::ext::HmacExt;
+use super::traits::KeyedHash;
+use crate::curv::arithmetic::num_bigint::BigInt;
+use hmac::Hmac;
+use sha2::Sha256;
+
+/// HMAC-SHA256 (RFC 2104) over the big-endian bytes of the key and of each input.
+pub struct HMacSha256;
+
+impl KeyedHash for HMacSha256 {
+    fn create_hmac(key: &BigInt, data: &[&BigInt]) -> BigInt {
+        data.iter()
+            .fold(Hmac::<Sha256>::new_bigint(key), |ctx, value| ctx.chain_bigint(value))
+            .result_bigint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HMacSha256;
+    use crate::curv::arithmetic::num_bigint::from;
+    use crate::curv::arithmetic::traits::Converter;
+    use crate::curv::cryptographic_primitives::hashing::traits::KeyedHash;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // RFC 4231, test case 2
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hmac_vector_test() {
+        let key = from(&b"Jefe"[..]);
+        let data = from(&b"what do ya want for nothing?"[..]);
+        let result = HMacSha256::create_hmac(&key, &[&data]);
+        assert_eq!(
+            result.to_hex(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use super::ext::HmacExt;
+use super::traits::KeyedHash;
+use crate::curv::arithmetic::num_bigint::BigInt;
+use hmac::Hmac;
+use sha2::Sha512;
+
+/// HMAC-SHA512 (RFC 2104) over the big-endian bytes of the key and of each input.
+pub struct HMacSha512;
+
+impl KeyedHash for HMacSha512 {
+    fn create_hmac(key: &BigInt, data: &[&BigInt]) -> BigInt {
+        data.iter()
+            .fold(Hmac::<Sha512>::new_bigint(key), |ctx, value| ctx.chain_bigint(value))
+            .result_bigint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HMacSha512;
+    use crate::curv::arithmetic::num_bigint::from;
+    use crate::curv::arithmetic::traits::Converter;
+    use crate::curv::cryptographic_primitives::hashing::traits::KeyedHash;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // RFC 4231, test case 2
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn create_hmac_vector_test() {
+        let key = from(&b"Jefe"[..]);
+        let data = from(&b"what do ya want for nothing?"[..]);
+        let result = HMacSha512::create_hmac(&key, &[&data]);
+        assert_eq!(
+            result.to_hex(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+}
@@ -7,5 +7,9 @@
 
 pub mod constants;
 pub mod hash_sha256;
+pub mod hash_to_curve;
+pub mod hmac_sha256;
+pub mod hmac_sha512;
 pub mod traits;
+pub mod transcript;
 pub mod ext;
@@ -6,11 +6,11 @@
 */
 
 use crate::curv::arithmetic::num_bigint::BigInt;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::elliptic::curves::traits::ECPoint;
 
 pub trait Hash {
     fn create_hash(big_ints: &[&BigInt]) -> BigInt;
-    fn create_hash_from_ge(ge_vec: &[&GE]) -> FE;
+    fn create_hash_from_ge<P: ECPoint>(ge_vec: &[&P]) -> P::Scalar;
 }
 
 pub trait KeyedHash {
@@ -0,0 +1,156 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Fiat-Shamir transcript shared by the non-interactive proofs.
+//!
+//! A transcript absorbs labelled messages into a running SHA-256 state and squeezes
+//! challenges out of it. Every message is framed with its label and length, so two different
+//! sequences of appends never hash alike. A challenge is itself absorbed before it is
+//! returned, so later challenges depend on earlier ones.
+//!
+//! The prover and the verifier must build the same transcript: the same protocol label, the
+//! same context (session, party index, protocol step) and the same appends in the same
+//! order. Proofs bound to a context do not verify under any other one, which is what stops
+//! them from being replayed across sessions or parties.
+
+use digest::Digest;
+use sha2::Sha256;
+use typenum::Unsigned;
+
+use super::hash_to_curve::expand_message_xmd;
+use crate::curv::arithmetic::num_bigint::{from, BigInt};
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+
+const TRANSCRIPT_DOMAIN: &[u8] = b"emerald-city/transcript/v1";
+const CHALLENGE_DST: &[u8] = b"emerald-city/transcript/v1/challenge";
+
+#[derive(Clone)]
+pub struct Transcript {
+    state: Sha256,
+}
+
+impl Transcript {
+    /// Starts a transcript for the protocol named by `label`.
+    pub fn new(label: &'static [u8]) -> Transcript {
+        let mut transcript = Transcript {
+            state: Sha256::new(),
+        };
+        transcript.append_message(b"domain", TRANSCRIPT_DOMAIN);
+        transcript.append_message(b"protocol", label);
+        transcript
+    }
+
+    /// Starts a transcript bound to a session and to the index of the proving party.
+    pub fn for_party(label: &'static [u8], session_id: &[u8], party_index: u64) -> Transcript {
+        let mut transcript = Transcript::new(label);
+        transcript.append_message(b"session", session_id);
+        transcript.append_u64(b"party", party_index);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.state.update((label.len() as u32).to_be_bytes());
+        self.state.update(label);
+        self.state.update((message.len() as u64).to_be_bytes());
+        self.state.update(message);
+    }
+
+    pub fn append_u64(&mut self, label: &'static [u8], n: u64) {
+        self.append_message(label, &n.to_be_bytes());
+    }
+
+    pub fn append_bigint(&mut self, label: &'static [u8], n: &BigInt) {
+        self.append_message(label, &n.to_bytes_be());
+    }
+
+    /// Points are absorbed in compressed form.
+    pub fn append_point<P: ECPoint>(&mut self, label: &'static [u8], point: &P) {
+        self.append_message(label, &point.to_bytes(true));
+    }
+
+    /// Scalars are absorbed as fixed length big-endian bytes.
+    pub fn append_scalar<S: ECScalar>(&mut self, label: &'static [u8], scalar: &S) {
+        let bytes = scalar.to_big_int().to_bytes_be();
+        let mut padded = vec![0u8; S::ScalarLength::to_usize().saturating_sub(bytes.len())];
+        padded.extend_from_slice(&bytes);
+        self.append_message(label, &padded);
+    }
+
+    /// Squeezes `len` bytes out of the transcript.
+    pub fn challenge_bytes(&mut self, label: &'static [u8], len: usize) -> Vec<u8> {
+        let mut seed = self.state.clone().finalize().to_vec();
+        seed.extend_from_slice(label);
+        let challenge = expand_message_xmd(CHALLENGE_DST, &seed, len);
+        self.append_message(label, &challenge);
+        challenge
+    }
+
+    /// A challenge in the scalar field of the curve, 16 bytes wider than a scalar before the
+    /// reduction mod q.
+    pub fn challenge_scalar<S: ECScalar>(&mut self, label: &'static [u8]) -> S {
+        let len = S::ScalarLength::to_usize() + 16;
+        let challenge = from(&self.challenge_bytes(label, len)[..]) % S::q();
+        ECScalar::from(&challenge)
+    }
+
+    /// A challenge uniform in `[0, 2^bits)`.
+    pub fn challenge_bigint(&mut self, label: &'static [u8], bits: usize) -> BigInt {
+        let len = bits.div_ceil(8);
+        let mut bytes = self.challenge_bytes(label, len);
+        if !bits.is_multiple_of(8) {
+            bytes[0] &= (1u8 << (bits % 8)) - 1;
+        }
+        from(&bytes[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transcript;
+    use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+    use crate::curv::elliptic::curves::traits::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_transcript_is_deterministic_and_bound() {
+        let build = |label: &'static [u8], session: &[u8], party: u64| {
+            let mut transcript = Transcript::for_party(label, session, party);
+            transcript.append_point(b"point", &GE::generator());
+            transcript.challenge_scalar::<FE>(b"challenge")
+        };
+        let e = build(b"test", b"session-1", 1);
+        assert_eq!(e, build(b"test", b"session-1", 1));
+        assert_ne!(e, build(b"test", b"session-2", 1));
+        assert_ne!(e, build(b"test", b"session-1", 2));
+        assert_ne!(e, build(b"other", b"session-1", 1));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_transcript_framing_and_ratchet() {
+        // moving bytes between a label and a message changes the challenge
+        let mut t1 = Transcript::new(b"test");
+        t1.append_message(b"ab", b"c");
+        let mut t2 = Transcript::new(b"test");
+        t2.append_message(b"a", b"bc");
+        assert_ne!(t1.challenge_bytes(b"e", 32), t2.challenge_bytes(b"e", 32));
+
+        // successive challenges differ
+        let mut t = Transcript::new(b"test");
+        let e1 = t.challenge_bytes(b"e", 32);
+        let e2 = t.challenge_bytes(b"e", 32);
+        assert_ne!(e1, e2);
+
+        let mut t = Transcript::new(b"test");
+        for bits in [1usize, 7, 8, 9, 255, 2048].iter() {
+            assert!(t.challenge_bigint(b"e", *bits).bits() <= *bits);
+        }
+    }
+}
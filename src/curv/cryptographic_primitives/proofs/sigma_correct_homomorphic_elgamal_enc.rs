@@ -7,10 +7,11 @@
 */
 
 use super::ProofError;
-use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::curv::cryptographic_primitives::hashing::traits::Hash;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
+use rand::{CryptoRng, RngCore};
 use zeroize::Zeroize;
 
 /// This is a proof of knowledge that a pair of group elements {D, E}
@@ -20,41 +21,95 @@ use zeroize::Zeroize;
 /// The relation R outputs 1 if D = xH+rY , E = rG (for the case of G=H this is ElGamal)
 ///
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoELGamalProof {
-    pub T: GE,
-    pub A3: GE,
-    pub z1: FE,
-    pub z2: FE,
+#[serde(bound = "")]
+pub struct HomoELGamalProof<P: ECPoint = GE> {
+    pub T: P,
+    pub A3: P,
+    pub z1: P::Scalar,
+    pub z2: P::Scalar,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoElGamalWitness {
-    pub r: FE,
-    pub x: FE,
+#[serde(bound = "")]
+pub struct HomoElGamalWitness<P: ECPoint = GE> {
+    pub r: P::Scalar,
+    pub x: P::Scalar,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct HomoElGamalStatement {
-    pub G: GE,
-    pub H: GE,
-    pub Y: GE,
-    pub D: GE,
-    pub E: GE,
+#[serde(bound = "")]
+pub struct HomoElGamalStatement<P: ECPoint = GE> {
+    pub G: P,
+    pub H: P,
+    pub Y: P,
+    pub D: P,
+    pub E: P,
 }
 
-impl HomoELGamalProof {
-    pub fn prove(w: &HomoElGamalWitness, delta: &HomoElGamalStatement) -> HomoELGamalProof {
-        let mut s1: FE = ECScalar::new_random();
-        let mut s2: FE = ECScalar::new_random();
+const HOMO_ELGAMAL_PROOF_LABEL: &[u8] = b"emerald-city/homo-elgamal-proof";
+
+fn homo_elgamal_challenge<P: ECPoint>(
+    transcript: &mut Transcript,
+    delta: &HomoElGamalStatement<P>,
+    T: &P,
+    A3: &P,
+) -> P::Scalar {
+    transcript.append_message(b"proof", HOMO_ELGAMAL_PROOF_LABEL);
+    transcript.append_point(b"G", &delta.G);
+    transcript.append_point(b"H", &delta.H);
+    transcript.append_point(b"Y", &delta.Y);
+    transcript.append_point(b"D", &delta.D);
+    transcript.append_point(b"E", &delta.E);
+    transcript.append_point(b"T", T);
+    transcript.append_point(b"A3", A3);
+    transcript.challenge_scalar(b"challenge")
+}
+
+impl<P: ECPoint> HomoELGamalProof<P> {
+    pub fn prove(w: &HomoElGamalWitness<P>, delta: &HomoElGamalStatement<P>) -> HomoELGamalProof<P> {
+        Self::prove_with_rng(w, delta, &mut os_rng())
+    }
+
+    pub fn prove_with_rng(
+        w: &HomoElGamalWitness<P>,
+        delta: &HomoElGamalStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> HomoELGamalProof<P> {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(HOMO_ELGAMAL_PROOF_LABEL),
+            w,
+            delta,
+            rng,
+        )
+    }
+
+    pub fn verify(&self, delta: &HomoElGamalStatement<P>) -> Result<(), ProofError> {
+        self.verify_with_transcript(&mut Transcript::new(HOMO_ELGAMAL_PROOF_LABEL), delta)
+    }
+
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        w: &HomoElGamalWitness<P>,
+        delta: &HomoElGamalStatement<P>,
+    ) -> HomoELGamalProof<P> {
+        Self::prove_with_transcript_and_rng(transcript, w, delta, &mut os_rng())
+    }
+
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        w: &HomoElGamalWitness<P>,
+        delta: &HomoElGamalStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> HomoELGamalProof<P> {
+        let mut s1: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let mut s2: P::Scalar = ECScalar::new_random_with_rng(rng);
         let mut A1 = delta.H.clone() * s1.clone();
         let mut A2 = delta.Y.clone() * s2.clone();
         let A3 = delta.G.clone() * s2.clone();
         let T = A1.clone() + A2.clone();
-        let e = HSha256::create_hash_from_ge(&[
-            &T, &A3, &delta.G, &delta.H, &delta.Y, &delta.D, &delta.E,
-        ]);
+        let e = homo_elgamal_challenge(transcript, delta, &T, &A3);
         // dealing with zero field element
-        let z1 = if w.x.clone() != FE::zero() {
+        let z1 = if w.x.clone() != P::Scalar::zero() {
             s1.clone() + w.x.clone() * e.clone()
         } else {
             s1.clone()
@@ -66,16 +121,13 @@ impl HomoELGamalProof {
         A2.zeroize();
         HomoELGamalProof { T, A3, z1, z2 }
     }
-    pub fn verify(&self, delta: &HomoElGamalStatement) -> Result<(), ProofError> {
-        let e = HSha256::create_hash_from_ge(&[
-            &self.T.clone(),
-            &self.A3.clone(),
-            &delta.G.clone(),
-            &delta.H.clone(),
-            &delta.Y.clone(),
-            &delta.D.clone(),
-            &delta.E.clone(),
-        ]);
+
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        delta: &HomoElGamalStatement<P>,
+    ) -> Result<(), ProofError> {
+        let e = homo_elgamal_challenge(transcript, delta, &self.T, &self.A3);
         let z1H_plus_z2Y = delta.H.clone() * self.z1.clone() + delta.Y.clone() * self.z2.clone();
         let T_plus_eD = self.T.clone() + delta.D.clone() * e.clone();
         let z2G = delta.G.clone() * self.z2.clone();
@@ -160,4 +212,23 @@ mod tests {
         let result = proof.verify(&delta);
         assert_eq!(result.unwrap_err().description(), PROOF_ERROR_DESCRIPTION);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_correct_homo_elgamal_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let witness = HomoElGamalWitness::<p256::GE> {
+            r: ECScalar::new_random(),
+            x: ECScalar::new_random(),
+        };
+        let G = p256::GE::generator();
+        let H = p256::GE::base_point2();
+        let y: p256::FE = ECScalar::new_random();
+        let Y = &G * &y;
+        let D = &H * &witness.x + Y.clone() * &witness.r;
+        let E = G.clone() * &witness.r;
+        let delta = HomoElGamalStatement { G, H, Y, D, E };
+        let proof = HomoELGamalProof::prove(&witness, &delta);
+        assert!(proof.verify(&delta).is_ok());
+    }
 }
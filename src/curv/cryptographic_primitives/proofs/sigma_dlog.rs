@@ -6,11 +6,12 @@
 */
 
 use super::ProofError;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
 
-use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::curv::cryptographic_primitives::hashing::traits::Hash;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use rand::{CryptoRng, RngCore};
 use zeroize::Zeroize;
 
 /// This is implementation of Schnorr's identification protocol for elliptic curve groups or a
@@ -23,34 +24,104 @@ use zeroize::Zeroize;
 /// How to prove yourself: Practical solutions to identification and signature problems.
 /// In Advances in Cryptology - CRYPTO ’86, Santa Barbara, California, USA, 1986, Proceedings,
 /// pages 186–194, 1986.
+///
+/// The challenge is drawn from a `Transcript`. `prove` and `verify` use a transcript with no
+/// context; protocols pass one bound to the session and the prover to the `_with_transcript`
+/// variants, so that a proof cannot be replayed by another party or in another session.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct DLogProof {
-    pub pk: GE,
-    pub pk_t_rand_commitment: GE,
-    pub challenge_response: FE,
+#[serde(bound = "")]
+pub struct DLogProof<P: ECPoint = GE> {
+    pub pk: P,
+    pub pk_t_rand_commitment: P,
+    pub challenge_response: P::Scalar,
 }
 
-pub trait ProveDLog {
-    fn prove(sk: &FE) -> DLogProof;
+pub trait ProveDLog<P: ECPoint> {
+    fn prove(sk: &P::Scalar) -> DLogProof<P>;
+
+    fn verify(proof: &DLogProof<P>) -> Result<(), ProofError>;
+
+    fn prove_with_transcript(transcript: &mut Transcript, sk: &P::Scalar) -> DLogProof<P>;
+
+    fn prove_with_rng(sk: &P::Scalar, rng: &mut (impl RngCore + CryptoRng)) -> DLogProof<P>;
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        sk: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> DLogProof<P>;
+
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &DLogProof<P>,
+    ) -> Result<(), ProofError>;
+
+    /// Verifies all the proofs at once. If the batch does not verify, the proofs are checked
+    /// one by one and the indices of the invalid ones are returned.
+    fn batch_verify(proofs: &[DLogProof<P>]) -> Result<(), Vec<usize>>;
+
+    /// As `batch_verify`, with the i-th proof checked against the i-th transcript. If there are
+    /// not as many transcripts as proofs, every index is returned.
+    fn batch_verify_with_transcripts(
+        transcripts: &mut [Transcript],
+        proofs: &[DLogProof<P>],
+    ) -> Result<(), Vec<usize>>;
 
-    fn verify(proof: &DLogProof) -> Result<(), ProofError>;
+    /// As `batch_verify_with_transcripts`, drawing the batch weights from `rng`.
+    fn batch_verify_with_transcripts_and_rng(
+        transcripts: &mut [Transcript],
+        proofs: &[DLogProof<P>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), Vec<usize>>;
 }
 
-impl ProveDLog for DLogProof {
-    fn prove(sk: &FE) -> DLogProof {
-        let base_point: GE = ECPoint::generator();
-        let generator_x = base_point.bytes_compressed_to_big_int();
-        let mut sk_t_rand_commitment: FE = ECScalar::new_random();
+const DLOG_PROOF_LABEL: &[u8] = b"emerald-city/dlog-proof";
+
+fn dlog_challenge<P: ECPoint>(transcript: &mut Transcript, pk: &P, commitment: &P) -> P::Scalar {
+    transcript.append_message(b"proof", DLOG_PROOF_LABEL);
+    transcript.append_point(b"G", &P::generator());
+    transcript.append_point(b"pk", pk);
+    transcript.append_point(b"commitment", commitment);
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// z*G + e*pk = R
+fn check_dlog_response<P: ECPoint>(proof: &DLogProof<P>, challenge: P::Scalar) -> bool {
+    let pk_verifier = P::multi_scalar_mul(
+        &[P::generator(), proof.pk.clone()],
+        &[proof.challenge_response.clone(), challenge],
+    );
+    pk_verifier == proof.pk_t_rand_commitment
+}
+
+impl<P: ECPoint> ProveDLog<P> for DLogProof<P> {
+    fn prove(sk: &P::Scalar) -> DLogProof<P> {
+        Self::prove_with_rng(sk, &mut os_rng())
+    }
+
+    fn verify(proof: &DLogProof<P>) -> Result<(), ProofError> {
+        Self::verify_with_transcript(&mut Transcript::new(DLOG_PROOF_LABEL), proof)
+    }
+
+    fn prove_with_transcript(transcript: &mut Transcript, sk: &P::Scalar) -> DLogProof<P> {
+        Self::prove_with_transcript_and_rng(transcript, sk, &mut os_rng())
+    }
+
+    fn prove_with_rng(sk: &P::Scalar, rng: &mut (impl RngCore + CryptoRng)) -> DLogProof<P> {
+        Self::prove_with_transcript_and_rng(&mut Transcript::new(DLOG_PROOF_LABEL), sk, rng)
+    }
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        sk: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> DLogProof<P> {
+        let base_point = P::generator();
+        let mut sk_t_rand_commitment = P::Scalar::new_random_with_rng(rng);
         let pk_t_rand_commitment = base_point.scalar_mul(&sk_t_rand_commitment.get_element());
-        let ec_point: GE = ECPoint::generator();
-        let pk = ec_point.scalar_mul(&sk.get_element());
-        let challenge = HSha256::create_hash(&[
-            &pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &generator_x,
-            &pk.bytes_compressed_to_big_int(),
-        ]);
-        let challenge_fe: FE = ECScalar::from(&challenge);
-        let challenge_mul_sk = challenge_fe.mul(&sk.get_element());
+        let pk = P::generator() * sk.clone();
+        let challenge_fe = dlog_challenge(transcript, &pk, &pk_t_rand_commitment);
+        let challenge_mul_sk = challenge_fe * sk.clone();
         let challenge_response = sk_t_rand_commitment.sub(&challenge_mul_sk.get_element());
         sk_t_rand_commitment.zeroize();
         DLogProof {
@@ -60,29 +131,73 @@ impl ProveDLog for DLogProof {
         }
     }
 
-    fn verify(proof: &DLogProof) -> Result<(), ProofError> {
-        let ec_point: GE = ECPoint::generator();
-        let challenge = HSha256::create_hash(&[
-            &proof.pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &ec_point.bytes_compressed_to_big_int(),
-            &proof.pk.clone().bytes_compressed_to_big_int(),
-        ]);
-
-        let sk_challenge: FE = ECScalar::from(&challenge);
-        let pk = proof.pk.clone();
-        let pk_challenge = pk.scalar_mul(&sk_challenge.get_element());
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &DLogProof<P>,
+    ) -> Result<(), ProofError> {
+        let sk_challenge = dlog_challenge(transcript, &proof.pk, &proof.pk_t_rand_commitment);
+        if check_dlog_response(proof, sk_challenge) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
 
-        let base_point: GE = ECPoint::generator();
+    fn batch_verify(proofs: &[DLogProof<P>]) -> Result<(), Vec<usize>> {
+        let mut transcripts = vec![Transcript::new(DLOG_PROOF_LABEL); proofs.len()];
+        Self::batch_verify_with_transcripts(&mut transcripts, proofs)
+    }
 
-        let mut pk_verifier = base_point.scalar_mul(&proof.challenge_response.get_element());
+    fn batch_verify_with_transcripts(
+        transcripts: &mut [Transcript],
+        proofs: &[DLogProof<P>],
+    ) -> Result<(), Vec<usize>> {
+        Self::batch_verify_with_transcripts_and_rng(transcripts, proofs, &mut os_rng())
+    }
 
-        pk_verifier = pk_verifier.add_point(&pk_challenge.get_element());
+    fn batch_verify_with_transcripts_and_rng(
+        transcripts: &mut [Transcript],
+        proofs: &[DLogProof<P>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), Vec<usize>> {
+        if transcripts.len() != proofs.len() {
+            return Err((0..transcripts.len().max(proofs.len())).collect());
+        }
+        let challenges = transcripts
+            .iter_mut()
+            .zip(proofs)
+            .map(|(transcript, proof)| {
+                dlog_challenge(transcript, &proof.pk, &proof.pk_t_rand_commitment)
+            })
+            .collect::<Vec<P::Scalar>>();
 
-        if pk_verifier == proof.pk_t_rand_commitment {
-            Ok(())
-        } else {
-            Err(ProofError)
+        // Each proof asserts z_i*G + e_i*pk_i - R_i = 0. A combination of the equations with
+        // random weights w_i vanishes when some proof is invalid only with probability 1/q:
+        // (sum w_i*z_i)*G + sum (w_i*e_i)*pk_i - sum w_i*R_i = 0
+        let mut points = Vec::with_capacity(2 * proofs.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * proofs.len() + 1);
+        let mut z = P::Scalar::zero();
+        for (proof, e) in proofs.iter().zip(&challenges) {
+            let w = P::Scalar::new_random_with_rng(rng);
+            z += w.clone() * proof.challenge_response.clone();
+            points.push(proof.pk.clone());
+            scalars.push(w.clone() * e.clone());
+            points.push(proof.pk_t_rand_commitment.clone());
+            scalars.push(-w);
         }
+        points.push(P::generator());
+        scalars.push(z);
+        if P::multi_scalar_mul(&points, &scalars).is_identity() {
+            return Ok(());
+        }
+
+        Err(proofs
+            .iter()
+            .zip(challenges)
+            .enumerate()
+            .filter(|(_, (proof, e))| !check_dlog_response(proof, e.clone()))
+            .map(|(i, _)| i)
+            .collect())
     }
 }
 
@@ -98,7 +213,7 @@ mod tests {
     #[test]
     fn test_dlog_proof() {
         let witness: FE = ECScalar::new_random();
-        let dlog_proof = DLogProof::prove(&witness);
+        let dlog_proof: DLogProof = DLogProof::prove(&witness);
         let verified = DLogProof::verify(&dlog_proof);
         match verified {
             Ok(_t) => assert!(true),
@@ -106,4 +221,99 @@ mod tests {
         }
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_with_seeded_rng() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let prove = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let witness: FE = ECScalar::new_random_with_rng(&mut rng);
+            DLogProof::<GE>::prove_with_rng(&witness, &mut rng)
+        };
+        let dlog_proof = prove(1);
+        assert!(DLogProof::verify(&dlog_proof).is_ok());
+        assert_eq!(dlog_proof, prove(1));
+        assert_ne!(dlog_proof, prove(2));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let witness: p256::FE = ECScalar::new_random();
+        let dlog_proof: DLogProof<p256::GE> = DLogProof::prove(&witness);
+        assert!(DLogProof::verify(&dlog_proof).is_ok());
+        let s = serde_json::to_string(&dlog_proof).expect("Failed in serialization");
+        let des: DLogProof<p256::GE> = serde_json::from_str(&s).expect("Failed in deserialization");
+        assert_eq!(des, dlog_proof);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_bound_to_party() {
+        use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+        let witness: FE = ECScalar::new_random();
+        let transcript = Transcript::for_party(b"keygen", b"session", 1);
+        let dlog_proof: DLogProof = DLogProof::prove_with_transcript(&mut transcript.clone(), &witness);
+        assert!(DLogProof::verify_with_transcript(&mut transcript.clone(), &dlog_proof).is_ok());
+        assert!(DLogProof::verify(&dlog_proof).is_err());
+        let mut other_party = Transcript::for_party(b"keygen", b"session", 2);
+        assert!(DLogProof::verify_with_transcript(&mut other_party, &dlog_proof).is_err());
+        let mut other_session = Transcript::for_party(b"keygen", b"other session", 1);
+        assert!(DLogProof::verify_with_transcript(&mut other_session, &dlog_proof).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_batch_verify() {
+        let mut proofs = (0..5)
+            .map(|_| {
+                let witness: FE = ECScalar::new_random();
+                DLogProof::prove(&witness)
+            })
+            .collect::<Vec<DLogProof>>();
+        assert!(DLogProof::batch_verify(&proofs).is_ok());
+        assert!(DLogProof::<GE>::batch_verify(&[]).is_ok());
+
+        proofs[1].challenge_response = proofs[1].challenge_response.clone() + FE::new_random();
+        proofs[3].pk = proofs[3].pk.clone() + GE::generator();
+        assert_eq!(DLogProof::batch_verify(&proofs), Err(vec![1, 3]));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_dlog_proof_batch_verify_with_transcripts() {
+        use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let transcripts = (1..4)
+            .map(|i| Transcript::for_party(b"keygen", b"session", i))
+            .collect::<Vec<Transcript>>();
+        let proofs = transcripts
+            .iter()
+            .map(|transcript| {
+                let witness: FE = ECScalar::new_random();
+                DLogProof::prove_with_transcript(&mut transcript.clone(), &witness)
+            })
+            .collect::<Vec<DLogProof>>();
+        assert!(DLogProof::batch_verify_with_transcripts(&mut transcripts.clone(), &proofs).is_ok());
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(
+            DLogProof::batch_verify_with_transcripts_and_rng(&mut transcripts.clone(), &proofs, &mut rng)
+                .is_ok()
+        );
+        assert_eq!(DLogProof::batch_verify(&proofs), Err(vec![0, 1, 2]));
+
+        let mut swapped = transcripts.clone();
+        swapped.swap(0, 2);
+        assert_eq!(
+            DLogProof::batch_verify_with_transcripts(&mut swapped, &proofs),
+            Err(vec![0, 2])
+        );
+        assert_eq!(
+            DLogProof::batch_verify_with_transcripts(&mut transcripts.clone(), &proofs[1..]),
+            Err(vec![0, 1, 2])
+        );
+    }
 }
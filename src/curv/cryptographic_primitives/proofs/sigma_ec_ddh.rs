@@ -0,0 +1,217 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use super::ProofError;
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::*;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+/// This protocol is the elliptic curve form of the protocol from :
+/// D. Chaum, T. P. Pedersen. Transferred cash grows in size. In Advances in Cryptology, EUROCRYPT , volume 658 of Lecture Notes in Computer Science, pages 390 - 407, 1993.
+/// This is a proof of membership of DDH: (G, xG, yG, xyG)
+/// The statement is (G1,H1, G2, H2), the witness is x. The relation outputs 1 if :
+/// H1 = xG1, H2 = xG2
+///
+/// The prover sends a1 = sG1, a2 = sG2 for a random s; with the challenge e it answers
+/// z = s + e*x, and the verifier checks zG1 = a1 + eH1 and zG2 = a2 + eH2.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ECDDHProof<P: ECPoint = GE> {
+    pub a1: P,
+    pub a2: P,
+    pub z: P::Scalar,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ECDDHStatement<P: ECPoint = GE> {
+    pub g1: P,
+    pub h1: P,
+    pub g2: P,
+    pub h2: P,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ECDDHWitness<P: ECPoint = GE> {
+    pub x: P::Scalar,
+}
+
+pub trait ProveECDDH<P: ECPoint> {
+    fn prove(w: &ECDDHWitness<P>, delta: &ECDDHStatement<P>) -> ECDDHProof<P>;
+
+    fn verify(proof: &ECDDHProof<P>, delta: &ECDDHStatement<P>) -> Result<(), ProofError>;
+
+    fn prove_with_transcript(
+        transcript: &mut Transcript,
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+    ) -> ECDDHProof<P>;
+
+    fn prove_with_rng(
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ECDDHProof<P>;
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ECDDHProof<P>;
+
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &ECDDHProof<P>,
+        delta: &ECDDHStatement<P>,
+    ) -> Result<(), ProofError>;
+}
+
+const EC_DDH_PROOF_LABEL: &[u8] = b"emerald-city/ec-ddh-proof";
+
+fn ec_ddh_challenge<P: ECPoint>(
+    transcript: &mut Transcript,
+    delta: &ECDDHStatement<P>,
+    a1: &P,
+    a2: &P,
+) -> P::Scalar {
+    transcript.append_message(b"proof", EC_DDH_PROOF_LABEL);
+    transcript.append_point(b"g1", &delta.g1);
+    transcript.append_point(b"h1", &delta.h1);
+    transcript.append_point(b"g2", &delta.g2);
+    transcript.append_point(b"h2", &delta.h2);
+    transcript.append_point(b"a1", a1);
+    transcript.append_point(b"a2", a2);
+    transcript.challenge_scalar(b"challenge")
+}
+
+impl<P: ECPoint> ProveECDDH<P> for ECDDHProof<P> {
+    fn prove(w: &ECDDHWitness<P>, delta: &ECDDHStatement<P>) -> ECDDHProof<P> {
+        Self::prove_with_rng(w, delta, &mut os_rng())
+    }
+
+    fn verify(proof: &ECDDHProof<P>, delta: &ECDDHStatement<P>) -> Result<(), ProofError> {
+        Self::verify_with_transcript(&mut Transcript::new(EC_DDH_PROOF_LABEL), proof, delta)
+    }
+
+    fn prove_with_transcript(
+        transcript: &mut Transcript,
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+    ) -> ECDDHProof<P> {
+        Self::prove_with_transcript_and_rng(transcript, w, delta, &mut os_rng())
+    }
+
+    fn prove_with_rng(
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ECDDHProof<P> {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(EC_DDH_PROOF_LABEL),
+            w,
+            delta,
+            rng,
+        )
+    }
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        w: &ECDDHWitness<P>,
+        delta: &ECDDHStatement<P>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> ECDDHProof<P> {
+        let mut s = P::Scalar::new_random_with_rng(rng);
+        let a1 = delta.g1.clone() * s.clone();
+        let a2 = delta.g2.clone() * s.clone();
+        let e = ec_ddh_challenge(transcript, delta, &a1, &a2);
+        let z = s.clone() + e * w.x.clone();
+        s.zeroize();
+        ECDDHProof { a1, a2, z }
+    }
+
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &ECDDHProof<P>,
+        delta: &ECDDHStatement<P>,
+    ) -> Result<(), ProofError> {
+        let e = ec_ddh_challenge(transcript, delta, &proof.a1, &proof.a2);
+        let z_g1 = delta.g1.clone() * proof.z.clone();
+        let z_g2 = delta.g2.clone() * proof.z.clone();
+        let a1_plus_e_h1 = proof.a1.clone() + delta.h1.clone() * e.clone();
+        let a2_plus_e_h2 = proof.a2.clone() + delta.h2.clone() * e;
+        if z_g1 == a1_plus_e_h1 && z_g2 == a2_plus_e_h2 {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::curv::cryptographic_primitives::proofs::sigma_ec_ddh::*;
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_ecddh_proof() {
+        let x: FE = ECScalar::new_random();
+        let g1 = GE::generator();
+        let g2 = GE::base_point2();
+        let h1 = g1.clone() * x.clone();
+        let h2 = g2.clone() * x.clone();
+        let delta = ECDDHStatement { g1, h1, g2, h2 };
+        let w = ECDDHWitness { x };
+        let proof = ECDDHProof::prove(&w, &delta);
+        assert!(ECDDHProof::verify(&proof, &delta).is_ok());
+
+        let s = serde_json::to_string(&proof).expect("Failed in serialization");
+        let des: ECDDHProof = serde_json::from_str(&s).expect("Failed in deserialization");
+        assert_eq!(des, proof);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_wrong_ecddh_proof() {
+        let x: FE = ECScalar::new_random();
+        let g1 = GE::generator();
+        let g2 = GE::base_point2();
+        let h1 = g1.clone() * x.clone();
+        // h2 = (x + 1)G2
+        let h2 = g2.clone() * x.clone() + g2.clone();
+        let delta = ECDDHStatement { g1, h1, g2, h2 };
+        let w = ECDDHWitness { x };
+        let proof = ECDDHProof::prove(&w, &delta);
+        assert!(ECDDHProof::verify(&proof, &delta).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_ecddh_proof_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let x: p256::FE = ECScalar::new_random();
+        let g1 = p256::GE::generator();
+        let g2 = p256::GE::base_point2();
+        let h1 = g1.clone() * x.clone();
+        let h2 = g2.clone() * x.clone();
+        let delta = ECDDHStatement { g1, h1, g2, h2 };
+        let w = ECDDHWitness { x };
+        let proof: ECDDHProof<p256::GE> = ECDDHProof::prove(&w, &delta);
+        assert!(ECDDHProof::verify(&proof, &delta).is_ok());
+        let mut other_statement = delta.clone();
+        other_statement.h2 = other_statement.h1.clone();
+        assert!(ECDDHProof::verify(&proof, &other_statement).is_err());
+    }
+}
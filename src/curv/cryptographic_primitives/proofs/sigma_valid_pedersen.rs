@@ -0,0 +1,172 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use super::ProofError;
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::*;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+/// Proof of knowledge of an opening (m, r) of the Pedersen commitment com = mG + rH, without
+/// revealing it (Okamoto's protocol, made non-interactive with Fiat-Shamir):
+///
+/// the prover sends a = s1*G + s2*H for random s1, s2; with the challenge e it answers
+/// z1 = s1 + e*m and z2 = s2 + e*r; the verifier checks z1*G + z2*H = a + e*com.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PedersenProof<P: ECPoint = GE> {
+    pub a: P,
+    pub com: P,
+    pub z1: P::Scalar,
+    pub z2: P::Scalar,
+}
+
+pub trait ProvePedersen<P: ECPoint> {
+    fn prove(m: &P::Scalar, r: &P::Scalar) -> PedersenProof<P>;
+
+    fn verify(proof: &PedersenProof<P>) -> Result<(), ProofError>;
+
+    fn prove_with_transcript(
+        transcript: &mut Transcript,
+        m: &P::Scalar,
+        r: &P::Scalar,
+    ) -> PedersenProof<P>;
+
+    fn prove_with_rng(
+        m: &P::Scalar,
+        r: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PedersenProof<P>;
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        m: &P::Scalar,
+        r: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PedersenProof<P>;
+
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &PedersenProof<P>,
+    ) -> Result<(), ProofError>;
+}
+
+const PEDERSEN_PROOF_LABEL: &[u8] = b"emerald-city/pedersen-opening";
+
+impl<P: ECPoint> PedersenProof<P> {
+    fn challenge(transcript: &mut Transcript, a: &P, com: &P) -> P::Scalar {
+        transcript.append_message(b"proof", PEDERSEN_PROOF_LABEL);
+        transcript.append_point(b"G", &P::generator());
+        transcript.append_point(b"H", &P::base_point2());
+        transcript.append_point(b"com", com);
+        transcript.append_point(b"a", a);
+        transcript.challenge_scalar(b"challenge")
+    }
+}
+
+impl<P: ECPoint> ProvePedersen<P> for PedersenProof<P> {
+    fn prove(m: &P::Scalar, r: &P::Scalar) -> PedersenProof<P> {
+        Self::prove_with_rng(m, r, &mut os_rng())
+    }
+
+    fn verify(proof: &PedersenProof<P>) -> Result<(), ProofError> {
+        Self::verify_with_transcript(&mut Transcript::new(PEDERSEN_PROOF_LABEL), proof)
+    }
+
+    fn prove_with_transcript(
+        transcript: &mut Transcript,
+        m: &P::Scalar,
+        r: &P::Scalar,
+    ) -> PedersenProof<P> {
+        Self::prove_with_transcript_and_rng(transcript, m, r, &mut os_rng())
+    }
+
+    fn prove_with_rng(
+        m: &P::Scalar,
+        r: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PedersenProof<P> {
+        Self::prove_with_transcript_and_rng(&mut Transcript::new(PEDERSEN_PROOF_LABEL), m, r, rng)
+    }
+
+    fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        m: &P::Scalar,
+        r: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PedersenProof<P> {
+        let mut s1 = P::Scalar::new_random_with_rng(rng);
+        let mut s2 = P::Scalar::new_random_with_rng(rng);
+        let a = PedersenCommitment::<P>::commit_scalars(&s1, &s2);
+        let com = PedersenCommitment::<P>::commit_scalars(m, r);
+        let e = Self::challenge(transcript, &a, &com);
+        let z1 = s1.clone() + e.clone() * m.clone();
+        let z2 = s2.clone() + e * r.clone();
+        s1.zeroize();
+        s2.zeroize();
+        PedersenProof { a, com, z1, z2 }
+    }
+
+    fn verify_with_transcript(
+        transcript: &mut Transcript,
+        proof: &PedersenProof<P>,
+    ) -> Result<(), ProofError> {
+        let e = Self::challenge(transcript, &proof.a, &proof.com);
+        // z1*G + z2*H - e*com - a must vanish
+        let lhs = P::multi_scalar_mul(
+            &[P::generator(), P::base_point2(), proof.com.clone()],
+            &[proof.z1.clone(), proof.z2.clone(), -e],
+        );
+        if lhs == proof.a {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::curv::cryptographic_primitives::proofs::sigma_valid_pedersen::*;
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_pedersen_proof() {
+        let m: FE = ECScalar::new_random();
+        let r: FE = ECScalar::new_random();
+        let proof: PedersenProof = PedersenProof::prove(&m, &r);
+        assert!(PedersenProof::verify(&proof).is_ok());
+        assert_eq!(proof.com, PedersenCommitment::<GE>::commit_scalars(&m, &r));
+
+        let mut bad_proof = proof.clone();
+        bad_proof.com = bad_proof.com + GE::generator();
+        assert!(PedersenProof::verify(&bad_proof).is_err());
+        let mut bad_proof = proof;
+        bad_proof.z2 = bad_proof.z2 + FE::new_random();
+        assert!(PedersenProof::verify(&bad_proof).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_pedersen_proof_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let m: p256::FE = ECScalar::new_random();
+        let r: p256::FE = ECScalar::new_random();
+        let proof: PedersenProof<p256::GE> = PedersenProof::prove(&m, &r);
+        assert!(PedersenProof::verify(&proof).is_ok());
+        let s = serde_json::to_string(&proof).expect("Failed in serialization");
+        let des: PedersenProof<p256::GE> = serde_json::from_str(&s).expect("Failed in deserialization");
+        assert_eq!(des, proof);
+    }
+}
@@ -7,9 +7,11 @@
 */
 
 use crate::curv::arithmetic::num_bigint::BigInt;
-use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::elliptic::curves::secp256_k1::GE;
 use crate::curv::elliptic::curves::traits::*;
 use num_traits::One;
+use rand::{CryptoRng, RngCore};
 use crate::ErrorSS::{self, VerifyShareError};
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ShamirSecretSharing {
@@ -19,57 +21,62 @@ pub struct ShamirSecretSharing {
 /// Feldman VSS, based on  Paul Feldman. 1987. A practical scheme for non-interactive verifiable secret sharing.
 /// In Foundations of Computer Science, 1987., 28th Annual Symposium on.IEEE, 427–43
 ///
-/// implementation details: The code is using FE and GE. Each party is given an index from 1,..,n and a secret share of type FE.
-/// The index of the party is also the point on the polynomial where we treat this number as u32 but converting it to FE internally.
+/// implementation details: The code is generic over the curve point `P` (secp256k1 by default) and its scalar `P::Scalar`.
+/// Each party is given an index from 1,..,n and a secret share of type `P::Scalar`.
+/// The index of the party is also the point on the polynomial where we treat this number as u32 but converting it to a scalar internally.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct VerifiableSS {
+#[serde(bound = "")]
+pub struct VerifiableSS<P: ECPoint = GE> {
     pub parameters: ShamirSecretSharing,
-    pub commitments: Vec<GE>,
+    pub commitments: Vec<P>,
 }
 
-impl VerifiableSS {
+impl<P: ECPoint> VerifiableSS<P> {
     pub fn reconstruct_limit(&self) -> usize {
         self.parameters.threshold + 1
     }
 
     // generate VerifiableSS from a secret
-    pub fn share(t: usize, n: usize, secret: &FE) -> (VerifiableSS, Vec<FE>) {
+    pub fn share(t: usize, n: usize, secret: &P::Scalar) -> (VerifiableSS<P>, Vec<P::Scalar>) {
+        Self::share_with_rng(t, n, secret, &mut os_rng())
+    }
+
+    pub fn share_with_rng(
+        t: usize,
+        n: usize,
+        secret: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (VerifiableSS<P>, Vec<P::Scalar>) {
         assert!(t < n);
-        let poly = VerifiableSS::sample_polynomial(t, secret);
         let index_vec: Vec<usize> = (1..=n).collect();
-        let secret_shares = VerifiableSS::evaluate_polynomial(&poly, &index_vec);
-
-        let G: GE = ECPoint::generator();
-        let commitments = (0..poly.len())
-            .map(|i| G.clone() * poly[i].clone())
-            .collect::<Vec<GE>>();
-        (
-            VerifiableSS {
-                parameters: ShamirSecretSharing {
-                    threshold: t,
-                    share_count: n,
-                },
-                commitments,
-            },
-            secret_shares,
-        )
+        Self::share_at_indices_with_rng(t, n, secret, &index_vec, rng)
     }
 
     // generate VerifiableSS from a secret and user defined x values (in case user wants to distribute point f(1), f(4), f(6) and not f(1),f(2),f(3))
     pub fn share_at_indices(
         t: usize,
         n: usize,
-        secret: &FE,
+        secret: &P::Scalar,
+        index_vec: &[usize],
+    ) -> (VerifiableSS<P>, Vec<P::Scalar>) {
+        Self::share_at_indices_with_rng(t, n, secret, index_vec, &mut os_rng())
+    }
+
+    pub fn share_at_indices_with_rng(
+        t: usize,
+        n: usize,
+        secret: &P::Scalar,
         index_vec: &[usize],
-    ) -> (VerifiableSS, Vec<FE>) {
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (VerifiableSS<P>, Vec<P::Scalar>) {
         assert_eq!(n, index_vec.len());
-        let poly = VerifiableSS::sample_polynomial(t, secret);
-        let secret_shares = VerifiableSS::evaluate_polynomial(&poly, index_vec);
+        let poly = Self::sample_polynomial_with_rng(t, secret, rng);
+        let secret_shares = Self::evaluate_polynomial(&poly, index_vec);
 
-        let G: GE = ECPoint::generator();
+        let G = P::generator();
         let commitments = (0..poly.len())
-            .map(|i| G.clone() * &poly[i])
-            .collect::<Vec<GE>>();
+            .map(|i| G.clone() * poly[i].clone())
+            .collect::<Vec<P>>();
         (
             VerifiableSS {
                 parameters: ShamirSecretSharing {
@@ -83,26 +90,35 @@ impl VerifiableSS {
     }
 
     // returns vector of coefficients
-    pub fn sample_polynomial(t: usize, coef0: &FE) -> Vec<FE> {
+    pub fn sample_polynomial(t: usize, coef0: &P::Scalar) -> Vec<P::Scalar> {
+        Self::sample_polynomial_with_rng(t, coef0, &mut os_rng())
+    }
+
+    pub fn sample_polynomial_with_rng(
+        t: usize,
+        coef0: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Vec<P::Scalar> {
         let mut coefficients = vec![coef0.clone()];
         // sample the remaining coefficients randomly using secure randomness
-        let random_coefficients: Vec<FE> = (0..t).map(|_| ECScalar::new_random()).collect();
+        let random_coefficients: Vec<P::Scalar> =
+            (0..t).map(|_| ECScalar::new_random_with_rng(rng)).collect();
         coefficients.extend(random_coefficients);
         // return
         coefficients
     }
 
-    pub fn evaluate_polynomial(coefficients: &[FE], index_vec: &[usize]) -> Vec<FE> {
+    pub fn evaluate_polynomial(coefficients: &[P::Scalar], index_vec: &[usize]) -> Vec<P::Scalar> {
         (0..index_vec.len())
             .map(|point| {
                 let point_bn = BigInt::from(index_vec[point] as u32);
 
-                VerifiableSS::mod_evaluate_polynomial(coefficients, ECScalar::from(&point_bn))
+                Self::mod_evaluate_polynomial(coefficients, ECScalar::from(&point_bn))
             })
-            .collect::<Vec<FE>>()
+            .collect::<Vec<P::Scalar>>()
     }
 
-    pub fn mod_evaluate_polynomial(coefficients: &[FE], point: FE) -> FE {
+    pub fn mod_evaluate_polynomial(coefficients: &[P::Scalar], point: P::Scalar) -> P::Scalar {
         // evaluate using Horner's rule
         //  - to combine with fold we consider the coefficients in reverse order
         let mut reversed_coefficients = coefficients.iter().rev();
@@ -110,12 +126,12 @@ impl VerifiableSS {
         let head = reversed_coefficients.next().unwrap();
         let tail = reversed_coefficients;
         tail.fold(head.clone(), |partial, coef| {
-            let partial_times_point = partial.mul(&point.get_element());
-            partial_times_point.add(&coef.get_element())
+            let partial_times_point = partial * point.clone();
+            partial_times_point + coef.clone()
         })
     }
 
-    pub fn reconstruct(&self, indices: &[usize], shares: &[FE]) -> FE {
+    pub fn reconstruct(&self, indices: &[usize], shares: &[P::Scalar]) -> P::Scalar {
         assert_eq!(shares.len(), indices.len());
         assert!(shares.len() >= self.reconstruct_limit());
         // add one to indices to get points
@@ -125,8 +141,8 @@ impl VerifiableSS {
                 let index_bn = BigInt::from(*i as u32 + 1 as u32);
                 ECScalar::from(&index_bn)
             })
-            .collect::<Vec<FE>>();
-        VerifiableSS::lagrange_interpolation_at_zero(&points, &shares)
+            .collect::<Vec<P::Scalar>>();
+        Self::lagrange_interpolation_at_zero(&points, &shares)
     }
 
     // Performs a Lagrange interpolation in field Zp at the origin
@@ -139,7 +155,7 @@ impl VerifiableSS {
     // This is obviously less general than `newton_interpolation_general` as we
     // only get a single value, but it is much faster.
 
-    pub fn lagrange_interpolation_at_zero(points: &[FE], values: &[FE]) -> FE {
+    pub fn lagrange_interpolation_at_zero(points: &[P::Scalar], values: &[P::Scalar]) -> P::Scalar {
         let vec_len = values.len();
 
         assert_eq!(points.len(), vec_len);
@@ -150,11 +166,11 @@ impl VerifiableSS {
                 .map(|i| {
                     let xi = &points[i];
                     let yi = &values[i];
-                    let num: FE = ECScalar::from(&BigInt::one());
-                    let denum: FE = ECScalar::from(&BigInt::one());
+                    let num: P::Scalar = ECScalar::from(&BigInt::one());
+                    let denum: P::Scalar = ECScalar::from(&BigInt::one());
                     let num = points.iter().zip(0..vec_len).fold(num, |acc, x| {
                         if i != x.1 {
-                            acc * x.0
+                            acc * x.0.clone()
                         } else {
                             acc
                         }
@@ -168,22 +184,22 @@ impl VerifiableSS {
                         }
                     });
                     let denum = denum.invert();
-                    num * denum * yi
+                    num * denum * yi.clone()
                 })
-                .collect::<Vec<FE>>();
+                .collect::<Vec<P::Scalar>>();
         let mut lag_coef_iter = lag_coef.iter();
         let head = lag_coef_iter.next().unwrap();
         let tail = lag_coef_iter;
-        tail.fold(head.clone(), |acc, x| acc.add(&x.get_element()))
+        tail.fold(head.clone(), |acc, x| acc + x.clone())
     }
 
-    pub fn validate_share(&self, secret_share: &FE, index: usize) -> Result<(), (ErrorSS)> {
-        let G: GE = ECPoint::generator();
-        let ss_point = G * secret_share;
+    pub fn validate_share(&self, secret_share: &P::Scalar, index: usize) -> Result<(), (ErrorSS)> {
+        // the share is secret: constant-time G * share, the MSM only touches public values
+        let ss_point = P::generator() * secret_share.clone();
         self.validate_share_public(&ss_point, index)
     }
 
-    pub fn validate_share_public(&self, ss_point: &GE, index: usize) -> Result<(), (ErrorSS)> {
+    pub fn validate_share_public(&self, ss_point: &P, index: usize) -> Result<(), (ErrorSS)> {
         let comm_to_point = self.get_point_commitment(index);
         if *ss_point == comm_to_point {
             Ok(())
@@ -192,33 +208,40 @@ impl VerifiableSS {
         }
     }
 
-    pub fn get_point_commitment(&self, index: usize) -> GE {
-        let index_fe: FE = ECScalar::from(&BigInt::from(index as u32));
-        let mut comm_iterator = self.commitments.iter().rev();
-        let head = comm_iterator.next().unwrap();
-        let tail = comm_iterator;
-        let comm_to_point = tail.fold(head.clone(), |acc, x: &GE| {
-            x.clone() + acc * index_fe.clone()
-        });
-        comm_to_point
+    pub fn get_point_commitment(&self, index: usize) -> P {
+        let powers = Self::index_powers(index, self.commitments.len());
+        P::multi_scalar_mul(&self.commitments, &powers)
+    }
+
+    // 1, index, index^2, ..., index^(len-1)
+    fn index_powers(index: usize, len: usize) -> Vec<P::Scalar> {
+        let index_fe: P::Scalar = ECScalar::from(&BigInt::from(index as u32));
+        let mut power: P::Scalar = ECScalar::from(&BigInt::one());
+        (0..len)
+            .map(|_| {
+                let current = power.clone();
+                power *= index_fe.clone();
+                current
+            })
+            .collect()
     }
 
     //compute \lambda_{index,S}, a lagrangian coefficient that change the (t,n) scheme to (|S|,|S|)
     // used in http://stevengoldfeder.com/papers/GG18.pdf
-    pub fn map_share_to_new_params(&self, index: usize, s: &[usize]) -> FE {
+    pub fn map_share_to_new_params(&self, index: usize, s: &[usize]) -> P::Scalar {
         let s_len = s.len();
         //     assert!(s_len > self.reconstruct_limit());
         // add one to indices to get points
-        let points: Vec<FE> = (0..self.parameters.share_count)
+        let points: Vec<P::Scalar> = (0..self.parameters.share_count)
             .map(|i| {
                 let index_bn = BigInt::from(i as u32 + 1 as u32);
                 ECScalar::from(&index_bn)
             })
-            .collect::<Vec<FE>>();
+            .collect::<Vec<P::Scalar>>();
 
         let xi = &points[index];
-        let num: FE = ECScalar::from(&BigInt::one());
-        let denum: FE = ECScalar::from(&BigInt::one());
+        let num: P::Scalar = ECScalar::from(&BigInt::one());
+        let denum: P::Scalar = ECScalar::from(&BigInt::one());
         let num = (0..s_len).fold(num, |acc, i| {
             if s[i] != index {
                 acc * points[s[i]].clone()
@@ -252,7 +275,7 @@ mod tests {
     fn test_secret_sharing_3_out_of_5_at_indices() {
         let secret: FE = ECScalar::new_random();
         let parties = [1, 2, 4, 5, 6];
-        let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(3, 5, &secret, &parties);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share_at_indices(3, 5, &secret, &parties);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -270,7 +293,7 @@ mod tests {
     fn test_secret_sharing_3_out_of_5() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(3, 5, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(3, 5, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -313,7 +336,7 @@ mod tests {
     fn test_secret_sharing_3_out_of_7() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(3, 7, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(3, 7, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -351,7 +374,7 @@ mod tests {
     fn test_secret_sharing_1_out_of_2() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(1, 2, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(1, 2, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
@@ -381,14 +404,14 @@ mod tests {
     fn test_secret_sharing_1_out_of_3() {
         let secret: FE = ECScalar::new_random();
 
-        let (vss_scheme, secret_shares) = VerifiableSS::share(1, 3, &secret);
+        let (vss_scheme, secret_shares) = VerifiableSS::<GE>::share(1, 3, &secret);
 
         let mut shares_vec = Vec::new();
         shares_vec.push(secret_shares[0].clone());
         shares_vec.push(secret_shares[1].clone());
 
         // test commitment to point and sum of commitments
-        let (vss_scheme2, secret_shares2) = VerifiableSS::share(1, 3, &secret);
+        let (vss_scheme2, secret_shares2) = VerifiableSS::<GE>::share(1, 3, &secret);
         let sum = secret_shares[0].clone() + secret_shares2[0].clone();
         let point_comm1 = vss_scheme.get_point_commitment(1);
         let point_comm2 = vss_scheme.get_point_commitment(2);
@@ -419,4 +442,40 @@ mod tests {
         assert_eq!(w, secret_reconstructed);
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_commitments_summing_to_identity() {
+        // adversarial commitments C0 = G, C1 = -G: the commitment to share 1 is the point at infinity
+        let g = GE::generator();
+        let minus_one: FE = ECScalar::from(&(FE::q() - BigInt::from(1u32)));
+        let vss_scheme = VerifiableSS {
+            parameters: ShamirSecretSharing {
+                threshold: 1,
+                share_count: 2,
+            },
+            commitments: vec![g.clone(), g.clone() * minus_one],
+        };
+        assert!(vss_scheme.get_point_commitment(1).is_identity());
+        assert!(vss_scheme.validate_share(&FE::zero(), 1).is_ok());
+        assert!(vss_scheme.validate_share(&FE::new_random(), 1).is_err());
+        assert!(vss_scheme.validate_share(&FE::new_random(), 2).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_secret_sharing_2_out_of_4_p256() {
+        use crate::curv::elliptic::curves::p256;
+        let secret: p256::FE = ECScalar::new_random();
+
+        let (vss_scheme, secret_shares) = VerifiableSS::<p256::GE>::share(2, 4, &secret);
+        for (i, share) in secret_shares.iter().enumerate() {
+            assert!(vss_scheme.validate_share(share, i + 1).is_ok());
+        }
+        let bad_share = secret_shares[0].clone() + secret_shares[1].clone();
+        assert!(vss_scheme.validate_share(&bad_share, 1).is_err());
+
+        let shares_vec = vec![secret_shares[1].clone(), secret_shares[2].clone(), secret_shares[3].clone()];
+        let secret_reconstructed = vss_scheme.reconstruct(&[1, 2, 3], &shares_vec);
+        assert_eq!(secret, secret_reconstructed);
+    }
 }
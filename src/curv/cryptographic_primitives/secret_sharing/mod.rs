@@ -6,3 +6,4 @@
 */
 
 pub mod feldman_vss;
+pub mod pvss;
@@ -0,0 +1,444 @@
+#![allow(non_snake_case)]
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::os_rng;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::curv::cryptographic_primitives::proofs::sigma_ec_ddh::*;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::*;
+use crate::ErrorSS::{self, DecryptShareError, VerifyShareError};
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+/// Publicly verifiable secret sharing on top of Feldman VSS.
+///
+/// The share of party i is encrypted bit by bit under its public key Y_i with ElGamal "in the
+/// exponent": bit b_k is sent as (r_k*G, b_k*G + r_k*Y_i). Every pair comes with a proof that it
+/// encrypts 0 or 1, and the share with a DDH proof that
+/// sum 2^k*(b_k*G + r_k*Y_i) - S_i = (sum 2^k*r_k)*Y_i, where S_i is the Feldman commitment to
+/// share i, i.e. that the encrypted bits add up to the committed share. Anyone holding the
+/// dealing and the public keys can check that every party receives a valid share, so a party
+/// claiming a bad share can be told apart from a cheating dealer.
+///
+/// Party i decrypts with y_i, where Y_i = y_i*G: b_k*G = c2 - y_i*c1 is either zero or G.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PubliclyVerifiableSS<P: ECPoint = GE> {
+    pub vss: VerifiableSS<P>,
+    /// the share of the party with index i is at position i - 1
+    pub encrypted_shares: Vec<EncryptedShare<P>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EncryptedShare<P: ECPoint = GE> {
+    /// least significant bit first
+    pub bits: Vec<BitCiphertext<P>>,
+    pub bit_proofs: Vec<BitProof<P>>,
+    pub consistency_proof: ECDDHProof<P>,
+}
+
+/// ElGamal encryption (c1, c2) = (r*G, b*G + r*Y) of a bit b.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BitCiphertext<P: ECPoint = GE> {
+    pub c1: P,
+    pub c2: P,
+}
+
+/// Proof that a `BitCiphertext` encrypts 0 or 1: the OR of the DDH statements
+/// (G, c1, Y, c2) and (G, c1, Y, c2 - G), with the branch that does not hold simulated
+/// (Cramer, Damgård, Schoenmakers). (e_b, z_b) is the transcript of branch b.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BitProof<P: ECPoint = GE> {
+    pub e0: P::Scalar,
+    pub e1: P::Scalar,
+    pub z0: P::Scalar,
+    pub z1: P::Scalar,
+}
+
+const PVSS_LABEL: &[u8] = b"emerald-city/pvss";
+
+impl<P: ECPoint> PubliclyVerifiableSS<P> {
+    /// Shares `secret` among `recipient_keys.len()` parties, encrypting the share of index i
+    /// to `recipient_keys[i - 1]`. The plain shares are returned as well.
+    pub fn share(
+        t: usize,
+        secret: &P::Scalar,
+        recipient_keys: &[P],
+    ) -> (PubliclyVerifiableSS<P>, Vec<P::Scalar>) {
+        Self::share_with_rng(t, secret, recipient_keys, &mut os_rng())
+    }
+
+    pub fn share_with_rng(
+        t: usize,
+        secret: &P::Scalar,
+        recipient_keys: &[P],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (PubliclyVerifiableSS<P>, Vec<P::Scalar>) {
+        let (vss, secret_shares) =
+            VerifiableSS::<P>::share_with_rng(t, recipient_keys.len(), secret, rng);
+        let encrypted_shares = secret_shares
+            .iter()
+            .zip(recipient_keys)
+            .enumerate()
+            .map(|(i, (share, key))| {
+                let transcript = share_transcript(&vss, i + 1, key);
+                let commitment = vss.get_point_commitment(i + 1);
+                EncryptedShare::encrypt(&transcript, share, key, &commitment, rng)
+            })
+            .collect::<Vec<EncryptedShare<P>>>();
+        (
+            PubliclyVerifiableSS {
+                vss,
+                encrypted_shares,
+            },
+            secret_shares,
+        )
+    }
+
+    /// Checks the encryption of every share against the commitments.
+    pub fn verify(&self, recipient_keys: &[P]) -> Result<(), ErrorSS> {
+        if recipient_keys.len() != self.vss.parameters.share_count
+            || self.encrypted_shares.len() != recipient_keys.len()
+        {
+            return Err(VerifyShareError);
+        }
+        let all_valid = self
+            .encrypted_shares
+            .iter()
+            .zip(recipient_keys)
+            .enumerate()
+            .all(|(i, (encrypted_share, key))| {
+                let transcript = share_transcript(&self.vss, i + 1, key);
+                encrypted_share.verify(&transcript, key, &self.vss.get_point_commitment(i + 1))
+            });
+        if all_valid {
+            Ok(())
+        } else {
+            Err(VerifyShareError)
+        }
+    }
+
+    /// Decrypts the share of party `index` with its secret key and validates it against the
+    /// commitments.
+    pub fn decrypt_share(&self, index: usize, dk: &P::Scalar) -> Result<P::Scalar, ErrorSS> {
+        if index == 0 || index > self.encrypted_shares.len() {
+            return Err(DecryptShareError);
+        }
+        let share = self.encrypted_shares[index - 1].decrypt(dk)?;
+        self.vss.validate_share(&share, index)?;
+        Ok(share)
+    }
+}
+
+/// Binds the proofs for share `index` to the dealing and to the recipient.
+fn share_transcript<P: ECPoint>(
+    vss: &VerifiableSS<P>,
+    index: usize,
+    recipient_key: &P,
+) -> Transcript {
+    let mut transcript = Transcript::new(PVSS_LABEL);
+    transcript.append_u64(b"threshold", vss.parameters.threshold as u64);
+    for commitment in &vss.commitments {
+        transcript.append_point(b"commitment", commitment);
+    }
+    transcript.append_u64(b"index", index as u64);
+    transcript.append_point(b"recipient", recipient_key);
+    transcript
+}
+
+/// 1, 2, 4, ..., 2^(len - 1) as scalars
+fn powers_of_two<S: ECScalar>(len: usize) -> Vec<S> {
+    let mut power: S = ECScalar::from(&BigInt::one());
+    let mut powers = Vec::with_capacity(len);
+    for _ in 0..len {
+        powers.push(power.clone());
+        power = power.clone() + power;
+    }
+    powers
+}
+
+fn share_bit_length<S: ECScalar>() -> usize {
+    S::q().bits()
+}
+
+impl<P: ECPoint> EncryptedShare<P> {
+    fn encrypt(
+        transcript: &Transcript,
+        share: &P::Scalar,
+        key: &P,
+        commitment: &P,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let nbits = share_bit_length::<P::Scalar>();
+        let G = P::generator();
+        let mut share_bytes = share.to_big_int().to_bytes_le();
+        share_bytes.resize(nbits / 8 + 1, 0);
+
+        let mut randomness = Vec::with_capacity(nbits);
+        let mut bits = Vec::with_capacity(nbits);
+        let mut bit_proofs = Vec::with_capacity(nbits);
+        for k in 0..nbits {
+            let bit = (share_bytes[k / 8] >> (k % 8)) & 1 == 1;
+            let r = P::Scalar::new_random_with_rng(rng);
+            let c1 = G.clone() * r.clone();
+            let c2 = if bit {
+                key.clone() * r.clone() + G.clone()
+            } else {
+                key.clone() * r.clone()
+            };
+            let ciphertext = BitCiphertext { c1, c2 };
+            let mut bit_transcript = transcript.clone();
+            bit_transcript.append_u64(b"bit", k as u64);
+            bit_proofs.push(BitProof::prove(
+                &mut bit_transcript,
+                key,
+                &ciphertext,
+                bit,
+                &r,
+                rng,
+            ));
+            bits.push(ciphertext);
+            randomness.push(r);
+        }
+        share_bytes.zeroize();
+
+        let powers = powers_of_two::<P::Scalar>(nbits);
+        let mut r_sum = randomness
+            .iter()
+            .zip(&powers)
+            .map(|(r, power)| r.clone() * power.clone())
+            .sum::<P::Scalar>();
+        randomness.iter_mut().for_each(|r| r.zeroize());
+        let delta = consistency_statement(&bits, &powers, key, commitment);
+        let consistency_proof = ECDDHProof::prove_with_transcript_and_rng(
+            &mut transcript.clone(),
+            &ECDDHWitness { x: r_sum.clone() },
+            &delta,
+            rng,
+        );
+        r_sum.zeroize();
+
+        EncryptedShare {
+            bits,
+            bit_proofs,
+            consistency_proof,
+        }
+    }
+
+    fn verify(&self, transcript: &Transcript, key: &P, commitment: &P) -> bool {
+        let nbits = share_bit_length::<P::Scalar>();
+        if self.bits.len() != nbits || self.bit_proofs.len() != nbits {
+            return false;
+        }
+        let bits_valid = self
+            .bits
+            .iter()
+            .zip(&self.bit_proofs)
+            .enumerate()
+            .all(|(k, (ciphertext, proof))| {
+                let mut bit_transcript = transcript.clone();
+                bit_transcript.append_u64(b"bit", k as u64);
+                proof.verify(&mut bit_transcript, key, ciphertext)
+            });
+        if !bits_valid {
+            return false;
+        }
+        let powers = powers_of_two::<P::Scalar>(nbits);
+        let delta = consistency_statement(&self.bits, &powers, key, commitment);
+        ECDDHProof::verify_with_transcript(&mut transcript.clone(), &self.consistency_proof, &delta)
+            .is_ok()
+    }
+
+    fn decrypt(&self, dk: &P::Scalar) -> Result<P::Scalar, ErrorSS> {
+        let G = P::generator();
+        let mut share = BigInt::zero();
+        for (k, ciphertext) in self.bits.iter().enumerate() {
+            let bit_point = ciphertext.c2.clone() - ciphertext.c1.clone() * dk.clone();
+            if bit_point == G {
+                share += BigInt::one() << k;
+            } else if !bit_point.is_identity() {
+                return Err(DecryptShareError);
+            }
+        }
+        Ok(ECScalar::from(&(share % P::Scalar::q())))
+    }
+}
+
+/// (G, sum 2^k*c1_k, Y, sum 2^k*c2_k - S): a DDH tuple exactly when the bits encrypted in
+/// the c2_k add up to the discrete log of S.
+fn consistency_statement<P: ECPoint>(
+    bits: &[BitCiphertext<P>],
+    powers: &[P::Scalar],
+    key: &P,
+    commitment: &P,
+) -> ECDDHStatement<P> {
+    let c1 = bits.iter().map(|ct| ct.c1.clone()).collect::<Vec<P>>();
+    let c2 = bits.iter().map(|ct| ct.c2.clone()).collect::<Vec<P>>();
+    ECDDHStatement {
+        g1: P::generator(),
+        h1: P::multi_scalar_mul(&c1, powers),
+        g2: key.clone(),
+        h2: P::multi_scalar_mul(&c2, powers) - commitment.clone(),
+    }
+}
+
+impl<P: ECPoint> BitProof<P> {
+    /// The points (a1, a2) = (z*G - e*c1, z*Y - e*(c2 - b*G)) of branch b.
+    fn branch_commitments(
+        key: &P,
+        ciphertext: &BitCiphertext<P>,
+        bit: bool,
+        e: &P::Scalar,
+        z: &P::Scalar,
+    ) -> (P, P) {
+        let d = if bit {
+            ciphertext.c2.clone() - P::generator()
+        } else {
+            ciphertext.c2.clone()
+        };
+        let a1 = P::multi_scalar_mul(
+            &[P::generator(), ciphertext.c1.clone()],
+            &[z.clone(), -e.clone()],
+        );
+        let a2 = P::multi_scalar_mul(&[key.clone(), d], &[z.clone(), -e.clone()]);
+        (a1, a2)
+    }
+
+    fn challenge(
+        transcript: &mut Transcript,
+        key: &P,
+        ciphertext: &BitCiphertext<P>,
+        branch0: &(P, P),
+        branch1: &(P, P),
+    ) -> P::Scalar {
+        transcript.append_point(b"Y", key);
+        transcript.append_point(b"c1", &ciphertext.c1);
+        transcript.append_point(b"c2", &ciphertext.c2);
+        transcript.append_point(b"a1_0", &branch0.0);
+        transcript.append_point(b"a2_0", &branch0.1);
+        transcript.append_point(b"a1_1", &branch1.0);
+        transcript.append_point(b"a2_1", &branch1.1);
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    fn prove(
+        transcript: &mut Transcript,
+        key: &P,
+        ciphertext: &BitCiphertext<P>,
+        bit: bool,
+        r: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        // simulate the other branch
+        let e_sim = P::Scalar::new_random_with_rng(rng);
+        let z_sim = P::Scalar::new_random_with_rng(rng);
+        let simulated = Self::branch_commitments(key, ciphertext, !bit, &e_sim, &z_sim);
+
+        let mut s = P::Scalar::new_random_with_rng(rng);
+        let real = (P::generator() * s.clone(), key.clone() * s.clone());
+        let e = if bit {
+            Self::challenge(transcript, key, ciphertext, &simulated, &real)
+        } else {
+            Self::challenge(transcript, key, ciphertext, &real, &simulated)
+        };
+        let e_real = e - e_sim.clone();
+        let z_real = s.clone() + e_real.clone() * r.clone();
+        s.zeroize();
+
+        if bit {
+            BitProof {
+                e0: e_sim,
+                e1: e_real,
+                z0: z_sim,
+                z1: z_real,
+            }
+        } else {
+            BitProof {
+                e0: e_real,
+                e1: e_sim,
+                z0: z_real,
+                z1: z_sim,
+            }
+        }
+    }
+
+    fn verify(&self, transcript: &mut Transcript, key: &P, ciphertext: &BitCiphertext<P>) -> bool {
+        let branch0 = Self::branch_commitments(key, ciphertext, false, &self.e0, &self.z0);
+        let branch1 = Self::branch_commitments(key, ciphertext, true, &self.e1, &self.z1);
+        let e = Self::challenge(transcript, key, ciphertext, &branch0, &branch1);
+        e == self.e0.clone() + self.e1.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn keypairs(n: usize) -> (Vec<FE>, Vec<GE>) {
+        let dks = (0..n).map(|_| FE::new_random()).collect::<Vec<FE>>();
+        let eks = dks
+            .iter()
+            .map(|dk| GE::generator() * dk.clone())
+            .collect::<Vec<GE>>();
+        (dks, eks)
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_pvss_share_verify_decrypt() {
+        let (dks, eks) = keypairs(3);
+        let secret: FE = ECScalar::new_random();
+        let (pvss, shares) = PubliclyVerifiableSS::share(1, &secret, &eks);
+        assert!(pvss.verify(&eks).is_ok());
+        for i in 0..3 {
+            let share = pvss.decrypt_share(i + 1, &dks[i]).ok().unwrap();
+            assert_eq!(share, shares[i]);
+        }
+        // the wrong key decrypts to garbage
+        assert!(pvss.decrypt_share(1, &dks[1]).is_err());
+
+        let s = serde_json::to_string(&pvss).expect("Failed in serialization");
+        let des: PubliclyVerifiableSS = serde_json::from_str(&s).expect("Failed in deserialization");
+        assert_eq!(des, pvss);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_pvss_detects_bad_dealing() {
+        let (_, eks) = keypairs(3);
+        let secret: FE = ECScalar::new_random();
+        let (pvss, _) = PubliclyVerifiableSS::share(1, &secret, &eks);
+
+        // share 2 encrypted to the wrong key
+        let mut wrong_key = eks.clone();
+        wrong_key.swap(1, 2);
+        assert!(pvss.verify(&wrong_key).is_err());
+
+        // shares of another dealing
+        let (other, _) = PubliclyVerifiableSS::share(1, &secret, &eks);
+        let mut mixed = pvss.clone();
+        mixed.encrypted_shares[1] = other.encrypted_shares[1].clone();
+        assert!(mixed.verify(&eks).is_err());
+
+        // a flipped bit
+        let mut flipped = pvss.clone();
+        let bit = &mut flipped.encrypted_shares[0].bits[5];
+        bit.c2 = bit.c2.clone() + GE::generator();
+        assert!(flipped.verify(&eks).is_err());
+    }
+}
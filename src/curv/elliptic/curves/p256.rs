@@ -0,0 +1,773 @@
+#![allow(non_snake_case)]
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+// NIST P-256 (secp256r1) elliptic curve utility functions, backed by the pure Rust `p256` crate.
+//
+// The scalar wraps `p256::Scalar` and the point wraps `p256::AffinePoint`; group operations are
+// done in projective coordinates and normalized back to affine. Serialization follows the same
+// format as the secp256k1 backend: scalars as a hex string, points as {"x", "y"} hex coordinates.
+//
+
+use super::rand::{CryptoRng, RngCore};
+use super::traits::{ECPoint, ECScalar};
+use crate::curv::arithmetic::num_bigint::from;
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::{Converter, Samplable};
+use num_integer::Integer;
+use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use crate::curv::cryptographic_primitives::hashing::traits::Hash;
+use ::p256::elliptic_curve::group::prime::PrimeCurveAffine;
+use ::p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use ::p256::elliptic_curve::{Field, PrimeField};
+use ::p256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
+use num_traits::Num;
+use serde::de;
+use serde::de::{MapAccess, Visitor};
+use serde::ser;
+use serde::ser::SerializeStruct;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::ptr;
+use std::sync::atomic;
+use zeroize::Zeroize;
+
+use crate::ErrorKey;
+
+/// Order of the P-256 group.
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+const COOR_SIZE: usize = 32;
+
+lazy_static::lazy_static! {
+    static ref CURVE_ORDER_: BigInt = BigInt::from_bytes_be(&CURVE_ORDER);
+}
+
+pub type SK = Scalar;
+pub type PK = AffinePoint;
+
+#[derive(Clone, Debug)]
+pub struct Secp256r1Scalar {
+    fe: SK,
+}
+#[derive(Clone, Debug)]
+pub struct Secp256r1Point {
+    ge: PK,
+}
+
+pub type GE = Secp256r1Point;
+pub type FE = Secp256r1Scalar;
+
+fn pad_be(n: &BigInt) -> Vec<u8> {
+    let v = BigInt::to_vec(n);
+    assert!(v.len() <= COOR_SIZE);
+    let mut template = vec![0; COOR_SIZE - v.len()];
+    template.extend_from_slice(&v);
+    template
+}
+
+impl Secp256r1Point {
+    pub fn random_point() -> Secp256r1Point {
+        let random_scalar: Secp256r1Scalar = Secp256r1Scalar::new_random();
+        let base_point = Secp256r1Point::generator();
+        Secp256r1Point {
+            ge: base_point.scalar_mul(&random_scalar.get_element()).ge,
+        }
+    }
+
+    fn from_projective(point: ProjectivePoint) -> Secp256r1Point {
+        Secp256r1Point {
+            ge: point.to_affine(),
+        }
+    }
+
+    /// Point addition, total on the group.
+    fn combine(&self, other: &PK) -> Secp256r1Point {
+        Secp256r1Point::from_projective(ProjectivePoint::from(self.ge) + other)
+    }
+}
+
+impl Zeroize for FE {
+    fn zeroize(&mut self) {
+        unsafe { ptr::write_volatile(self, FE::zero()) };
+        atomic::fence(atomic::Ordering::SeqCst);
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+    }
+}
+
+impl ECScalar for Secp256r1Scalar {
+    type SecretKey = SK;
+    type ScalarLength = typenum::U32;
+
+    fn new_random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Secp256r1Scalar {
+        // rejection sampling: zero is drawn again, as with the secp256k1 backend
+        let q = FE::q();
+        loop {
+            let k = BigInt::sample_below_with_rng(&q, rng);
+            if k != BigInt::from(0u32) {
+                break ECScalar::from(&k);
+            }
+        }
+    }
+
+    fn zero() -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: Scalar::zero(),
+        }
+    }
+
+    fn get_element(&self) -> SK {
+        self.fe
+    }
+
+    fn set_element(&mut self, element: SK) {
+        self.fe = element
+    }
+
+    fn from(n: &BigInt) -> Secp256r1Scalar {
+        let n_reduced = n.mod_floor(&FE::q());
+        let bytes = pad_be(&n_reduced);
+        Secp256r1Scalar {
+            fe: Scalar::from_repr(FieldBytes::clone_from_slice(&bytes)).unwrap(),
+        }
+    }
+
+    fn to_big_int(&self) -> BigInt {
+        from(self.fe.to_bytes().as_ref())
+    }
+
+    fn q() -> BigInt {
+        from(CURVE_ORDER.as_ref())
+    }
+
+    fn group_order() -> &'static BigInt {
+        &CURVE_ORDER_
+    }
+
+    fn add(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe + other,
+        }
+    }
+
+    fn mul(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe * other,
+        }
+    }
+
+    fn sub(&self, other: &SK) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe - other,
+        }
+    }
+
+    /// Zero has no inverse and maps to zero, as with the secp256k1 backend.
+    fn invert(&self) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe.invert().unwrap_or(Scalar::zero()),
+        }
+    }
+}
+
+impl Mul<Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn mul(self, other: Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::mul(&self, &other.get_element())
+    }
+}
+
+impl Add<Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn add(self, other: Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn add(self, other: &'o Secp256r1Scalar) -> Secp256r1Scalar {
+        ECScalar::add(&self, &other.get_element())
+    }
+}
+
+impl Neg for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn neg(self) -> Secp256r1Scalar {
+        Secp256r1Scalar { fe: -self.fe }
+    }
+}
+
+impl Sub<Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn sub(self, other: Secp256r1Scalar) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe - other.fe,
+        }
+    }
+}
+
+impl<'o> Sub<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    type Output = Secp256r1Scalar;
+    fn sub(self, other: &'o Secp256r1Scalar) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: self.fe - other.fe,
+        }
+    }
+}
+
+impl AddAssign<Secp256r1Scalar> for Secp256r1Scalar {
+    fn add_assign(&mut self, other: Secp256r1Scalar) {
+        self.fe += other.fe;
+    }
+}
+
+impl SubAssign<Secp256r1Scalar> for Secp256r1Scalar {
+    fn sub_assign(&mut self, other: Secp256r1Scalar) {
+        self.fe -= other.fe;
+    }
+}
+
+impl MulAssign<Secp256r1Scalar> for Secp256r1Scalar {
+    fn mul_assign(&mut self, other: Secp256r1Scalar) {
+        self.fe *= other.fe;
+    }
+}
+
+impl Sum for Secp256r1Scalar {
+    fn sum<I: Iterator<Item = Secp256r1Scalar>>(iter: I) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: iter.fold(Scalar::zero(), |acc, x| acc + x.fe),
+        }
+    }
+}
+
+impl<'o> Sum<&'o Secp256r1Scalar> for Secp256r1Scalar {
+    fn sum<I: Iterator<Item = &'o Secp256r1Scalar>>(iter: I) -> Secp256r1Scalar {
+        Secp256r1Scalar {
+            fe: iter.fold(Scalar::zero(), |acc, x| acc + x.fe),
+        }
+    }
+}
+
+impl Serialize for Secp256r1Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_big_int().to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256r1Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256r1Scalar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Secp256r1ScalarVisitor)
+    }
+}
+
+struct Secp256r1ScalarVisitor;
+
+impl<'de> Visitor<'de> for Secp256r1ScalarVisitor {
+    type Value = Secp256r1Scalar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Secp256r1Scalar")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Secp256r1Scalar, E> {
+        let v = BigInt::from_str_radix(s, 16).map_err(E::custom)?;
+        Ok(ECScalar::from(&v))
+    }
+}
+
+impl PartialEq for Secp256r1Scalar {
+    fn eq(&self, other: &Secp256r1Scalar) -> bool {
+        self.fe == other.fe
+    }
+}
+
+impl PartialEq for Secp256r1Point {
+    fn eq(&self, other: &Secp256r1Point) -> bool {
+        self.ge == other.ge
+    }
+}
+
+impl Zeroize for GE {
+    fn zeroize(&mut self) {
+        unsafe { ptr::write_volatile(self, GE::generator()) };
+        atomic::fence(atomic::Ordering::SeqCst);
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+    }
+}
+
+impl ECPoint for Secp256r1Point {
+    type SecretKey = SK;
+    type PublicKey = PK;
+    type Scalar = Secp256r1Scalar;
+    type CompressedPointLength = typenum::U33;
+    type UncompressedPointLength = typenum::U65;
+
+    fn generator() -> Secp256r1Point {
+        Secp256r1Point {
+            ge: AffinePoint::generator(),
+        }
+    }
+
+    /// Same recipe as the secp256k1 backend: hash the compressed P-256 generator and keep
+    /// re-hashing until the digest, read as an x coordinate with the `02` (even y) prefix,
+    /// decodes to a point. Nobody knows its discrete log relative to `generator()`.
+    fn base_point2() -> Secp256r1Point {
+        let g: Secp256r1Point = ECPoint::generator();
+        let mut hash = HSha256::create_hash(&[&g.bytes_compressed_to_big_int()]);
+        loop {
+            let mut template: Vec<u8> = vec![2];
+            template.extend_from_slice(&pad_be(&hash));
+            if let Ok(point) = Secp256r1Point::from_bytes(&template) {
+                return Secp256r1Point {
+                    ge: point.ge,
+                };
+            }
+            hash = HSha256::create_hash(&[&hash]);
+        }
+    }
+
+    fn identity() -> Secp256r1Point {
+        Secp256r1Point {
+            ge: AffinePoint::identity(),
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.ge.is_identity().into()
+    }
+
+    fn get_element(&self) -> Option<PK> {
+        Some(self.ge).filter(|_| !self.is_identity())
+    }
+
+    fn bytes_compressed_to_big_int(&self) -> BigInt {
+        from(self.ge.to_encoded_point(true).as_bytes())
+    }
+
+    fn x_coor(&self) -> Option<BigInt> {
+        let encoded = self.ge.to_encoded_point(false);
+        encoded.x().map(|x| from(x.as_ref()))
+    }
+
+    fn y_coor(&self) -> Option<BigInt> {
+        let encoded = self.ge.to_encoded_point(false);
+        encoded.y().map(|y| from(y.as_ref()))
+    }
+
+    /// Accepts SEC1 encoded points only: 33 bytes compressed, 65 bytes uncompressed or the
+    /// single byte `00` for the point at infinity.
+    fn from_bytes(bytes: &[u8]) -> Result<Secp256r1Point, ErrorKey> {
+        match (bytes.len(), bytes.first()) {
+            (1, Some(0)) => return Ok(Secp256r1Point::identity()),
+            (33, Some(2)) | (33, Some(3)) | (65, Some(4)) => (),
+            _ => return Err(ErrorKey::InvalidPublicKey),
+        }
+        let encoded = EncodedPoint::from_bytes(bytes).map_err(|_| ErrorKey::InvalidPublicKey)?;
+        let ge: Option<AffinePoint> = AffinePoint::from_encoded_point(&encoded).into();
+        ge.map(|ge| Secp256r1Point {
+            ge,
+        })
+        .ok_or(ErrorKey::InvalidPublicKey)
+    }
+
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        self.to_bytes(false)
+    }
+
+    fn scalar_mul(&self, fe: &SK) -> Secp256r1Point {
+        Secp256r1Point::from_projective(ProjectivePoint::from(self.ge) * fe)
+    }
+
+    fn add_point(&self, other: &PK) -> Option<Secp256r1Point> {
+        Some(self.combine(other)).filter(|sum| !sum.is_identity())
+    }
+
+    fn sub_point(&self, other: &PK) -> Option<Secp256r1Point> {
+        Some(self.combine(&-*other)).filter(|difference| !difference.is_identity())
+    }
+
+    /// Serializes a point in (un)compressed form
+    fn to_bytes(&self, compressed: bool) -> Vec<u8> {
+        self.ge.to_encoded_point(compressed).as_bytes().to_vec()
+    }
+
+    fn from_coor(x: &BigInt, y: &BigInt) -> Secp256r1Point {
+        let mut v = vec![4 as u8];
+        v.extend(pad_be(x));
+        v.extend(pad_be(y));
+        Secp256r1Point::from_bytes(&v).expect("invalid P-256 point coordinates")
+    }
+}
+
+impl Mul<Secp256r1Scalar> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl<'o> Mul<&'o Secp256r1Scalar> for &'o Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn mul(self, other: &'o Secp256r1Scalar) -> Self::Output {
+        self.scalar_mul(&other.get_element())
+    }
+}
+
+impl Add<Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: Secp256r1Point) -> Self::Output {
+        self.combine(&other.ge)
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: &'o Secp256r1Point) -> Self::Output {
+        self.combine(&other.ge)
+    }
+}
+
+impl<'o> Add<&'o Secp256r1Point> for &'o Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn add(self, other: &'o Secp256r1Point) -> Self::Output {
+        self.combine(&other.ge)
+    }
+}
+
+impl Neg for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn neg(self) -> Secp256r1Point {
+        Secp256r1Point { ge: -self.ge }
+    }
+}
+
+impl Sub<Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn sub(self, other: Secp256r1Point) -> Self::Output {
+        self.combine(&-other.ge)
+    }
+}
+
+impl<'o> Sub<&'o Secp256r1Point> for Secp256r1Point {
+    type Output = Secp256r1Point;
+    fn sub(self, other: &'o Secp256r1Point) -> Self::Output {
+        self.combine(&-other.ge)
+    }
+}
+
+impl AddAssign<Secp256r1Point> for Secp256r1Point {
+    fn add_assign(&mut self, other: Secp256r1Point) {
+        *self = self.combine(&other.ge);
+    }
+}
+
+impl<'o> AddAssign<&'o Secp256r1Point> for Secp256r1Point {
+    fn add_assign(&mut self, other: &'o Secp256r1Point) {
+        *self = self.combine(&other.ge);
+    }
+}
+
+impl SubAssign<Secp256r1Point> for Secp256r1Point {
+    fn sub_assign(&mut self, other: Secp256r1Point) {
+        *self = self.combine(&-other.ge);
+    }
+}
+
+impl MulAssign<Secp256r1Scalar> for Secp256r1Point {
+    fn mul_assign(&mut self, other: Secp256r1Scalar) {
+        *self = self.scalar_mul(&other.fe);
+    }
+}
+
+impl Sum for Secp256r1Point {
+    fn sum<I: Iterator<Item = Secp256r1Point>>(iter: I) -> Secp256r1Point {
+        Secp256r1Point::from_projective(
+            iter.fold(ProjectivePoint::IDENTITY, |acc, x| acc + x.ge),
+        )
+    }
+}
+
+impl<'o> Sum<&'o Secp256r1Point> for Secp256r1Point {
+    fn sum<I: Iterator<Item = &'o Secp256r1Point>>(iter: I) -> Secp256r1Point {
+        Secp256r1Point::from_projective(
+            iter.fold(ProjectivePoint::IDENTITY, |acc, x| acc + x.ge),
+        )
+    }
+}
+
+/// As with secp256k1, the point at infinity has no coordinates and does not serialize.
+impl Serialize for Secp256r1Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (x, y) = match (self.x_coor(), self.y_coor()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(ser::Error::custom("the point at infinity has no coordinates")),
+        };
+        let mut state = serializer.serialize_struct("Secp256r1Point", 2)?;
+        state.serialize_field("x", &x.to_hex())?;
+        state.serialize_field("y", &y.to_hex())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256r1Point {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256r1Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = &["x", "y"];
+        deserializer.deserialize_struct("Secp256r1Point", fields, Secp256r1PointVisitor)
+    }
+}
+
+struct Secp256r1PointVisitor;
+
+impl<'de> Visitor<'de> for Secp256r1PointVisitor {
+    type Value = Secp256r1Point;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Secp256r1Point")
+    }
+
+    fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<Secp256r1Point, E::Error> {
+        let mut x = String::new();
+        let mut y = String::new();
+
+        while let Some(ref key) = map.next_key::<String>()? {
+            let v = map.next_value::<String>()?;
+            if key == "x" {
+                x = v
+            } else if key == "y" {
+                y = v
+            } else {
+                return Err(de::Error::unknown_field(key, &["x", "y"]));
+            }
+        }
+
+        let bx = BigInt::from_hex(&x);
+        let by = BigInt::from_hex(&y);
+        if bx.bits() > COOR_SIZE * 8 || by.bits() > COOR_SIZE * 8 {
+            return Err(de::Error::custom("coordinate out of range"));
+        }
+
+        let mut v = vec![4 as u8];
+        v.extend(pad_be(&bx));
+        v.extend(pad_be(&by));
+        Secp256r1Point::from_bytes(&v).map_err(|_| de::Error::custom("invalid P-256 point"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Secp256r1Point, FE, GE};
+    use crate::curv::arithmetic::num_bigint::BigInt;
+    use crate::curv::arithmetic::traits::{Converter, Modulo};
+    use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+    use crate::ErrorKey;
+    use rand::{CryptoRng, RngCore};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    /// Yields zero bytes for its first fill and 0x01 bytes afterwards.
+    struct ZeroFirstRng {
+        filled: bool,
+    }
+
+    impl RngCore for ZeroFirstRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut buf = [0u8; 4];
+            self.fill_bytes(&mut buf);
+            u32::from_le_bytes(buf)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut buf = [0u8; 8];
+            self.fill_bytes(&mut buf);
+            u64::from_le_bytes(buf)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let byte = if self.filled { 1 } else { 0 };
+            self.filled = true;
+            dest.iter_mut().for_each(|b| *b = byte);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for ZeroFirstRng {}
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_scalar_and_point() {
+        let scalar: FE = ECScalar::from(&BigInt::from(123456 as u32));
+        let s = serde_json::to_string(&scalar).expect("Failed in serialization");
+        assert_eq!(s, "\"1e240\"");
+        let des: FE = serde_json::from_str(&s).expect("Failed in deserialization");
+        assert_eq!(des, scalar);
+
+        for pk in vec![GE::generator(), GE::base_point2(), Secp256r1Point::random_point()] {
+            let s = serde_json::to_string(&pk).expect("Failed in serialization");
+            let des_pk: GE = serde_json::from_str(&s).expect("Failed in deserialization");
+            assert_eq!(des_pk, pk);
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serdes_bad_pk() {
+        let pk = GE::generator();
+        let s = serde_json::to_string(&pk).expect("Failed in serialization");
+        let s: String = s.replace("6b17", "6b18");
+        assert!(serde_json::from_str::<GE>(&s).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_generator_vector() {
+        let g = GE::generator();
+        assert_eq!(
+            g.x_coor().unwrap().to_hex(),
+            "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+        );
+        assert_eq!(
+            g.y_coor().unwrap().to_hex(),
+            "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"
+        );
+        // 2G from the NIST point multiplication test vectors
+        let two: FE = ECScalar::from(&BigInt::from(2 as u32));
+        assert_eq!(
+            (g * two).x_coor().unwrap().to_hex(),
+            "7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_bytes_roundtrip() {
+        let p = Secp256r1Point::random_point();
+        assert_eq!(GE::from_bytes(&p.to_bytes(true)).unwrap(), p);
+        assert_eq!(GE::from_bytes(&p.to_bytes(false)).unwrap(), p);
+        assert_eq!(
+            GE::from_bytes(&p.to_bytes(false)[1..]).unwrap_err(),
+            ErrorKey::InvalidPublicKey
+        );
+        let mut bad = p.to_bytes(true);
+        bad[0] = 5;
+        assert_eq!(GE::from_bytes(&bad).unwrap_err(), ErrorKey::InvalidPublicKey);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_scalar_arithmetic() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let q = FE::q();
+        assert_eq!(
+            (a.clone() * b.clone()).to_big_int(),
+            BigInt::mod_mul(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            a.sub(&b.get_element()).to_big_int(),
+            BigInt::mod_sub(&a.to_big_int(), &b.to_big_int(), &q)
+        );
+        assert_eq!(
+            a.invert().to_big_int(),
+            BigInt::mod_inv(&a.to_big_int(), &q)
+        );
+        assert_eq!(FE::zero().invert(), FE::zero());
+        let q_plus_one: FE = ECScalar::from(&(&q + BigInt::from(1u32)));
+        assert_eq!(q_plus_one.to_big_int(), BigInt::from(1u32));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_new_random_rejects_zero() {
+        let mut rng = ZeroFirstRng { filled: false };
+        let k: FE = ECScalar::new_random_with_rng(&mut rng);
+        assert_ne!(k, FE::zero());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_minus_point() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let base: GE = ECPoint::generator();
+        let point_ab1 = base.clone() * a.sub(&b.get_element());
+        let point_ab2 = (base.clone() * a)
+            .sub_point(&(base.clone() * b).get_element().unwrap())
+            .unwrap();
+        assert_eq!(point_ab1, point_ab2);
+        assert!(GE::identity().get_element().is_none());
+        assert!(base.sub_point(&base.get_element().unwrap()).is_none());
+        let o = GE::identity();
+        assert!(serde_json::to_string(&o).is_err());
+        assert_eq!(o.to_bytes(true), vec![0]);
+        assert_eq!(GE::from_bytes(&o.to_bytes(false)).unwrap(), o);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_operators() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let g = GE::generator();
+
+        assert_eq!(-a.clone() + a.clone(), FE::zero());
+        let mut c = a.clone();
+        c += b.clone();
+        c -= a.clone();
+        c *= a.clone();
+        assert_eq!(c, a.clone() * b.clone());
+        assert_eq!(vec![a.clone(), b.clone(), -a.clone()].iter().sum::<FE>(), b);
+
+        let p_a = g.clone() * a.clone();
+        let p_b = g.clone() * b.clone();
+        assert_eq!(p_a.clone() - p_b.clone(), g.clone() * (a.clone() - b.clone()));
+        let mut p = p_a.clone();
+        p += p_b.clone();
+        p -= p_a.clone();
+        p *= a.clone();
+        assert_eq!(p, g.clone() * (a.clone() * b.clone()));
+        let points = vec![p_a.clone(), p_b.clone(), -p_a];
+        assert_eq!(points.into_iter().sum::<GE>(), p_b);
+    }
+}
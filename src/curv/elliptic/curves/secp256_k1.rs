@@ -16,7 +16,7 @@
 // The Public Key codec: Point <> SecretKey
 //
 
-use super::rand::{thread_rng, Rng};
+use super::rand::{CryptoRng, RngCore};
 use super::secp256k1::{PublicKey, SecretKey};
 use super::traits::{ECPoint, ECScalar};
 use crate::curv::arithmetic::num_bigint::from;
@@ -27,20 +27,24 @@ use crate::curv::cryptographic_primitives::hashing::constants::{
 };
 use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::curv::cryptographic_primitives::hashing::traits::Hash;
-use num_traits::Num;
+use num_traits::{Num, Zero};
 use serde::de;
 use serde::de::{MapAccess, Visitor};
+use serde::ser;
 use serde::ser::SerializeStruct;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
 use std::fmt;
-use std::ops::{Add, Mul};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::ptr;
 use std::sync::atomic;
 use zeroize::Zeroize;
 
-use super::secp256k1::curve::Scalar;
-use generic_array::GenericArray;
+use super::secp256k1::curve::{Affine, Jacobian, Scalar};
+
+/// Window width of the per-point tables in `multi_scalar_mul`.
+const STRAUS_WINDOW: usize = 4;
 
 lazy_static::lazy_static! {
     static ref CURVE_ORDER_: BigInt = BigInt::from_bytes_be(&CURVE_ORDER);
@@ -55,10 +59,12 @@ pub struct Secp256k1Scalar {
     purpose: &'static str,
     fe: SK,
 }
+/// A point on secp256k1. `ge` is `None` for the point at infinity, which libsecp256k1's
+/// `PublicKey` cannot represent.
 #[derive(Clone, Debug)]
 pub struct Secp256k1Point {
     purpose: &'static str,
-    ge: PK,
+    ge: Option<PK>,
 }
 
 pub type GE = Secp256k1Point;
@@ -71,6 +77,21 @@ impl Secp256k1Scalar {
             fe: self.fe.clone(),
         }
     }
+
+    fn to_native(&self) -> Scalar {
+        self.fe.clone().into()
+    }
+
+    /// `SecretKey::parse` refuses zero, so a zero result goes through `zero()`.
+    fn from_native(scalar: Scalar, purpose: &'static str) -> Secp256k1Scalar {
+        if scalar.is_zero() {
+            return Secp256k1Scalar::zero();
+        }
+        Secp256k1Scalar {
+            purpose,
+            fe: SK::parse(&scalar.b32()).expect("reduced non-zero scalar"),
+        }
+    }
 }
 
 impl Secp256k1Point {
@@ -80,36 +101,54 @@ impl Secp256k1Point {
         let pk = base_point.scalar_mul(&random_scalar.get_element());
         Secp256k1Point {
             purpose: "random_point",
-            ge: pk.get_element(),
+            ge: pk.ge,
         }
     }
-    // To generate a random base point we take the hash of the curve generator.
-    // This hash creates a random string which do not encode a valid (x,y) curve point.
-    // Therefore we continue to hash the result until the first valid point comes out.
-    // This function is a result of a manual testing to find
-    // this minimal number of hashes and therefore it is written like this.
-    // the prefix "2" is to complete for the right parity of the point
-    pub fn base_point2() -> Secp256k1Point {
-        let g: Secp256k1Point = ECPoint::generator();
-        let hash = HSha256::create_hash(&[&g.bytes_compressed_to_big_int()]);
 
-        let hash = HSha256::create_hash(&[&hash]);
-
-        let hash = HSha256::create_hash(&[&hash]);
-        let mut hash_vec = BigInt::to_vec(&hash);
-        let mut template: Vec<u8> = vec![2];
-        template.append(&mut hash_vec);
+    pub fn copy(&self) -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: self.purpose,
+            ge: self.ge.clone(),
+        }
+    }
 
+    /// Point addition, total on the group: sums reaching the point at infinity return it
+    /// instead of failing inside libsecp256k1.
+    fn combine(&self, other: &Secp256k1Point) -> Secp256k1Point {
+        let ge = match (&self.ge, &other.ge) {
+            (None, _) => other.ge.clone(),
+            (_, None) => self.ge.clone(),
+            (Some(a), Some(b)) => PublicKey::combine(&[a.clone(), b.clone()]).ok(),
+        };
         Secp256k1Point {
-            purpose: "random",
-            ge: PK::parse_slice(&template, None).unwrap(),
+            purpose: "combine",
+            ge,
         }
     }
 
-    pub fn copy(&self) -> Secp256k1Point {
+    /// -P, computed on the backend's affine representation: (x, y) -> (x, -y).
+    fn negate(&self) -> Secp256k1Point {
+        match self.ge {
+            Some(ref ge) => {
+                let affine: Affine = ge.clone().into();
+                Secp256k1Point::from_affine(affine.neg(), "negate")
+            }
+            None => Secp256k1Point::identity(),
+        }
+    }
+
+    fn from_affine(mut affine: Affine, purpose: &'static str) -> Secp256k1Point {
+        if affine.is_infinity() {
+            return Secp256k1Point::identity();
+        }
+        affine.x.normalize();
+        affine.y.normalize();
+        let mut bytes = [4u8; UNCOMPRESSED_PUBLIC_KEY_SIZE];
+        bytes[1..33].copy_from_slice(&affine.x.b32());
+        bytes[33..65].copy_from_slice(&affine.y.b32());
         Secp256k1Point {
-            purpose: self.purpose,
-            ge: self.ge.clone(),
+            purpose,
+            ge: Some(PK::parse(&bytes).expect("affine point on the curve")),
         }
     }
 }
@@ -122,16 +161,23 @@ impl Zeroize for FE {
     }
 }
 
-impl ECScalar<SK> for Secp256k1Scalar {
-
+impl ECScalar for Secp256k1Scalar {
+    type SecretKey = SK;
     type ScalarLength = typenum::U32;
 
-    fn new_random() -> Secp256k1Scalar {
+    fn new_random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Secp256k1Scalar {
         let mut arr = [0u8; 32];
-        thread_rng().fill(&mut arr[..]);
+        // rejection sampling: zero and values above the group order are drawn again
+        let fe = loop {
+            rng.fill_bytes(&mut arr);
+            if let Ok(fe) = SK::parse_slice(&arr[0..arr.len()]) {
+                break fe;
+            }
+        };
+        arr.zeroize();
         Secp256k1Scalar {
             purpose: "random",
-            fe: SK::parse_slice(&arr[0..arr.len()]).unwrap(),
+            fe,
         }
     }
 
@@ -155,6 +201,9 @@ impl ECScalar<SK> for Secp256k1Scalar {
     fn from(n: &BigInt) -> Secp256k1Scalar {
         let curve_order = FE::q();
         let n_reduced = BigInt::mod_add(n, &BigInt::from(0 as u16), &curve_order);
+        if n_reduced.is_zero() {
+            return Secp256k1Scalar::zero();
+        }
         let mut v = BigInt::to_vec(&n_reduced);
 
         if v.len() < SECRET_KEY_SIZE {
@@ -183,45 +232,18 @@ impl ECScalar<SK> for Secp256k1Scalar {
     }
 
     fn add(&self, other: &SK) -> Secp256k1Scalar {
-        let mut other_scalar: FE = ECScalar::new_random();
-        other_scalar.set_element(other.clone());
-        let res: FE = ECScalar::from(&BigInt::mod_add(
-            &self.to_big_int(),
-            &other_scalar.to_big_int(),
-            &FE::q(),
-        ));
-        Secp256k1Scalar {
-            purpose: "add",
-            fe: res.get_element(),
-        }
+        let other: Scalar = other.clone().into();
+        Secp256k1Scalar::from_native(self.to_native() + other, "add")
     }
 
     fn mul(&self, other: &SK) -> Secp256k1Scalar {
-        let mut other_scalar: FE = ECScalar::new_random();
-        other_scalar.set_element(other.clone());
-        let res: FE = ECScalar::from(&BigInt::mod_mul(
-            &self.to_big_int(),
-            &other_scalar.to_big_int(),
-            &FE::q(),
-        ));
-        Secp256k1Scalar {
-            purpose: "mul",
-            fe: res.get_element(),
-        }
+        let other: Scalar = other.clone().into();
+        Secp256k1Scalar::from_native(self.to_native() * other, "mul")
     }
 
     fn sub(&self, other: &SK) -> Secp256k1Scalar {
-        let mut other_scalar: FE = ECScalar::new_random();
-        other_scalar.set_element(other.clone());
-        let res: FE = ECScalar::from(&BigInt::mod_sub(
-            &self.to_big_int(),
-            &other_scalar.to_big_int(),
-            &FE::q(),
-        ));
-        Secp256k1Scalar {
-            purpose: "sub",
-            fe: res.get_element(),
-        }
+        let other: Scalar = other.clone().into();
+        Secp256k1Scalar::from_native(self.to_native() + other.neg(), "sub")
     }
 
     fn invert(&self) -> Secp256k1Scalar {
@@ -259,6 +281,59 @@ impl<'o> Add<&'o Secp256k1Scalar> for Secp256k1Scalar {
     }
 }
 
+impl Neg for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+    fn neg(self) -> Secp256k1Scalar {
+        Secp256k1Scalar::from_native(self.to_native().neg(), "neg")
+    }
+}
+
+impl Sub<Secp256k1Scalar> for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+    fn sub(self, other: Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar::from_native(self.to_native().add(other.to_native().neg()), "sub")
+    }
+}
+
+impl<'o> Sub<&'o Secp256k1Scalar> for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+    fn sub(self, other: &'o Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar::from_native(self.to_native().add(other.to_native().neg()), "sub")
+    }
+}
+
+impl AddAssign<Secp256k1Scalar> for Secp256k1Scalar {
+    fn add_assign(&mut self, other: Secp256k1Scalar) {
+        *self = Secp256k1Scalar::from_native(self.to_native() + other.to_native(), "add");
+    }
+}
+
+impl SubAssign<Secp256k1Scalar> for Secp256k1Scalar {
+    fn sub_assign(&mut self, other: Secp256k1Scalar) {
+        *self = Secp256k1Scalar::from_native(self.to_native().add(other.to_native().neg()), "sub");
+    }
+}
+
+impl MulAssign<Secp256k1Scalar> for Secp256k1Scalar {
+    fn mul_assign(&mut self, other: Secp256k1Scalar) {
+        *self = Secp256k1Scalar::from_native(self.to_native() * other.to_native(), "mul");
+    }
+}
+
+impl Sum for Secp256k1Scalar {
+    fn sum<I: Iterator<Item = Secp256k1Scalar>>(iter: I) -> Secp256k1Scalar {
+        let sum = iter.fold(Scalar::default(), |acc, x| acc + x.to_native());
+        Secp256k1Scalar::from_native(sum, "sum")
+    }
+}
+
+impl<'o> Sum<&'o Secp256k1Scalar> for Secp256k1Scalar {
+    fn sum<I: Iterator<Item = &'o Secp256k1Scalar>>(iter: I) -> Secp256k1Scalar {
+        let sum = iter.fold(Scalar::default(), |acc, x| acc + x.to_native());
+        Secp256k1Scalar::from_native(sum, "sum")
+    }
+}
+
 impl Serialize for Secp256k1Scalar {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -300,7 +375,7 @@ impl PartialEq for Secp256k1Scalar {
 
 impl PartialEq for Secp256k1Point {
     fn eq(&self, other: &Secp256k1Point) -> bool {
-        self.get_element() == other.get_element()
+        self.ge == other.ge
     }
 }
 
@@ -312,7 +387,10 @@ impl Zeroize for GE {
     }
 }
 
-impl ECPoint<PK, SK> for Secp256k1Point {
+impl ECPoint for Secp256k1Point {
+    type SecretKey = SK;
+    type PublicKey = PK;
+    type Scalar = Secp256k1Scalar;
     type CompressedPointLength = typenum::U33;
     type UncompressedPointLength = typenum::U65;
     fn generator() -> Secp256k1Point {
@@ -321,163 +399,186 @@ impl ECPoint<PK, SK> for Secp256k1Point {
         v.extend(GENERATOR_Y.as_ref());
         Secp256k1Point {
             purpose: "base_fe",
-            ge: PK::parse_slice(&v, None).unwrap(),
+            ge: Some(PK::parse_slice(&v, None).unwrap()),
+        }
+    }
+
+    /// To generate a random base point we take the hash of the curve generator.
+    /// This hash creates a random string which do not encode a valid (x,y) curve point.
+    /// Therefore we continue to hash the result until the first valid point comes out.
+    /// This function is a result of a manual testing to find
+    /// this minimal number of hashes and therefore it is written like this.
+    /// The prefix "2" is to complete for the right parity of the point.
+    ///
+    /// Existing commitments depend on this exact point, so the derivation must not change.
+    /// New generators should come from `hash_to_curve::nums_generator` with a protocol label.
+    fn base_point2() -> Secp256k1Point {
+        let g: Secp256k1Point = ECPoint::generator();
+        let hash = HSha256::create_hash(&[&g.bytes_compressed_to_big_int()]);
+
+        let hash = HSha256::create_hash(&[&hash]);
+
+        let hash = HSha256::create_hash(&[&hash]);
+        let mut hash_vec = BigInt::to_vec(&hash);
+        let mut template: Vec<u8> = vec![2];
+        template.append(&mut hash_vec);
+
+        Secp256k1Point {
+            purpose: "random",
+            ge: Some(PK::parse_slice(&template, None).unwrap()),
+        }
+    }
+
+    fn identity() -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "identity",
+            ge: None,
         }
     }
 
-    fn get_element(&self) -> PK {
+    fn is_identity(&self) -> bool {
+        self.ge.is_none()
+    }
+
+    fn get_element(&self) -> Option<PK> {
         self.ge.clone()
     }
 
     /// to return from BigInt to PK use from_bytes:
     /// 1) convert BigInt::to_vec
-    /// 2) remove first byte [1..33]
+    /// 2) left pad to 33 bytes
     /// 3) call from_bytes
+    /// The point at infinity maps to zero.
     fn bytes_compressed_to_big_int(&self) -> BigInt {
-        let serial = self.ge.serialize();
-        let y_coor_last_byte = serial[64].clone();
-        let y_coor_parity = (y_coor_last_byte << 7) >> 7;
-        let mut compressed = vec![2 + y_coor_parity];
-        compressed.append(&mut serial[1..33].to_vec());
-        from(&compressed)
+        from(&self.to_bytes(true)[..])
     }
 
     fn x_coor(&self) -> Option<BigInt> {
-        let serialized_pk = PK::serialize(&self.ge);
-        let x = &serialized_pk[1..serialized_pk.len() / 2 + 1];
-        let x_vec = x.to_vec();
-        Some(from(&x_vec[..]))
+        self.ge.as_ref().map(|ge| {
+            let serialized_pk = PK::serialize(ge);
+            from(&serialized_pk[1..33])
+        })
     }
 
     fn y_coor(&self) -> Option<BigInt> {
-        let serialized_pk = PK::serialize(&self.ge);
-        let y = &serialized_pk[(serialized_pk.len() - 1) / 2 + 1..serialized_pk.len()];
-        let y_vec = y.to_vec();
-        Some(from(&y_vec[..]))
+        self.ge.as_ref().map(|ge| {
+            let serialized_pk = PK::serialize(ge);
+            from(&serialized_pk[33..65])
+        })
     }
 
+    /// Strict SEC1 decoding: 33 bytes compressed (`02`/`03` prefix), 65 bytes uncompressed
+    /// (`04` prefix) or the single byte `00` for the point at infinity. Any other length or
+    /// prefix, or a point not on the curve, is rejected.
     fn from_bytes(bytes: &[u8]) -> Result<Secp256k1Point, ErrorKey> {
-        let bytes_vec = bytes.to_vec();
-        let mut bytes_array_65 = [0u8; 65];
-        let mut bytes_array_33 = [0u8; 33];
-
-        let byte_len = bytes_vec.len();
-        match byte_len {
-            33..=63 => {
-                let mut template = vec![0; 64 - bytes_vec.len()];
-                template.extend_from_slice(&bytes);
-                let bytes_vec = template;
-                let mut template: Vec<u8> = vec![4];
-                template.append(&mut bytes_vec.clone());
-                let bytes_slice = &template[..];
-
-                bytes_array_65.copy_from_slice(&bytes_slice[0..65]);
-                let result = PK::parse(&bytes_array_65);
-                let test = result.map(|pk| Secp256k1Point {
-                    purpose: "random",
-                    ge: pk,
-                });
-                test.map_err(|_err| ErrorKey::InvalidPublicKey)
+        let pk = match (bytes.len(), bytes.first()) {
+            (1, Some(0)) => return Ok(Secp256k1Point::identity()),
+            (33, Some(2)) | (33, Some(3)) => {
+                let mut bytes_array_33 = [0u8; 33];
+                bytes_array_33.copy_from_slice(bytes);
+                PK::parse_compressed(&bytes_array_33)
             }
-
-            0..=32 => {
-                let mut template = vec![0; 32 - bytes_vec.len()];
-                template.extend_from_slice(&bytes);
-                let bytes_vec = template;
-                let mut rng = rand::thread_rng();
-                let bit: bool = rng.gen();
-                println!("rand {:?}", 2 + bit as u8);
-                let mut template: Vec<u8> = vec![2 + bit as u8];
-                template.append(&mut bytes_vec.clone());
-                let bytes_slice = &template[..];
-
-                bytes_array_33.copy_from_slice(&bytes_slice[0..33]);
-                let result = PK::parse_compressed(&bytes_array_33);
-                let test = result.map(|pk| Secp256k1Point {
-                    purpose: "random",
-                    ge: pk,
-                });
-                test.map_err(|_err| ErrorKey::InvalidPublicKey)
+            (65, Some(4)) => {
+                let mut bytes_array_65 = [0u8; 65];
+                bytes_array_65.copy_from_slice(bytes);
+                PK::parse(&bytes_array_65)
             }
-            _ => {
-                let bytes_slice = &bytes_vec[0..64];
-                let bytes_vec = bytes_slice.to_vec();
-                let mut template: Vec<u8> = vec![4];
-                template.append(&mut bytes_vec.clone());
-                let bytes_slice = &template[..];
-
-                bytes_array_65.copy_from_slice(&bytes_slice[0..65]);
-                let result = PK::parse(&bytes_array_65);
-                let test = result.map(|pk| Secp256k1Point {
-                    purpose: "random",
-                    ge: pk,
-                });
-                test.map_err(|_err| ErrorKey::InvalidPublicKey)
-            }
-        }
+            _ => return Err(ErrorKey::InvalidPublicKey),
+        };
+        pk.map(|pk| Secp256k1Point {
+            purpose: "from_bytes",
+            ge: Some(pk),
+        })
+        .map_err(|_err| ErrorKey::InvalidPublicKey)
     }
-    fn pk_to_key_slice(&self) -> Vec<u8> {
-        let mut v = vec![4 as u8];
 
-        v.extend(BigInt::to_vec(&self.x_coor().unwrap()));
-        v.extend(BigInt::to_vec(&self.y_coor().unwrap()));
-        v
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        match (self.x_coor(), self.y_coor()) {
+            (Some(x), Some(y)) => {
+                let mut v = vec![4 as u8];
+                v.extend(BigInt::to_vec(&x));
+                v.extend(BigInt::to_vec(&y));
+                v
+            }
+            _ => vec![0],
+        }
     }
 
+    /// Multiplying by zero, or multiplying the point at infinity, gives the point at infinity.
     fn scalar_mul(&self, fe: &SK) -> Secp256k1Point {
         let mut new_point = self.clone();
-        new_point
-            .ge
-            .tweak_mul_assign(fe)
-            .expect("Assignment expected");
+        new_point.purpose = "scalar_mul";
+        if let Some(ref mut ge) = new_point.ge {
+            if ge.tweak_mul_assign(fe).is_err() {
+                new_point.ge = None;
+            }
+        }
         new_point
     }
 
-    fn add_point(&self, other: &PK) -> Secp256k1Point {
-        Secp256k1Point {
+    fn add_point(&self, other: &PK) -> Option<Secp256k1Point> {
+        let sum = self.combine(&Secp256k1Point {
             purpose: "combine",
-            ge: PublicKey::combine(&[self.ge.clone(), other.clone()]).unwrap(),
-        }
+            ge: Some(other.clone()),
+        });
+        Some(sum).filter(|sum| !sum.is_identity())
     }
 
-    fn sub_point(&self, other: &PK) -> Secp256k1Point {
+    fn sub_point(&self, other: &PK) -> Option<Secp256k1Point> {
         let point = Secp256k1Point {
             purpose: "sub_point",
-            ge: other.clone(),
+            ge: Some(other.clone()),
         };
-        let p: Vec<u8> = vec![
-            255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
-            255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 254, 255, 255, 252, 47,
-        ];
-        let order = from(&p[..]);
-        let x = point.x_coor().unwrap();
-        let y = point.y_coor().unwrap();
-        let minus_y = BigInt::mod_sub(&order, &y, &order);
-
-        let x_vec = BigInt::to_vec(&x);
-        let y_vec = BigInt::to_vec(&minus_y);
-
-        let mut template_x = vec![0; 32 - x_vec.len()];
-        template_x.extend_from_slice(&x_vec);
-        let mut x_vec = template_x;
-
-        let mut template_y = vec![0; 32 - y_vec.len()];
-        template_y.extend_from_slice(&y_vec);
-        let y_vec = template_y;
-
-        x_vec.extend_from_slice(&y_vec);
+        Some(self.combine(&point.negate())).filter(|difference| !difference.is_identity())
+    }
 
-        let minus_point: GE = ECPoint::from_bytes(&x_vec).unwrap();
-        //let minus_point: GE = ECPoint::from_coor(&x, &y_inv);
-        ECPoint::add_point(self, &minus_point.get_element())
+    /// Straus' interleaved window method: every point gets a table of its first `2^w - 1`
+    /// multiples, and all scalars share one chain of 256 doublings. Works in Jacobian
+    /// coordinates throughout, so there is a single field inversion at the end.
+    fn multi_scalar_mul(points: &[Secp256k1Point], scalars: &[Secp256k1Scalar]) -> Secp256k1Point {
+        assert_eq!(points.len(), scalars.len());
+        let table_len = (1 << STRAUS_WINDOW) - 1;
+        let tables = points
+            .iter()
+            .zip(scalars)
+            .filter_map(|(point, scalar)| {
+                let ge = point.ge.as_ref()?;
+                let affine: Affine = ge.clone().into();
+                let mut table = vec![Jacobian::from_ge(&affine)];
+                for i in 1..table_len {
+                    let next = table[i - 1].add_ge_var(&affine, None);
+                    table.push(next);
+                }
+                Some((table, scalar.to_native().b32()))
+            })
+            .collect::<Vec<(Vec<Jacobian>, [u8; 32])>>();
+
+        let mut acc = Jacobian::default();
+        acc.set_infinity();
+        for byte in 0..32 {
+            for shift in [STRAUS_WINDOW, 0].iter() {
+                for _ in 0..STRAUS_WINDOW {
+                    acc = acc.double_var(None);
+                }
+                for (table, scalar_bytes) in tables.iter() {
+                    let digit = (scalar_bytes[byte] >> shift) as usize & table_len;
+                    if digit != 0 {
+                        acc = acc.add_var(&table[digit - 1], None);
+                    }
+                }
+            }
+        }
+        let mut affine = Affine::default();
+        affine.set_gej_var(&acc);
+        Secp256k1Point::from_affine(affine, "multi_scalar_mul")
     }
 
-    /// Serializes a point in (un)compressed form
+    /// Serializes a point in (un)compressed form. The point at infinity is the single byte `00`.
     fn to_bytes(&self, compressed: bool) -> Vec<u8> {
-        if compressed {
-            self.ge.serialize_compressed().to_vec()
-        } else {
-            self.ge.serialize().to_vec()
+        match self.ge {
+            Some(ref ge) if compressed => ge.serialize_compressed().to_vec(),
+            Some(ref ge) => ge.serialize().to_vec(),
+            None => vec![0],
         }
     }
 
@@ -509,7 +610,7 @@ impl ECPoint<PK, SK> for Secp256k1Point {
 
         Secp256k1Point {
             purpose: "base_fe",
-            ge: PK::parse_slice(&v, None).unwrap(),
+            ge: Some(PK::parse_slice(&v, None).unwrap()),
         }
     }
 }
@@ -538,32 +639,95 @@ impl<'o> Mul<&'o Secp256k1Scalar> for &'o Secp256k1Point {
 impl Add<Secp256k1Point> for Secp256k1Point {
     type Output = Secp256k1Point;
     fn add(self, other: Secp256k1Point) -> Self::Output {
-        self.add_point(&other.get_element())
+        self.combine(&other)
     }
 }
 
 impl<'o> Add<&'o Secp256k1Point> for Secp256k1Point {
     type Output = Secp256k1Point;
     fn add(self, other: &'o Secp256k1Point) -> Self::Output {
-        self.add_point(&other.get_element())
+        self.combine(other)
     }
 }
 
 impl<'o> Add<&'o Secp256k1Point> for &'o Secp256k1Point {
     type Output = Secp256k1Point;
     fn add(self, other: &'o Secp256k1Point) -> Self::Output {
-        self.add_point(&other.get_element())
+        self.combine(other)
+    }
+}
+
+impl Neg for Secp256k1Point {
+    type Output = Secp256k1Point;
+    fn neg(self) -> Secp256k1Point {
+        self.negate()
+    }
+}
+
+impl Sub<Secp256k1Point> for Secp256k1Point {
+    type Output = Secp256k1Point;
+    fn sub(self, other: Secp256k1Point) -> Secp256k1Point {
+        self.combine(&other.negate())
+    }
+}
+
+impl<'o> Sub<&'o Secp256k1Point> for Secp256k1Point {
+    type Output = Secp256k1Point;
+    fn sub(self, other: &'o Secp256k1Point) -> Secp256k1Point {
+        self.combine(&other.negate())
     }
 }
 
+impl AddAssign<Secp256k1Point> for Secp256k1Point {
+    fn add_assign(&mut self, other: Secp256k1Point) {
+        *self = self.combine(&other);
+    }
+}
+
+impl<'o> AddAssign<&'o Secp256k1Point> for Secp256k1Point {
+    fn add_assign(&mut self, other: &'o Secp256k1Point) {
+        *self = self.combine(other);
+    }
+}
+
+impl SubAssign<Secp256k1Point> for Secp256k1Point {
+    fn sub_assign(&mut self, other: Secp256k1Point) {
+        *self = self.combine(&other.negate());
+    }
+}
+
+impl MulAssign<Secp256k1Scalar> for Secp256k1Point {
+    fn mul_assign(&mut self, other: Secp256k1Scalar) {
+        *self = self.scalar_mul(&other.get_element());
+    }
+}
+
+impl Sum for Secp256k1Point {
+    fn sum<I: Iterator<Item = Secp256k1Point>>(iter: I) -> Secp256k1Point {
+        iter.fold(Secp256k1Point::identity(), |acc, x| acc.combine(&x))
+    }
+}
+
+impl<'o> Sum<&'o Secp256k1Point> for Secp256k1Point {
+    fn sum<I: Iterator<Item = &'o Secp256k1Point>>(iter: I) -> Secp256k1Point {
+        iter.fold(Secp256k1Point::identity(), |acc, x| acc.combine(x))
+    }
+}
+
+/// Points serialize as their affine coordinates, which the point at infinity does not have:
+/// serializing it fails. `to_bytes` and `from_bytes` cover it.
 impl Serialize for Secp256k1Point {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        let (x, y) = match (self.x_coor(), self.y_coor()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(ser::Error::custom("the point at infinity has no coordinates")),
+        };
         let mut state = serializer.serialize_struct("Secp256k1Point", 2)?;
-        state.serialize_field("x", &self.x_coor().unwrap().to_hex())?;
-        state.serialize_field("y", &self.y_coor().unwrap().to_hex())?;
+        state.serialize_field("x", &x.to_hex())?;
+        state.serialize_field("y", &y.to_hex())?;
         state.end()
     }
 }
@@ -735,7 +899,7 @@ mod tests {
             0, 0, 0, 1, 2, 3, 4, 5, 6,
         ];
         let result = Secp256k1Point::from_bytes(&test_vec);
-        assert!(result.is_ok() | result.is_err())
+        assert_eq!(result.unwrap_err(), ErrorKey::InvalidPublicKey)
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -745,7 +909,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6,
         ];
         let result = Secp256k1Point::from_bytes(&test_vec);
-        assert!(result.is_ok() | result.is_err())
+        assert_eq!(result.unwrap_err(), ErrorKey::InvalidPublicKey)
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -758,7 +922,7 @@ mod tests {
             4, 5, 6,
         ];
         let result = Secp256k1Point::from_bytes(&test_vec);
-        assert!(result.is_ok() | result.is_err())
+        assert_eq!(result.unwrap_err(), ErrorKey::InvalidPublicKey)
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -776,10 +940,65 @@ mod tests {
 
         let point_a = base.clone() * a;
         let point_b = base.clone() * b;
-        let point_ab2 = point_a.sub_point(&point_b.get_element());
+        let point_ab2 = point_a.sub_point(&point_b.get_element().unwrap()).unwrap();
         assert_eq!(point_ab1.get_element(), point_ab2.get_element());
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_bytes_strict_encoding() {
+        let p = GE::generator() * FE::new_random();
+        let compressed = p.to_bytes(true);
+        let uncompressed = p.to_bytes(false);
+        assert_eq!(GE::from_bytes(&compressed).unwrap(), p);
+        assert_eq!(GE::from_bytes(&uncompressed).unwrap(), p);
+
+        // raw x coordinate, raw x||y, and a truncated encoding
+        assert!(GE::from_bytes(&compressed[1..]).is_err());
+        assert!(GE::from_bytes(&uncompressed[1..]).is_err());
+        assert!(GE::from_bytes(&compressed[..32]).is_err());
+        // prefix not matching the length
+        let mut bad_prefix = compressed.clone();
+        bad_prefix[0] = 4;
+        assert!(GE::from_bytes(&bad_prefix).is_err());
+        let mut bad_prefix = uncompressed.clone();
+        bad_prefix[0] = 2;
+        assert!(GE::from_bytes(&bad_prefix).is_err());
+        // x coordinate not on the curve
+        let mut off_curve = [0u8; 33];
+        off_curve[0] = 2;
+        off_curve[32] = 5;
+        assert!(GE::from_bytes(&off_curve).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_identity() {
+        let g: GE = ECPoint::generator();
+        let p = g.clone() * FE::new_random();
+        let o = GE::identity();
+        assert!(o.is_identity());
+        assert!(o.x_coor().is_none() && o.y_coor().is_none());
+
+        assert!(o.get_element().is_none());
+        assert!(p.sub_point(&p.get_element().unwrap()).is_none());
+        assert_eq!(p.add_point(&(-p.clone()).get_element().unwrap()), None);
+        let p_minus_p = p.clone() - p.clone();
+        assert!(p_minus_p.is_identity());
+        assert_eq!(p_minus_p, o);
+        assert!((g.clone() * FE::zero()).is_identity());
+        assert!((o.clone() * FE::new_random()).is_identity());
+
+        assert_eq!(o.clone() + p.clone(), p);
+        assert_eq!(p.clone() + o.clone(), p);
+        assert_eq!(p.clone() + p_minus_p + g.clone(), p.clone() + g);
+        assert!(serde_json::to_string(&o).is_err());
+        assert_eq!(o.to_bytes(true), vec![0]);
+        assert_eq!(GE::from_bytes(&o.to_bytes(true)), Ok(o.clone()));
+        assert_eq!(GE::from_bytes(&o.to_bytes(false)), Ok(o));
+        assert!(GE::from_bytes(&[0, 0]).is_err());
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_invert() {
@@ -801,4 +1020,72 @@ mod tests {
         assert_eq!(c1.get_element(), c2.get_element());
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_operators() {
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        let g = GE::generator();
+
+        assert_eq!(a.clone() - b.clone(), a.sub(&b.get_element()));
+        assert_eq!(-a.clone() + a.clone(), FE::zero());
+        assert_eq!(-FE::zero(), FE::zero());
+        let mut c = a.clone();
+        c += b.clone();
+        c -= a.clone();
+        assert_eq!(c, b);
+        c *= a.clone();
+        assert_eq!(c, a.clone() * b.clone());
+        let scalars = vec![a.clone(), b.clone(), -a.clone()];
+        assert_eq!(scalars.iter().sum::<FE>(), b);
+        assert_eq!(scalars.into_iter().sum::<FE>(), b);
+
+        let p_a = g.clone() * a.clone();
+        let p_b = g.clone() * b.clone();
+        assert_eq!(-p_a.clone(), g.clone() * -a.clone());
+        assert_eq!(p_a.clone() - p_b.clone(), g.clone() * (a.clone() - b.clone()));
+        assert!((p_a.clone() - p_a.clone()).is_identity());
+        assert!((-GE::identity()).is_identity());
+        let mut p = p_a.clone();
+        p += p_b.clone();
+        p -= p_a.clone();
+        assert_eq!(p, p_b);
+        p *= a.clone();
+        assert_eq!(p, g.clone() * (a.clone() * b.clone()));
+        let points = vec![p_a.clone(), p_b.clone(), -p_a.clone()];
+        assert_eq!(points.iter().sum::<GE>(), p_b);
+        assert!(Vec::<GE>::new().into_iter().sum::<GE>().is_identity());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_multi_scalar_mul() {
+        let g = GE::generator();
+        for n in [0usize, 1, 2, 7].iter() {
+            let points = (0..*n).map(|_| GE::random_point()).collect::<Vec<GE>>();
+            let scalars = (0..*n).map(|_| FE::new_random()).collect::<Vec<FE>>();
+            let expected: GE = points
+                .iter()
+                .zip(&scalars)
+                .map(|(p, s)| p.clone() * s.clone())
+                .sum();
+            assert_eq!(GE::multi_scalar_mul(&points, &scalars), expected);
+        }
+
+        // degenerate terms: identity points, zero and minus-one scalars, cancelling sums
+        let a: FE = ECScalar::new_random();
+        let one: FE = ECScalar::from(&BigInt::from(1u32));
+        let three: FE = ECScalar::from(&BigInt::from(3u32));
+        let six: FE = ECScalar::from(&BigInt::from(6u32));
+        let minus_one = -one.clone();
+        let points = vec![g.clone(), GE::identity(), g.clone() * a.clone(), g.clone()];
+        let scalars = vec![a.clone(), a.clone(), minus_one.clone(), FE::zero()];
+        assert!(GE::multi_scalar_mul(&points, &scalars).is_identity());
+        let scalars = vec![a.clone(), a.clone(), FE::zero(), minus_one];
+        assert_eq!(GE::multi_scalar_mul(&points, &scalars), g.clone() * (a - one));
+        // repeated point, exercising doubling inside the addition chain
+        let points = vec![g.clone(), g.clone()];
+        let scalars = vec![three.clone(), three];
+        assert_eq!(GE::multi_scalar_mul(&points, &scalars), g * six);
+    }
 }
@@ -6,45 +6,115 @@
 */
 
 use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::os_rng;
 use crate::ErrorKey;
-use typenum::Unsigned;
+use rand::{CryptoRng, RngCore};
 use generic_array::ArrayLength;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use typenum::Unsigned;
+use zeroize::Zeroize;
 
-pub trait ECScalar<SK> {
+/// Element of the scalar field of a curve. `SecretKey` is the backend's native representation.
+pub trait ECScalar:
+    Mul<Output = Self>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + Sum
+    + for<'a> Sum<&'a Self>
+    + Clone
+    + PartialEq
+    + Debug
+    + Serialize
+    + DeserializeOwned
+    + Zeroize
+    + Sized
+{
+    type SecretKey;
     type ScalarLength: ArrayLength<u8> + Unsigned;
-    fn new_random() -> Self;
+    fn new_random() -> Self {
+        Self::new_random_with_rng(&mut os_rng())
+    }
+    fn new_random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self;
     fn zero() -> Self;
-    fn get_element(&self) -> SK;
-    fn set_element(&mut self, element: SK);
+    fn get_element(&self) -> Self::SecretKey;
+    fn set_element(&mut self, element: Self::SecretKey);
     fn from(n: &BigInt) -> Self;
     fn to_big_int(&self) -> BigInt;
     fn q() -> BigInt;
-    fn add(&self, other: &SK) -> Self;
-    fn mul(&self, other: &SK) -> Self;
-    fn sub(&self, other: &SK) -> Self;
+    fn add(&self, other: &Self::SecretKey) -> Self;
+    fn mul(&self, other: &Self::SecretKey) -> Self;
+    fn sub(&self, other: &Self::SecretKey) -> Self;
     fn invert(&self) -> Self;
     fn group_order() -> &'static BigInt;
 }
 
 // TODO: add a fn is_point
-pub trait ECPoint<PK, SK>
-where
-    Self: Sized,
+/// Point of a prime order curve group. Code generic over the curve takes a `P: ECPoint`
+/// parameter and reaches the matching scalar type through `P::Scalar`.
+pub trait ECPoint:
+    Mul<<Self as ECPoint>::Scalar, Output = Self>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign<<Self as ECPoint>::Scalar>
+    + Sum
+    + for<'a> Sum<&'a Self>
+    + Clone
+    + PartialEq
+    + Debug
+    + Serialize
+    + DeserializeOwned
+    + Zeroize
+    + Sized
 {
+    type SecretKey;
+    type PublicKey;
+    type Scalar: ECScalar<SecretKey = Self::SecretKey>;
     /// The byte length of point serialized in compressed form
     type CompressedPointLength: ArrayLength<u8> + Unsigned;
     /// The byte length of point serialized in uncompressed form
     type UncompressedPointLength: ArrayLength<u8> + Unsigned;
     fn generator() -> Self;
-    fn get_element(&self) -> PK;
+    /// Second generator whose discrete log relative to `generator()` is unknown.
+    fn base_point2() -> Self;
+    /// The point at infinity, neutral element of the group.
+    fn identity() -> Self;
+    fn is_identity(&self) -> bool;
+    /// `None` for the point at infinity, which backends cannot hand out as a public key.
+    fn get_element(&self) -> Option<Self::PublicKey>;
+    /// `None` for the point at infinity.
     fn x_coor(&self) -> Option<BigInt>;
+    /// `None` for the point at infinity.
     fn y_coor(&self) -> Option<BigInt>;
     fn bytes_compressed_to_big_int(&self) -> BigInt;
+    /// Decodes a SEC1 point: 33 bytes compressed or 65 bytes uncompressed, nothing else.
     fn from_bytes(bytes: &[u8]) -> Result<Self, ErrorKey>;
     fn pk_to_key_slice(&self) -> Vec<u8>;
-    fn scalar_mul(&self, fe: &SK) -> Self;
-    fn add_point(&self, other: &PK) -> Self;
-    fn sub_point(&self, other: &PK) -> Self;
+    fn scalar_mul(&self, fe: &Self::SecretKey) -> Self;
+    /// `None` when the sum is the point at infinity. The `+` operator is total.
+    fn add_point(&self, other: &Self::PublicKey) -> Option<Self>;
+    /// `None` when the difference is the point at infinity. The `-` operator is total.
+    fn sub_point(&self, other: &Self::PublicKey) -> Option<Self>;
+    /// `sum_i scalars[i] * points[i]`. The default multiplies term by term; backends
+    /// override it with a proper multi-scalar multiplication.
+    fn multi_scalar_mul(points: &[Self], scalars: &[Self::Scalar]) -> Self {
+        assert_eq!(points.len(), scalars.len());
+        points
+            .iter()
+            .zip(scalars)
+            .map(|(point, scalar)| point.clone() * scalar.clone())
+            .sum()
+    }
     fn from_coor(x: &BigInt, y: &BigInt) -> Self;
     fn to_bytes(&self, compressed: bool) -> Vec<u8>;
 }
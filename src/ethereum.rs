@@ -0,0 +1,502 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Ethereum transactions signed with the threshold key: RLP encoding, signing
+//! hashes for legacy (EIP-155) and EIP-1559 transactions, and the signed raw
+//! transaction once `gg_2018` produced `(r, s, recid)`.
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::elliptic::curves::secp256_k1::GE;
+use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::gg_2018::message::keccak256;
+use crate::gg_2018::party_i::Signature;
+use crate::Error::{self, InvalidSig};
+use num_traits::Zero;
+
+const EIP1559_TX_TYPE: u8 = 0x02;
+const ADDRESS_LEN: usize = 20;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AccessListItem {
+    #[serde(with = "hex_serde::bytes")]
+    pub address: Vec<u8>,
+    #[serde(with = "hex_serde::bytes_vec")]
+    pub storage_keys: Vec<Vec<u8>>,
+}
+
+/// Pre-EIP-2718 transaction, replay protected with EIP-155.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LegacyTransaction {
+    pub chain_id: u64,
+    #[serde(with = "hex_serde::quantity")]
+    pub nonce: BigInt,
+    #[serde(with = "hex_serde::quantity")]
+    pub gas_price: BigInt,
+    #[serde(with = "hex_serde::quantity")]
+    pub gas_limit: BigInt,
+    /// `None` for contract creation
+    #[serde(with = "hex_serde::address_opt")]
+    pub to: Option<Vec<u8>>,
+    #[serde(with = "hex_serde::quantity")]
+    pub value: BigInt,
+    #[serde(with = "hex_serde::bytes")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    #[serde(with = "hex_serde::quantity")]
+    pub nonce: BigInt,
+    #[serde(with = "hex_serde::quantity")]
+    pub max_priority_fee_per_gas: BigInt,
+    #[serde(with = "hex_serde::quantity")]
+    pub max_fee_per_gas: BigInt,
+    #[serde(with = "hex_serde::quantity")]
+    pub gas_limit: BigInt,
+    #[serde(with = "hex_serde::address_opt")]
+    pub to: Option<Vec<u8>>,
+    #[serde(with = "hex_serde::quantity")]
+    pub value: BigInt,
+    #[serde(with = "hex_serde::bytes")]
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Transaction {
+    Legacy(LegacyTransaction),
+    Eip1559(Eip1559Transaction),
+}
+
+impl Transaction {
+    /// The bytes whose Keccak-256 is signed.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        match self {
+            Transaction::Legacy(tx) => {
+                let mut fields = tx.fields();
+                fields.push(rlp::encode_uint(&BigInt::from(tx.chain_id)));
+                fields.push(rlp::encode_uint(&BigInt::zero()));
+                fields.push(rlp::encode_uint(&BigInt::zero()));
+                rlp::encode_list(&fields)
+            }
+            Transaction::Eip1559(tx) => {
+                let mut payload = vec![EIP1559_TX_TYPE];
+                payload.extend_from_slice(&rlp::encode_list(&tx.fields()));
+                payload
+            }
+        }
+    }
+
+    /// 32 byte digest to feed `MessageHashMode::Prehashed`.
+    pub fn signing_hash(&self) -> Vec<u8> {
+        keccak256(&self.signing_payload())
+    }
+
+    /// Raw signed transaction, ready for `eth_sendRawTransaction`.
+    ///
+    /// Fails if the recovery id cannot be expressed in `v`, i.e. R.x overflowed the group order.
+    pub fn encode_signed(&self, sig: &Signature) -> Result<Vec<u8>, Error> {
+        if sig.recid > 1 {
+            return Err(InvalidSig);
+        }
+        let y_parity = BigInt::from(sig.recid);
+        let r = rlp::encode_uint(&sig.r.to_big_int());
+        let s = rlp::encode_uint(&sig.s.to_big_int());
+        match self {
+            Transaction::Legacy(tx) => {
+                let v = BigInt::from(tx.chain_id) * BigInt::from(2u32)
+                    + BigInt::from(35u32)
+                    + y_parity;
+                let mut fields = tx.fields();
+                fields.push(rlp::encode_uint(&v));
+                fields.push(r);
+                fields.push(s);
+                Ok(rlp::encode_list(&fields))
+            }
+            Transaction::Eip1559(tx) => {
+                let mut fields = tx.fields();
+                fields.push(rlp::encode_uint(&y_parity));
+                fields.push(r);
+                fields.push(s);
+                let mut raw = vec![EIP1559_TX_TYPE];
+                raw.extend_from_slice(&rlp::encode_list(&fields));
+                Ok(raw)
+            }
+        }
+    }
+}
+
+impl LegacyTransaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp::encode_uint(&self.nonce),
+            rlp::encode_uint(&self.gas_price),
+            rlp::encode_uint(&self.gas_limit),
+            rlp::encode_bytes(self.to.as_deref().unwrap_or(&[])),
+            rlp::encode_uint(&self.value),
+            rlp::encode_bytes(&self.data),
+        ]
+    }
+}
+
+impl Eip1559Transaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        let access_list = self
+            .access_list
+            .iter()
+            .map(|item| {
+                let keys = item
+                    .storage_keys
+                    .iter()
+                    .map(|key| rlp::encode_bytes(key))
+                    .collect::<Vec<Vec<u8>>>();
+                rlp::encode_list(&[rlp::encode_bytes(&item.address), rlp::encode_list(&keys)])
+            })
+            .collect::<Vec<Vec<u8>>>();
+        vec![
+            rlp::encode_uint(&BigInt::from(self.chain_id)),
+            rlp::encode_uint(&self.nonce),
+            rlp::encode_uint(&self.max_priority_fee_per_gas),
+            rlp::encode_uint(&self.max_fee_per_gas),
+            rlp::encode_uint(&self.gas_limit),
+            rlp::encode_bytes(self.to.as_deref().unwrap_or(&[])),
+            rlp::encode_uint(&self.value),
+            rlp::encode_bytes(&self.data),
+            rlp::encode_list(&access_list),
+        ]
+    }
+}
+
+/// Ethereum address of a public key: the last 20 bytes of the Keccak-256 of the
+/// uncompressed point without its 0x04 prefix.
+pub fn public_key_address(public_key: &GE) -> Vec<u8> {
+    let public_key = public_key.to_bytes(false);
+    debug_assert_eq!(public_key[0], 0x04);
+    let hash = keccak256(&public_key[1..]);
+    hash[12..].to_vec()
+}
+
+/// EIP-55 mixed case checksum encoding of an address, `0x` prefixed.
+pub fn to_checksum_address(address: &[u8]) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+    let checksummed = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    format!("0x{}", checksummed)
+}
+
+pub mod rlp {
+    use crate::curv::arithmetic::num_bigint::BigInt;
+    use num_traits::Zero;
+
+    /// Integers are encoded as their minimal big-endian byte string, zero being the empty string.
+    pub fn encode_uint(n: &BigInt) -> Vec<u8> {
+        if n.is_zero() {
+            encode_bytes(&[])
+        } else {
+            encode_bytes(&n.to_bytes_be())
+        }
+    }
+
+    pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+            return bytes.to_vec();
+        }
+        let mut out = encode_length(bytes.len(), 0x80);
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    /// `items` are already RLP encoded.
+    pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut out = encode_length(payload.len(), 0xc0);
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+        if len < 56 {
+            vec![offset + len as u8]
+        } else {
+            let len_bytes = len.to_be_bytes();
+            let first_non_zero = len_bytes.iter().position(|b| *b != 0).unwrap();
+            let len_bytes = &len_bytes[first_non_zero..];
+            let mut out = vec![offset + 55 + len_bytes.len() as u8];
+            out.extend_from_slice(len_bytes);
+            out
+        }
+    }
+}
+
+/// JSON representation matching the Ethereum JSON-RPC conventions: quantities and
+/// byte strings are `0x` prefixed hex.
+mod hex_serde {
+    fn strip(s: &str) -> &str {
+        s.strip_prefix("0x").unwrap_or(s)
+    }
+
+    pub mod quantity {
+        use crate::curv::arithmetic::num_bigint::BigInt;
+        use num_traits::Num;
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(n: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&format!("0x{}", n.to_str_radix(16)))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            let s = super::strip(&s);
+            if s.is_empty() {
+                return Ok(BigInt::from(0u32));
+            }
+            // from_str_radix also takes a sign and digit separators
+            if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(de::Error::custom(format!("invalid hex quantity: {}", s)));
+            }
+            BigInt::from_str_radix(s, 16).map_err(de::Error::custom)
+        }
+    }
+
+    pub mod bytes {
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(b: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&format!("0x{}", hex::encode(b)))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            hex::decode(super::strip(&s)).map_err(de::Error::custom)
+        }
+    }
+
+    pub mod bytes_opt {
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(b: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+            match b {
+                Some(b) => super::bytes::serialize(b, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Vec<u8>>, D::Error> {
+            let s = Option::<String>::deserialize(deserializer)?;
+            s.map(|s| hex::decode(super::strip(&s)).map_err(de::Error::custom))
+                .transpose()
+        }
+    }
+
+    /// Like `bytes_opt`, rejecting anything but a 20 byte address.
+    pub mod address_opt {
+        use serde::{de, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(b: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+            super::bytes_opt::serialize(b, serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Vec<u8>>, D::Error> {
+            let address = super::bytes_opt::deserialize(deserializer)?;
+            match &address {
+                Some(a) if a.len() != super::super::ADDRESS_LEN => Err(de::Error::invalid_length(
+                    a.len(),
+                    &"a 20 byte address",
+                )),
+                _ => Ok(address),
+            }
+        }
+    }
+
+    pub mod bytes_vec {
+        use serde::ser::SerializeSeq;
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(v: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(v.len()))?;
+            for b in v {
+                seq.serialize_element(&format!("0x{}", hex::encode(b)))?;
+            }
+            seq.end()
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<Vec<u8>>, D::Error> {
+            let v = Vec::<String>::deserialize(deserializer)?;
+            v.iter()
+                .map(|s| hex::decode(super::strip(s)).map_err(de::Error::custom))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::arithmetic::traits::Converter;
+    use crate::curv::elliptic::curves::secp256_k1::FE;
+    use num_traits::Num;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // the worked example from EIP-155
+    fn eip155_example() -> Transaction {
+        Transaction::Legacy(LegacyTransaction {
+            chain_id: 1,
+            nonce: BigInt::from(9u32),
+            gas_price: BigInt::from(20_000_000_000u64),
+            gas_limit: BigInt::from(21000u32),
+            to: Some(vec![0x35; 20]),
+            value: BigInt::from(1_000_000_000_000_000_000u64),
+            data: vec![],
+        })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_legacy_eip155_vector() {
+        let tx = eip155_example();
+        assert_eq!(
+            hex::encode(tx.signing_payload()),
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let sig = Signature {
+            r: ECScalar::from(
+                &BigInt::from_str_radix(
+                    "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+                    10,
+                )
+                .unwrap(),
+            ),
+            s: ECScalar::from(
+                &BigInt::from_str_radix(
+                    "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+                    10,
+                )
+                .unwrap(),
+            ),
+            recid: 0,
+        };
+        assert_eq!(
+            hex::encode(tx.encode_signed(&sig).unwrap()),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_eip1559_layout() {
+        let tx = Transaction::Eip1559(Eip1559Transaction {
+            chain_id: 1,
+            nonce: BigInt::zero(),
+            max_priority_fee_per_gas: BigInt::from(1u32),
+            max_fee_per_gas: BigInt::from(2u32),
+            gas_limit: BigInt::from(21000u32),
+            to: None,
+            value: BigInt::zero(),
+            data: vec![],
+            access_list: vec![],
+        });
+        assert_eq!(hex::encode(tx.signing_payload()), "02cb01800102825208808080c0");
+
+        let sig = Signature {
+            r: ECScalar::from(&BigInt::from(1u32)),
+            s: ECScalar::from(&BigInt::from(2u32)),
+            recid: 1,
+        };
+        assert_eq!(
+            hex::encode(tx.encode_signed(&sig).unwrap()),
+            "02ce01800102825208808080c0010102"
+        );
+
+        let overflowed = Signature { recid: 2, ..sig };
+        assert_eq!(tx.encode_signed(&overflowed), Err(InvalidSig));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_rlp() {
+        assert_eq!(rlp::encode_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(rlp::encode_bytes(&[0x0f]), vec![0x0f]);
+        assert_eq!(rlp::encode_uint(&BigInt::from(1024u32)), vec![0x82, 0x04, 0x00]);
+        assert_eq!(rlp::encode_list(&[]), vec![0xc0]);
+        let long = vec![b'a'; 56];
+        let encoded = rlp::encode_bytes(&long);
+        assert_eq!(&encoded[..2], &[0xb8, 56]);
+        assert_eq!(encoded.len(), 58);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_public_key_address() {
+        let sk: FE = ECScalar::from(&BigInt::from_hex(&"46".repeat(32)));
+        let g: GE = ECPoint::generator();
+        let y = g * sk;
+        let address = public_key_address(&y);
+        assert_eq!(
+            to_checksum_address(&address),
+            "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_transaction_json() {
+        let json = r#"{"type":"eip1559","chain_id":5,"nonce":"0x1","max_priority_fee_per_gas":"0x3b9aca00","max_fee_per_gas":"0x77359400","gas_limit":"0x5208","to":"0x3535353535353535353535353535353535353535","value":"0x0","data":"0x","access_list":[]}"#;
+        let tx: Transaction = serde_json::from_str(json).unwrap();
+        match &tx {
+            Transaction::Eip1559(inner) => {
+                assert_eq!(inner.chain_id, 5);
+                assert_eq!(inner.max_fee_per_gas, BigInt::from(2_000_000_000u64));
+                assert_eq!(inner.to, Some(vec![0x35; 20]));
+            }
+            _ => panic!("wrong transaction type"),
+        }
+        let round_trip: Transaction = serde_json::from_str(&serde_json::to_string(&tx).unwrap()).unwrap();
+        assert_eq!(round_trip, tx);
+
+        let short_to = json.replace("0x3535353535353535353535353535353535353535", "0x3535");
+        assert!(serde_json::from_str::<Transaction>(&short_to).is_err());
+        for nonce in &["-0x1", "0x-1", "0x+1", "0x1_0", "0x0x1", "0xg"] {
+            let bad_nonce = json.replace(r#""nonce":"0x1""#, &format!(r#""nonce":"{}""#, nonce));
+            assert!(serde_json::from_str::<Transaction>(&bad_nonce).is_err(), "{}", nonce);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Turning a message into the integer `e` that enters the ECDSA equation
+//! `s = k^-1 (e + r x)`.
+
+use std::str::FromStr;
+
+use cryptoxide::digest::Digest;
+use cryptoxide::sha2::Sha256;
+use cryptoxide::sha3::Sha3;
+
+use crate::curv::arithmetic::num_bigint::{from, BigInt};
+use crate::curv::elliptic::curves::secp256_k1::FE;
+use crate::curv::elliptic::curves::traits::ECScalar;
+use crate::Error::{self, InvalidMessage};
+
+const ETHEREUM_MESSAGE_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MessageHashMode {
+    /// the message is already a 32 byte digest
+    Prehashed,
+    Sha256,
+    /// SHA-256(SHA-256(m)), as used by Bitcoin
+    DoubleSha256,
+    Keccak256,
+    /// Keccak-256("\x19Ethereum Signed Message:\n" || len(m) || m), as used by `personal_sign`
+    EthereumPersonal,
+}
+
+impl MessageHashMode {
+    pub fn digest(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            MessageHashMode::Prehashed => {
+                if message.len() != 32 {
+                    return Err(InvalidMessage);
+                }
+                Ok(message.to_vec())
+            }
+            MessageHashMode::Sha256 => Ok(sha256(message)),
+            MessageHashMode::DoubleSha256 => Ok(sha256(&sha256(message))),
+            MessageHashMode::Keccak256 => Ok(keccak256(message)),
+            MessageHashMode::EthereumPersonal => {
+                let mut prefixed = ETHEREUM_MESSAGE_PREFIX.as_bytes().to_vec();
+                prefixed.extend_from_slice(message.len().to_string().as_bytes());
+                prefixed.extend_from_slice(message);
+                Ok(keccak256(&prefixed))
+            }
+        }
+    }
+
+    /// Hashes `message` and converts the digest with `bits2int`, ready to be
+    /// passed to `LocalSignature::phase5_local_sig` and `verify`.
+    pub fn hash(&self, message: &[u8]) -> Result<BigInt, Error> {
+        Ok(bits2int(&self.digest(message)?))
+    }
+}
+
+impl FromStr for MessageHashMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prehashed" => Ok(MessageHashMode::Prehashed),
+            "sha256" => Ok(MessageHashMode::Sha256),
+            "sha256d" => Ok(MessageHashMode::DoubleSha256),
+            "keccak256" => Ok(MessageHashMode::Keccak256),
+            "eth_personal" => Ok(MessageHashMode::EthereumPersonal),
+            _ => Err(InvalidMessage),
+        }
+    }
+}
+
+/// SEC1 4.1.3 step 5: interpret the digest as a big-endian integer and keep only
+/// its leftmost `qlen` bits when it is longer than the group order.
+pub fn bits2int(digest: &[u8]) -> BigInt {
+    bits2int_for::<FE>(digest)
+}
+
+/// `bits2int` for the order of the scalar field `S`. As in RFC 6979 section 2.3.2, the length
+/// of the digest is its length in bytes, leading zero bytes included.
+pub fn bits2int_for<S: ECScalar>(digest: &[u8]) -> BigInt {
+    let qlen = S::q().bits();
+    let e = from(digest);
+    let blen = digest.len() * 8;
+    if blen > qlen {
+        e >> (blen - qlen)
+    } else {
+        e
+    }
+}
+
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(data);
+    let mut result = [0u8; 32];
+    hasher.result(&mut result);
+    result.to_vec()
+}
+
+pub fn keccak256(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3::keccak256();
+    hasher.input(data);
+    let mut result = [0u8; 32];
+    hasher.result(&mut result);
+    result.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curv::arithmetic::traits::Converter;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_hash_modes() {
+        let sha = MessageHashMode::Sha256.hash(b"abc").unwrap();
+        assert_eq!(
+            sha.to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let sha_d = MessageHashMode::DoubleSha256.hash(b"abc").unwrap();
+        assert_eq!(
+            sha_d.to_hex(),
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+        );
+        let keccak = MessageHashMode::Keccak256.hash(b"").unwrap();
+        assert_eq!(
+            keccak.to_hex(),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        let personal = MessageHashMode::EthereumPersonal
+            .hash(b"hello world")
+            .unwrap();
+        assert_eq!(
+            personal.to_hex(),
+            "d9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68"
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_prehashed_length() {
+        let digest = [7u8; 32];
+        assert_eq!(
+            MessageHashMode::Prehashed.hash(&digest).unwrap(),
+            from(&digest[..])
+        );
+        assert_eq!(MessageHashMode::Prehashed.hash(&[7u8; 31]), Err(InvalidMessage));
+        assert_eq!(MessageHashMode::Prehashed.hash(&[7u8; 33]), Err(InvalidMessage));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_bits2int_truncates_long_digests() {
+        let mut digest = vec![0xffu8; 32];
+        digest.extend_from_slice(&[0x11u8; 32]);
+        assert_eq!(bits2int(&digest), from(&digest[..32]));
+
+        // leading zero bytes still count towards the digest length
+        let mut digest = vec![0u8; 33];
+        digest[32] = 1;
+        assert_eq!(bits2int(&digest), BigInt::from(0u32));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_mode_from_str() {
+        assert_eq!(
+            "eth_personal".parse::<MessageHashMode>(),
+            Ok(MessageHashMode::EthereumPersonal)
+        );
+        assert_eq!("sha256d".parse::<MessageHashMode>(), Ok(MessageHashMode::DoubleSha256));
+        assert!("md5".parse::<MessageHashMode>().is_err());
+    }
+}
@@ -14,6 +14,7 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+pub mod message;
 pub mod mta;
 pub mod party_i;
 pub mod range_proofs;
@@ -14,7 +14,8 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 use crate::curv::arithmetic::num_bigint::BigInt;
-use crate::curv::arithmetic::traits::Samplable;
+use crate::curv::arithmetic::traits::{os_rng, Samplable};
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
 use crate::curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
 use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
 use crate::curv::elliptic::curves::traits::*;
@@ -29,39 +30,81 @@ use crate::gg_2018::range_proofs::AliceProof;
 use crate::paillier::zkproofs::DLogStatement;
 use crate::num_traits::Pow;
 
-use crate::curv::elliptic::curves::secp256_k1::{Secp256k1Point, Secp256k1Scalar};
 use crate::paillier::traits::EncryptWithChosenRandomness;
+use rand::{CryptoRng, RngCore};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MessageA {
+#[serde(bound = "")]
+pub struct MessageA<S: ECScalar = FE> {
     pub c: BigInt, // paillier encryption
-    pub range_proofs: Vec<AliceProof>, // proofs (using other parties' h1,h2,N_tilde) that the plaintext is small
+    pub range_proofs: Vec<AliceProof<S>>, // proofs (using other parties' h1,h2,N_tilde) that the plaintext is small
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MessageB {
+#[serde(bound = "")]
+pub struct MessageB<P: ECPoint = GE> {
     pub c: BigInt, // paillier encryption
-    pub b_proof: DLogProof,
-    pub beta_tag_proof: DLogProof,
+    pub b_proof: DLogProof<P>,
+    pub beta_tag_proof: DLogProof<P>,
 }
 
-impl MessageA {
+impl<S: ECScalar> MessageA<S> {
+    /// `transcript` binds the range proofs to the session and to Alice, e.g.
+    /// `Transcript::for_party(label, session_id, alice_index)`. Bob verifies them against
+    /// the same transcript.
     pub fn a(
-        a: &Secp256k1Scalar,
+        transcript: &Transcript,
+        a: &S,
         alice_ek: &EncryptionKey,
         dlog_statements: &[DLogStatement],
         ) -> (Self, BigInt){
+        Self::a_with_rng(transcript, a, alice_ek, dlog_statements, &mut os_rng())
+    }
 
-        let randomness = BigInt::sample_below(&alice_ek.n);
-        let m_a = MessageA::a_with_predefined_randomness(a, alice_ek, &randomness, dlog_statements);
+    pub fn a_with_rng(
+        transcript: &Transcript,
+        a: &S,
+        alice_ek: &EncryptionKey,
+        dlog_statements: &[DLogStatement],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Self, BigInt) {
+        let randomness = BigInt::sample_below_with_rng(&alice_ek.n, rng);
+        let m_a = Self::a_with_predefined_randomness_and_rng(
+            transcript,
+            a,
+            alice_ek,
+            &randomness,
+            dlog_statements,
+            rng,
+        );
         (m_a, randomness)
     }
 
     pub fn a_with_predefined_randomness(
-        a: &Secp256k1Scalar,
+        transcript: &Transcript,
+        a: &S,
+        alice_ek: &EncryptionKey,
+        randomness: &BigInt,
+        dlog_statements: &[DLogStatement],
+    ) -> Self {
+        Self::a_with_predefined_randomness_and_rng(
+            transcript,
+            a,
+            alice_ek,
+            randomness,
+            dlog_statements,
+            &mut os_rng(),
+        )
+    }
+
+    /// As `a_with_predefined_randomness`, drawing the range proofs' randomness from `rng`.
+    pub fn a_with_predefined_randomness_and_rng(
+        transcript: &Transcript,
+        a: &S,
         alice_ek: &EncryptionKey,
         randomness: &BigInt,
         dlog_statements: &[DLogStatement],
+        rng: &mut (impl RngCore + CryptoRng),
     ) -> Self {
         let c_a = Paillier::encrypt_with_chosen_randomness(
             alice_ek,
@@ -74,9 +117,17 @@ impl MessageA {
         let alice_range_proofs = dlog_statements
             .iter()
             .map(|dlog_statement| {
-                AliceProof::generate(&a.to_big_int(), &c_a, alice_ek, dlog_statement, randomness)
+                AliceProof::generate_with_transcript_and_rng(
+                    &mut transcript.clone(),
+                    &a.to_big_int(),
+                    &c_a,
+                    alice_ek,
+                    dlog_statement,
+                    randomness,
+                    rng,
+                )
             })
-            .collect::<Vec<AliceProof>>();
+            .collect::<Vec<AliceProof<S>>>();
 
         Self {
             c: c_a,
@@ -85,35 +136,92 @@ impl MessageA {
     }
 }
 
-impl MessageB {
+impl<P: ECPoint> MessageB<P> {
+    /// `alice_transcript` is the transcript `m_a` was created with. `bob_transcript` binds
+    /// Bob's dlog proofs to the session and to Bob, e.g.
+    /// `Transcript::for_party(label, session_id, bob_index)`; Alice checks them against it.
     pub fn b(
-        b: &Secp256k1Scalar,
+        alice_transcript: &Transcript,
+        bob_transcript: &Transcript,
+        b: &P::Scalar,
+        alice_ek: &EncryptionKey,
+        m_a: MessageA<P::Scalar>,
+        dlog_statements: &[DLogStatement],
+        ) -> Result<(Self, P::Scalar, BigInt, BigInt), Error> {
+        Self::b_with_rng(
+            alice_transcript,
+            bob_transcript,
+            b,
+            alice_ek,
+            m_a,
+            dlog_statements,
+            &mut os_rng(),
+        )
+    }
+
+    pub fn b_with_rng(
+        alice_transcript: &Transcript,
+        bob_transcript: &Transcript,
+        b: &P::Scalar,
         alice_ek: &EncryptionKey,
-        m_a: MessageA,
+        m_a: MessageA<P::Scalar>,
         dlog_statements: &[DLogStatement],
-        ) -> Result<(Self, Secp256k1Scalar, BigInt, BigInt), Error> {
-        let beta_tag = BigInt::sample_below(&alice_ek.n);
-        let randomness = BigInt::sample_below(&alice_ek.n);
-        let (m_b, beta) = MessageB::b_with_predefined_randomness(
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self, P::Scalar, BigInt, BigInt), Error> {
+        let beta_tag = BigInt::sample_below_with_rng(&alice_ek.n, rng);
+        let randomness = BigInt::sample_below_with_rng(&alice_ek.n, rng);
+        let (m_b, beta) = Self::b_with_predefined_randomness_and_rng(
+            alice_transcript,
+            bob_transcript,
             b,
             alice_ek,
             m_a,
             &randomness,
             &beta_tag,
             dlog_statements,
+            rng,
         )?;
 
         Ok((m_b, beta, randomness, beta_tag))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn b_with_predefined_randomness(
-        b: &Secp256k1Scalar,
+        alice_transcript: &Transcript,
+        bob_transcript: &Transcript,
+        b: &P::Scalar,
+        alice_ek: &EncryptionKey,
+        m_a: MessageA<P::Scalar>,
+        randomness: &BigInt,
+        beta_tag: &BigInt,
+        dlog_statements: &[DLogStatement],
+    ) -> Result<(Self, P::Scalar), Error> {
+        Self::b_with_predefined_randomness_and_rng(
+            alice_transcript,
+            bob_transcript,
+            b,
+            alice_ek,
+            m_a,
+            randomness,
+            beta_tag,
+            dlog_statements,
+            &mut os_rng(),
+        )
+    }
+
+    /// As `b_with_predefined_randomness`, drawing the dlog proofs' randomness from `rng`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn b_with_predefined_randomness_and_rng(
+        alice_transcript: &Transcript,
+        bob_transcript: &Transcript,
+        b: &P::Scalar,
         alice_ek: &EncryptionKey,
-        m_a: MessageA,
+        m_a: MessageA<P::Scalar>,
         randomness: &BigInt,
         beta_tag: &BigInt,
         dlog_statements: &[DLogStatement],
-    ) -> Result<(Self, Secp256k1Scalar), Error> {
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self, P::Scalar), Error> {
         if m_a.range_proofs.len() != dlog_statements.len() {
             return Err(InvalidKey);
         }
@@ -122,12 +230,19 @@ impl MessageB {
             .range_proofs
             .iter()
             .zip(dlog_statements)
-            .map(|(proof, dlog_statement)| proof.verify(&m_a.c, alice_ek, dlog_statement))
+            .map(|(proof, dlog_statement)| {
+                proof.verify_with_transcript(
+                    &mut alice_transcript.clone(),
+                    &m_a.c,
+                    alice_ek,
+                    dlog_statement,
+                )
+            })
             .all(|x| x)
         {
             return Err(InvalidKey);
         };
-        let beta_tag_fe: Secp256k1Scalar = ECScalar::from(beta_tag);
+        let beta_tag_fe: P::Scalar = ECScalar::from(beta_tag);
         let c_beta_tag = Paillier::encrypt_with_chosen_randomness(
             alice_ek,
             RawPlaintext::from(beta_tag),
@@ -141,9 +256,11 @@ impl MessageB {
             RawPlaintext::from(b_bn),
         );
         let c_b = Paillier::add(alice_ek, b_c_a, c_beta_tag);
-        let beta = FE::zero().sub(&beta_tag_fe.get_element());
-        let dlog_proof_b = DLogProof::prove(b);
-        let dlog_proof_beta_tag = DLogProof::prove(&beta_tag_fe);
+        let beta = P::Scalar::zero().sub(&beta_tag_fe.get_element());
+        let [mut b_transcript, mut beta_tag_transcript] = Self::proof_transcripts(bob_transcript);
+        let dlog_proof_b = DLogProof::prove_with_transcript_and_rng(&mut b_transcript, b, rng);
+        let dlog_proof_beta_tag =
+            DLogProof::prove_with_transcript_and_rng(&mut beta_tag_transcript, &beta_tag_fe, rng);
 
         Ok((
             Self {
@@ -155,15 +272,36 @@ impl MessageB {
         ))
     }
 
-    pub fn verify_proofs_get_alpha(&self, dk: &DecryptionKey, a: &Secp256k1Scalar) -> Result<(Secp256k1Scalar, BigInt), Error> {
+    /// Transcripts of the proofs of knowledge of b and of beta_tag.
+    fn proof_transcripts(bob_transcript: &Transcript) -> [Transcript; 2] {
+        let mut b_transcript = bob_transcript.clone();
+        b_transcript.append_message(b"step", b"mta/b");
+        let mut beta_tag_transcript = bob_transcript.clone();
+        beta_tag_transcript.append_message(b"step", b"mta/beta-tag");
+        [b_transcript, beta_tag_transcript]
+    }
+
+    fn verify_proofs(&self, bob_transcript: &Transcript) -> bool {
+        DLogProof::batch_verify_with_transcripts(
+            &mut Self::proof_transcripts(bob_transcript),
+            &[self.b_proof.clone(), self.beta_tag_proof.clone()],
+        )
+        .is_ok()
+    }
+
+    /// `bob_transcript` is the one Bob passed to `b`.
+    pub fn verify_proofs_get_alpha(
+        &self,
+        bob_transcript: &Transcript,
+        dk: &DecryptionKey,
+        a: &P::Scalar,
+    ) -> Result<(P::Scalar, BigInt), Error> {
         let alice_share = Paillier::decrypt(dk, &RawCiphertext::from(self.c.clone()));
-        let g: GE = ECPoint::generator();
-        let alpha: FE = ECScalar::from(&alice_share.0);
-        let g_alpha = g * &alpha;
-        let ba_btag = &self.b_proof.pk * a + &self.beta_tag_proof.pk;
-        match DLogProof::verify(&self.b_proof).is_ok()
-            && DLogProof::verify(&self.beta_tag_proof).is_ok()
-            && ba_btag == g_alpha
+        let g = P::generator();
+        let alpha: P::Scalar = ECScalar::from(&alice_share.0);
+        let g_alpha = g * alpha.clone();
+        let ba_btag = self.b_proof.pk.clone() * a.clone() + self.beta_tag_proof.pk.clone();
+        match self.verify_proofs(bob_transcript) && ba_btag == g_alpha
         {
             true => Ok((alpha, alice_share.0.into_owned())),
             false => Err(InvalidKey),
@@ -174,25 +312,24 @@ impl MessageB {
     //  with the regular version mta can be used in general
     pub fn verify_proofs_get_alpha_gg18(
         &self,
-        private: &PartyPrivate,
-        a: &FE,
-    ) -> Result<FE, Error> {
+        bob_transcript: &Transcript,
+        private: &PartyPrivate<P>,
+        a: &P::Scalar,
+    ) -> Result<P::Scalar, Error> {
         let alice_share = private.decrypt(self.c.clone());
-        let g: GE = ECPoint::generator();
-        let alpha: FE = ECScalar::from(&alice_share.0);
-        let g_alpha = g * &alpha;
-        let ba_btag = &self.b_proof.pk * a + &self.beta_tag_proof.pk;
-
-        match DLogProof::verify(&self.b_proof).is_ok()
-            && DLogProof::verify(&self.beta_tag_proof).is_ok()
-            && ba_btag.get_element() == g_alpha.get_element()
+        let g = P::generator();
+        let alpha: P::Scalar = ECScalar::from(&alice_share.0);
+        let g_alpha = g * alpha.clone();
+        let ba_btag = self.b_proof.pk.clone() * a.clone() + self.beta_tag_proof.pk.clone();
+
+        match self.verify_proofs(bob_transcript) && ba_btag == g_alpha
         {
             true => Ok(alpha),
             false => Err(InvalidKey),
         }
     }
 
-    pub fn verify_b_against_public(public_gb: &GE, mta_gb: &GE) -> bool {
-        public_gb.get_element() == mta_gb.get_element()
+    pub fn verify_b_against_public(public_gb: &P, mta_gb: &P) -> bool {
+        public_gb == mta_gb
     }
 }
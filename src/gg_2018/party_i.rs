@@ -16,10 +16,11 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
-use crate::paillier::zkproofs::NICorrectKeyProof;
-use crate::paillier::KeyGeneration;
+use crate::gg_2018::message::bits2int_for;
+use crate::paillier::zkproofs::{DLogStatement, NICorrectKeyProof};
+use crate::paillier::{KeyGenProgress, KeyGeneration, KeypairSearch, PrimeSampable};
 use crate::paillier::Paillier;
-use crate::paillier::{DecryptionKey, EncryptionKey};
+use crate::paillier::{DecryptionKey, EncryptionKey, Keypair};
 use crate::Error::{self, InvalidCom, InvalidKey, InvalidSS, InvalidSig};
 
 use crate::curv::arithmetic::traits::*;
@@ -32,11 +33,18 @@ use crate::curv::cryptographic_primitives::commitments::traits::Commitment;
 use crate::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::curv::cryptographic_primitives::hashing::traits::Hash;
 use crate::curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::*;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
 use crate::curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
-use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use crate::curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use crate::curv::cryptographic_primitives::secret_sharing::pvss::PubliclyVerifiableSS;
 use crate::curv::elliptic::curves::secp256_k1::{FE, GE};
 use crate::paillier::{Decrypt, RawCiphertext, RawPlaintext};
 use num_integer::Integer;
+use num_traits::One;
+use rand::{CryptoRng, RngCore};
+use std::future::Future;
 
 use crate::console_log;
 use crate::log;
@@ -44,18 +52,152 @@ use crate::log;
 const SECURITY: usize = 256;
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct Keys {
-    pub u_i: FE,
-    pub y_i: GE,
+#[serde(bound = "")]
+pub struct Keys<P: ECPoint = GE> {
+    pub u_i: P::Scalar,
+    pub y_i: P,
     pub dk: DecryptionKey,
     pub ek: EncryptionKey,
     pub party_index: usize,
 }
 
+/// Keygen inputs that are slow to generate and do not depend on the other parties: the
+/// Paillier keypair, and the ring-Pedersen parameters the other parties use in their range
+/// proofs to this party, with h2 = h1^xhi mod N_tilde.
+///
+/// They can be generated ahead of time, in a background job or a Web Worker, stored, and then
+/// passed to `Keys::create_with_preparams`, leaving only the fast rounds to the interactive
+/// keygen.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PreParams {
+    pub keypair: Keypair,
+    pub n_tilde: BigInt,
+    pub h1: BigInt,
+    pub h2: BigInt,
+    pub xhi: BigInt,
+    pub xhi_inv: BigInt,
+}
+
+impl PreParams {
+    pub fn generate() -> PreParams {
+        Self::generate_with_modulus_size(2048)
+    }
+
+    /// Both the Paillier modulus and N_tilde have `bit_length` bits. N_tilde is the product of
+    /// two safe primes, which takes much longer to find than the Paillier key.
+    pub fn generate_with_modulus_size(bit_length: usize) -> PreParams {
+        Self::generate_with_modulus_size_and_rng(bit_length, &mut os_rng())
+    }
+
+    pub fn generate_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> PreParams {
+        Self::generate_with_modulus_size_and_rng(2048, rng)
+    }
+
+    pub fn generate_with_modulus_size_and_rng(
+        bit_length: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PreParams {
+        let keypair = Paillier::keypair_with_modulus_size_and_rng(bit_length, rng);
+        let n_tilde_factors = Keypair {
+            p: BigInt::sample_safe_prime_with_rng(bit_length / 2, rng),
+            q: BigInt::sample_safe_prime_with_rng(bit_length / 2, rng),
+        };
+        Self::from_keypairs(keypair, n_tilde_factors, rng)
+    }
+
+    /// Same as [`PreParams::generate_with_modulus_size`], searching for the primes through
+    /// [`KeypairSearch::run_async`]: `on_progress` is called for the Paillier key and then for
+    /// the safe primes of N_tilde, and `yield_now()` is awaited between chunks.
+    pub async fn generate_async<F, Y, Fut>(
+        bit_length: usize,
+        on_progress: F,
+        yield_now: Y,
+    ) -> PreParams
+    where
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        Self::generate_with_modulus_size_and_rng_async(
+            bit_length,
+            &mut os_rng(),
+            on_progress,
+            yield_now,
+        )
+        .await
+    }
+
+    pub async fn generate_with_modulus_size_and_rng_async<R, F, Y, Fut>(
+        bit_length: usize,
+        rng: &mut R,
+        mut on_progress: F,
+        mut yield_now: Y,
+    ) -> PreParams
+    where
+        R: RngCore + CryptoRng,
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let keypair = Paillier::keypair_with_modulus_size_and_rng_async(
+            bit_length,
+            rng,
+            &mut on_progress,
+            &mut yield_now,
+        )
+        .await;
+        let n_tilde_factors = KeypairSearch::new_safe_with_rng(bit_length, rng)
+            .run_async(rng, &mut on_progress, &mut yield_now)
+            .await;
+        Self::from_keypairs(keypair, n_tilde_factors, rng)
+    }
+
+    fn from_keypairs(
+        keypair: Keypair,
+        n_tilde_factors: Keypair,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PreParams {
+        let Keypair { p, q } = n_tilde_factors;
+        let one = BigInt::one();
+        let n_tilde = &p * &q;
+        let phi = (&p - &one) * (&q - &one);
+        // with p = 2p' + 1 and q = 2q' + 1 the squares form a cyclic group of order p'q', which
+        // a random square generates but with negligible probability
+        let r = BigInt::sample_range_with_rng(&one, &n_tilde, rng);
+        let h1 = BigInt::mod_mul(&r, &r, &n_tilde);
+        let xhi = loop {
+            let xhi = BigInt::sample_below_with_rng(&phi, rng);
+            if xhi.gcd(&phi) == one {
+                break xhi;
+            }
+        };
+        let xhi_inv = BigInt::mod_inv(&xhi, &phi);
+        let h2 = BigInt::mod_pow(&h1, &xhi, &n_tilde);
+        PreParams {
+            keypair,
+            n_tilde,
+            h1,
+            h2,
+            xhi,
+            xhi_inv,
+        }
+    }
+
+    /// The ring-Pedersen parameters to publish, as the other parties' range proofs take them.
+    pub fn dlog_statement(&self) -> DLogStatement {
+        DLogStatement {
+            N: self.n_tilde.clone(),
+            g: self.h1.clone(),
+            ni: self.h2.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct PartyPrivate {
-    u_i: FE,
-    x_i: FE,
+#[serde(bound = "")]
+pub struct PartyPrivate<P: ECPoint = GE> {
+    u_i: P::Scalar,
+    x_i: P::Scalar,
     dk: DecryptionKey,
 }
 
@@ -67,9 +209,10 @@ pub struct KeyGenBroadcastMessage1 {
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct KeyGenDecommitMessage1 {
+#[serde(bound = "")]
+pub struct KeyGenDecommitMessage1<P: ECPoint = GE> {
     pub blind_factor: BigInt,
-    pub y_i: GE,
+    pub y_i: P,
 }
 
 #[derive(Debug)]
@@ -79,18 +222,20 @@ pub struct Parameters {
 }
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct SharedKeys {
-    pub y: GE,
-    pub x_i: FE,
+#[serde(bound = "")]
+pub struct SharedKeys<P: ECPoint = GE> {
+    pub y: P,
+    pub x_i: P::Scalar,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct SignKeys {
-    pub w_i: FE,
-    pub g_w_i: GE,
-    pub k_i: FE,
-    pub gamma_i: FE,
-    pub g_gamma_i: GE,
+#[serde(bound = "")]
+pub struct SignKeys<P: ECPoint = GE> {
+    pub w_i: P::Scalar,
+    pub g_w_i: P,
+    pub k_i: P::Scalar,
+    pub gamma_i: P::Scalar,
+    pub g_gamma_i: P,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -99,19 +244,21 @@ pub struct SignBroadcastPhase1 {
 }
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct SignDecommitPhase1 {
+#[serde(bound = "")]
+pub struct SignDecommitPhase1<P: ECPoint = GE> {
     pub blind_factor: BigInt,
-    pub g_gamma_i: GE,
+    pub g_gamma_i: P,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct LocalSignature {
-    pub l_i: FE,
-    pub rho_i: FE,
-    pub R: GE,
-    pub s_i: FE,
+#[serde(bound = "")]
+pub struct LocalSignature<P: ECPoint = GE> {
+    pub l_i: P::Scalar,
+    pub rho_i: P::Scalar,
+    pub R: P,
+    pub s_i: P::Scalar,
     pub m: BigInt,
-    pub y: GE,
+    pub y: P,
 }
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Phase5Com1 {
@@ -124,31 +271,39 @@ pub struct Phase5Com2 {
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Phase5ADecom1 {
-    pub V_i: GE,
-    pub A_i: GE,
-    pub B_i: GE,
+#[serde(bound = "")]
+pub struct Phase5ADecom1<P: ECPoint = GE> {
+    pub V_i: P,
+    pub A_i: P,
+    pub B_i: P,
     pub blind_factor: BigInt,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Phase5DDecom2 {
-    pub u_i: GE,
-    pub t_i: GE,
+#[serde(bound = "")]
+pub struct Phase5DDecom2<P: ECPoint = GE> {
+    pub u_i: P,
+    pub t_i: P,
     pub blind_factor: BigInt,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct Signature {
-    pub r: FE,
-    pub s: FE,
+#[serde(bound = "")]
+pub struct Signature<S: ECScalar = FE> {
+    pub r: S,
+    pub s: S,
+    pub recid: u8,
 }
 
-impl Keys {
-    pub fn create(index: usize) -> Keys {
-        let u: FE = ECScalar::new_random();
-        let y = &ECPoint::generator() * &u;
-        let (ek, dk) = Paillier::keypair().keys();
+impl<P: ECPoint> Keys<P> {
+    pub fn create(index: usize) -> Keys<P> {
+        Self::create_with_rng(index, &mut os_rng())
+    }
+
+    pub fn create_with_rng(index: usize, rng: &mut (impl RngCore + CryptoRng)) -> Keys<P> {
+        let u = P::Scalar::new_random_with_rng(rng);
+        let y = P::generator() * u.clone();
+        let (ek, dk) = Paillier::keypair_with_rng(rng).keys();
 
         Keys {
             u_i: u,
@@ -159,9 +314,81 @@ impl Keys {
         }
     }
 
-    pub fn create_from(u: FE, index: usize) -> Keys {
-        let y = &ECPoint::generator() * &u;
-        let (ek, dk) = Paillier::keypair().keys();
+    /// Same as [`Keys::create`], generating the Paillier key through
+    /// [`Paillier::keypair_with_modulus_size_async`] so that the caller can report progress and
+    /// yield to its event loop while the primes are searched for.
+    pub async fn create_async<F, Y, Fut>(index: usize, on_progress: F, yield_now: Y) -> Keys<P>
+    where
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        Self::create_with_rng_async(index, &mut os_rng(), on_progress, yield_now).await
+    }
+
+    pub async fn create_with_rng_async<R, F, Y, Fut>(
+        index: usize,
+        rng: &mut R,
+        on_progress: F,
+        yield_now: Y,
+    ) -> Keys<P>
+    where
+        R: RngCore + CryptoRng,
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let u = P::Scalar::new_random_with_rng(rng);
+        let y = P::generator() * u.clone();
+        let (ek, dk) =
+            Paillier::keypair_with_modulus_size_and_rng_async(2048, rng, on_progress, yield_now)
+                .await
+                .keys();
+
+        Keys {
+            u_i: u,
+            y_i: y,
+            dk,
+            ek,
+            party_index: index,
+        }
+    }
+
+    /// Creates the keys of a party from pre-generated parameters, skipping the Paillier key
+    /// generation.
+    pub fn create_with_preparams(index: usize, preparams: &PreParams) -> Keys<P> {
+        Self::create_with_preparams_and_rng(index, preparams, &mut os_rng())
+    }
+
+    pub fn create_with_preparams_and_rng(
+        index: usize,
+        preparams: &PreParams,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Keys<P> {
+        let u = P::Scalar::new_random_with_rng(rng);
+        let y = P::generator() * u.clone();
+        let (ek, dk) = preparams.keypair.keys();
+
+        Keys {
+            u_i: u,
+            y_i: y,
+            dk,
+            ek,
+            party_index: index,
+        }
+    }
+
+    pub fn create_from(u: P::Scalar, index: usize) -> Keys<P> {
+        Self::create_from_with_rng(u, index, &mut os_rng())
+    }
+
+    pub fn create_from_with_rng(
+        u: P::Scalar,
+        index: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Keys<P> {
+        let y = P::generator() * u.clone();
+        let (ek, dk) = Paillier::keypair_with_rng(rng).keys();
 
         Keys {
             u_i: u,
@@ -172,11 +399,26 @@ impl Keys {
         }
     }
 
+    /// `session_id` identifies the key generation run and must be the same for all parties.
+    /// The proof of a correct Paillier key is bound to it and to `party_index`, which must be
+    /// the position of this party in the broadcast order, counting from 1.
     pub fn phase1_broadcast_phase3_proof_of_correct_key(
         &self,
-    ) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1) {
-        let blind_factor = BigInt::sample(SECURITY);
-        let correct_key_proof = NICorrectKeyProof::proof(&self.dk);
+        session_id: &[u8],
+    ) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1<P>) {
+        self.phase1_broadcast_phase3_proof_of_correct_key_with_rng(session_id, &mut os_rng())
+    }
+
+    pub fn phase1_broadcast_phase3_proof_of_correct_key_with_rng(
+        &self,
+        session_id: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (KeyGenBroadcastMessage1, KeyGenDecommitMessage1<P>) {
+        let blind_factor = BigInt::sample_with_rng(SECURITY, rng);
+        let correct_key_proof = NICorrectKeyProof::proof_with_transcript(
+            &mut Self::correct_key_transcript(session_id, self.party_index),
+            &self.dk,
+        );
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &self.y_i.bytes_compressed_to_big_int(),
             &blind_factor,
@@ -196,39 +438,150 @@ impl Keys {
     pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
         &self,
         params: &Parameters,
-        decom_vec: &Vec<KeyGenDecommitMessage1>,
+        session_id: &[u8],
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+    ) -> Result<(VerifiableSS<P>, Vec<P::Scalar>, usize), Error> {
+        self.phase1_verify_com_phase3_verify_correct_key_phase2_distribute_with_rng(
+            params,
+            session_id,
+            decom_vec,
+            bc1_vec,
+            &mut os_rng(),
+        )
+    }
+
+    pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute_with_rng(
+        &self,
+        params: &Parameters,
+        session_id: &[u8],
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(VerifiableSS<P>, Vec<P::Scalar>, usize), Error> {
+        let correct_key_correct_decom_all =
+            Self::verify_com_and_correct_keys(params, session_id, decom_vec, bc1_vec);
+
+        let (vss_scheme, secret_shares) =
+            VerifiableSS::share_with_rng(params.threshold, params.share_count, &self.u_i, rng);
+        match correct_key_correct_decom_all {
+            true => Ok((vss_scheme, secret_shares, self.party_index.clone())),
+            false => Err(InvalidKey),
+        }
+    }
+
+    /// PVSS mode of phase 2: the shares are encrypted to the parties' PVSS encryption keys
+    /// and broadcast along with proofs that they match the commitments, instead of being sent
+    /// privately. `pvss_ek_vec[i]` is the key of party i + 1, see `create_pvss_keypair`.
+    pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss(
+        &self,
+        params: &Parameters,
+        session_id: &[u8],
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
         bc1_vec: &Vec<KeyGenBroadcastMessage1>,
-    ) -> Result<(VerifiableSS, Vec<FE>, usize), Error> {
+        pvss_ek_vec: &[P],
+    ) -> Result<(PubliclyVerifiableSS<P>, usize), Error> {
+        self.phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss_with_rng(
+            params,
+            session_id,
+            decom_vec,
+            bc1_vec,
+            pvss_ek_vec,
+            &mut os_rng(),
+        )
+    }
+
+    pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss_with_rng(
+        &self,
+        params: &Parameters,
+        session_id: &[u8],
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+        pvss_ek_vec: &[P],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(PubliclyVerifiableSS<P>, usize), Error> {
+        if !Self::verify_com_and_correct_keys(params, session_id, decom_vec, bc1_vec)
+            || pvss_ek_vec.len() != params.share_count
+        {
+            return Err(InvalidKey);
+        }
+        let (pvss_scheme, _) =
+            PubliclyVerifiableSS::share_with_rng(params.threshold, &self.u_i, pvss_ek_vec, rng);
+        Ok((pvss_scheme, self.party_index.clone()))
+    }
+
+    /// ElGamal keypair `(dk, ek)` a party receives its PVSS shares under. It is separate from
+    /// u_i, so decrypting shares never touches the keygen secret, and is only needed until
+    /// phase 2: `ek` is broadcast with the phase 1 decommitment and `dk` then dropped.
+    pub fn create_pvss_keypair() -> (P::Scalar, P) {
+        Self::create_pvss_keypair_with_rng(&mut os_rng())
+    }
+
+    pub fn create_pvss_keypair_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> (P::Scalar, P) {
+        let dk = P::Scalar::new_random_with_rng(rng);
+        let ek = P::generator() * dk.clone();
+        (dk, ek)
+    }
+
+    /// Transcript of the proof that party `index` holds a well-formed Paillier key.
+    fn correct_key_transcript(session_id: &[u8], index: usize) -> Transcript {
+        Transcript::for_party(b"gg18/keygen/correct-key", session_id, index as u64)
+    }
+
+    fn verify_com_and_correct_keys(
+        params: &Parameters,
+        session_id: &[u8],
+        decom_vec: &Vec<KeyGenDecommitMessage1<P>>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+    ) -> bool {
         // test length:
         assert_eq!(decom_vec.len(), params.share_count);
         assert_eq!(bc1_vec.len(), params.share_count);
         // test paillier correct key and test decommitments
-        let correct_key_correct_decom_all = (0..bc1_vec.len())
+        (0..bc1_vec.len())
             .map(|i| {
                 HashCommitment::create_commitment_with_user_defined_randomness(
                     &decom_vec[i].y_i.bytes_compressed_to_big_int(),
                     &decom_vec[i].blind_factor,
                 ) == bc1_vec[i].com
-                    && bc1_vec[i].correct_key_proof.verify(&bc1_vec[i].e).is_ok()
+                    && bc1_vec[i]
+                        .correct_key_proof
+                        .verify_with_transcript(
+                            &mut Self::correct_key_transcript(session_id, i + 1),
+                            &bc1_vec[i].e,
+                        )
+                        .is_ok()
             })
-            .all(|x| x == true);
-
-        let (vss_scheme, secret_shares) =
-            VerifiableSS::share(params.threshold, params.share_count, &self.u_i);
-        match correct_key_correct_decom_all {
-            true => Ok((vss_scheme, secret_shares, self.party_index.clone())),
-            false => Err(InvalidKey),
-        }
+            .all(|x| x == true)
     }
 
     pub fn phase2_verify_vss_construct_keypair_phase3_pok_dlog(
         &self,
         params: &Parameters,
-        y_vec: &Vec<GE>,
-        secret_shares_vec: &Vec<FE>,
-        vss_scheme_vec: &Vec<VerifiableSS>,
+        y_vec: &Vec<P>,
+        secret_shares_vec: &Vec<P::Scalar>,
+        vss_scheme_vec: &Vec<VerifiableSS<P>>,
+        index: &usize,
+    ) -> Result<(SharedKeys<P>, DLogProof<P>), Error> {
+        self.phase2_verify_vss_construct_keypair_phase3_pok_dlog_with_rng(
+            params,
+            y_vec,
+            secret_shares_vec,
+            vss_scheme_vec,
+            index,
+            &mut os_rng(),
+        )
+    }
+
+    pub fn phase2_verify_vss_construct_keypair_phase3_pok_dlog_with_rng(
+        &self,
+        params: &Parameters,
+        y_vec: &Vec<P>,
+        secret_shares_vec: &Vec<P::Scalar>,
+        vss_scheme_vec: &Vec<VerifiableSS<P>>,
         index: &usize,
-    ) -> Result<(SharedKeys, DLogProof), Error> {
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(SharedKeys<P>, DLogProof<P>), Error> {
         assert_eq!(y_vec.len(), params.share_count);
         assert_eq!(secret_shares_vec.len(), params.share_count);
         assert_eq!(vss_scheme_vec.len(), params.share_count);
@@ -238,7 +591,7 @@ impl Keys {
                 vss_scheme_vec[i]
                     .validate_share(&secret_shares_vec[i], *index)
                     .is_ok()
-                    && vss_scheme_vec[i].commitments[0].get_element() == y_vec[i].get_element()
+                    && vss_scheme_vec[i].commitments[0] == y_vec[i]
             })
             .all(|x| x == true);
 
@@ -246,54 +599,164 @@ impl Keys {
             true => {
                 let mut y_vec_iter = y_vec.iter();
                 let y0 = y_vec_iter.next().unwrap();
-                let y = y_vec_iter.fold(y0.clone(), |acc, x| acc + x);
-                let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
-                let dlog_proof = DLogProof::prove(&x_i);
+                let y = y_vec_iter.fold(y0.clone(), |acc, x| acc + x.clone());
+                let x_i = secret_shares_vec
+                    .iter()
+                    .fold(P::Scalar::zero(), |acc, x| acc + x.clone());
+                let dlog_proof = DLogProof::prove_with_transcript_and_rng(
+                    &mut Self::pok_dlog_transcript(&y, *index),
+                    &x_i,
+                    rng,
+                );
                 Ok((SharedKeys { y, x_i }, dlog_proof))
             }
             false => Err(InvalidSS),
         }
     }
 
-    pub fn get_commitments_to_xi(vss_scheme_vec: &Vec<VerifiableSS>) -> Vec<GE> {
-        let len = vss_scheme_vec.len();
-        let xi_points_vec = (1..len + 1)
-            .map(|i| {
-                let xij_points_vec = (0..len)
-                    .map(|j| vss_scheme_vec[j].get_point_commitment(i))
-                    .collect::<Vec<GE>>();
+    /// PVSS mode of phase 2 and 3: checks every dealing, decrypts this party's shares with its
+    /// PVSS decryption key `pvss_dk` and continues as
+    /// `phase2_verify_vss_construct_keypair_phase3_pok_dlog`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn phase2_verify_pvss_construct_keypair_phase3_pok_dlog(
+        &self,
+        params: &Parameters,
+        y_vec: &Vec<P>,
+        pvss_ek_vec: &[P],
+        pvss_scheme_vec: &Vec<PubliclyVerifiableSS<P>>,
+        index: &usize,
+        pvss_dk: &P::Scalar,
+    ) -> Result<(SharedKeys<P>, DLogProof<P>), Error> {
+        self.phase2_verify_pvss_construct_keypair_phase3_pok_dlog_with_rng(
+            params,
+            y_vec,
+            pvss_ek_vec,
+            pvss_scheme_vec,
+            index,
+            pvss_dk,
+            &mut os_rng(),
+        )
+    }
 
-                let mut xij_points_iter = xij_points_vec.iter();
-                let first = xij_points_iter.next().unwrap();
+    #[allow(clippy::too_many_arguments)]
+    pub fn phase2_verify_pvss_construct_keypair_phase3_pok_dlog_with_rng(
+        &self,
+        params: &Parameters,
+        y_vec: &Vec<P>,
+        pvss_ek_vec: &[P],
+        pvss_scheme_vec: &Vec<PubliclyVerifiableSS<P>>,
+        index: &usize,
+        pvss_dk: &P::Scalar,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(SharedKeys<P>, DLogProof<P>), Error> {
+        Self::verify_pvss_dealings(params, pvss_scheme_vec, y_vec, pvss_ek_vec)?;
+        let secret_shares_vec = pvss_scheme_vec
+            .iter()
+            .map(|pvss_scheme| pvss_scheme.decrypt_share(*index, pvss_dk))
+            .collect::<Result<Vec<P::Scalar>, _>>()
+            .map_err(|_| InvalidSS)?;
+        let vss_scheme_vec = pvss_scheme_vec
+            .iter()
+            .map(|pvss_scheme| pvss_scheme.vss.clone())
+            .collect::<Vec<VerifiableSS<P>>>();
+        self.phase2_verify_vss_construct_keypair_phase3_pok_dlog_with_rng(
+            params,
+            y_vec,
+            &secret_shares_vec,
+            &vss_scheme_vec,
+            index,
+            rng,
+        )
+    }
 
-                let tail = xij_points_iter;
-                tail.fold(first.clone(), |acc, x| acc + x)
-            })
-            .collect::<Vec<GE>>();
+    /// Lets anyone check the dealings of PVSS mode: dealing i must share the secret behind
+    /// y_vec[i] and encrypt a valid share to each party's key in `pvss_ek_vec`.
+    pub fn verify_pvss_dealings(
+        params: &Parameters,
+        pvss_scheme_vec: &Vec<PubliclyVerifiableSS<P>>,
+        y_vec: &Vec<P>,
+        pvss_ek_vec: &[P],
+    ) -> Result<(), Error> {
+        assert_eq!(y_vec.len(), params.share_count);
+        assert_eq!(pvss_scheme_vec.len(), params.share_count);
+        if pvss_ek_vec.len() != params.share_count {
+            return Err(InvalidSS);
+        }
+        let all_valid = pvss_scheme_vec
+            .iter()
+            .zip(y_vec)
+            .all(|(pvss_scheme, y_i)| {
+                pvss_scheme.vss.parameters.threshold == params.threshold
+                    && pvss_scheme.vss.commitments.first() == Some(y_i)
+                    && pvss_scheme.verify(pvss_ek_vec).is_ok()
+            });
+        match all_valid {
+            true => Ok(()),
+            false => Err(InvalidSS),
+        }
+    }
 
-        xi_points_vec
+    pub fn get_commitments_to_xi(vss_scheme_vec: &Vec<VerifiableSS<P>>) -> Vec<P> {
+        let len = vss_scheme_vec.len();
+        // sum_j C_j(i) = sum_k (sum_j C_jk) i^k: add the commitments coefficient-wise once,
+        // then evaluate the aggregate at every index with a single multi-scalar multiplication.
+        let degree = vss_scheme_vec
+            .iter()
+            .map(|vss_scheme| vss_scheme.commitments.len())
+            .max()
+            .unwrap_or(0);
+        let aggregate = VerifiableSS {
+            parameters: ShamirSecretSharing {
+                threshold: degree.saturating_sub(1),
+                share_count: len,
+            },
+            commitments: (0..degree)
+                .map(|k| {
+                    vss_scheme_vec
+                        .iter()
+                        .filter_map(|vss_scheme| vss_scheme.commitments.get(k))
+                        .sum()
+                })
+                .collect::<Vec<P>>(),
+        };
+
+        (1..len + 1)
+            .map(|i| aggregate.get_point_commitment(i))
+            .collect::<Vec<P>>()
     }
 
     pub fn update_commitments_to_xi(
-        comm: &GE,
-        vss_scheme: &VerifiableSS,
+        comm: &P,
+        vss_scheme: &VerifiableSS<P>,
         index: usize,
         s: &Vec<usize>,
-    ) -> GE {
+    ) -> P {
         let li = vss_scheme.map_share_to_new_params(index, s);
-        comm * &li
+        comm.clone() * li
+    }
+
+    /// Transcript of the proof of knowledge of x_i: bound to the joint public key, which
+    /// identifies the key generation session, and to the share index of the prover.
+    fn pok_dlog_transcript(y: &P, index: usize) -> Transcript {
+        let mut transcript =
+            Transcript::for_party(b"gg18/keygen/pok-dlog", &y.to_bytes(true), index as u64);
+        transcript.append_message(b"step", b"phase3");
+        transcript
     }
 
     pub fn verify_dlog_proofs(
         params: &Parameters,
-        dlog_proofs_vec: &Vec<DLogProof>,
-        y_vec: &Vec<GE>,
+        dlog_proofs_vec: &Vec<DLogProof<P>>,
+        y_vec: &Vec<P>,
     ) -> Result<(), Error> {
         assert_eq!(y_vec.len(), params.share_count);
         assert_eq!(dlog_proofs_vec.len(), params.share_count);
-        let xi_dlog_verify = (0..y_vec.len())
-            .map(|i| DLogProof::verify(&dlog_proofs_vec[i]).is_ok())
-            .all(|x| x == true);
+        let y = y_vec.iter().sum::<P>();
+        let mut transcripts = (0..y_vec.len())
+            .map(|i| Self::pok_dlog_transcript(&y, i + 1))
+            .collect::<Vec<Transcript>>();
+        let xi_dlog_verify =
+            DLogProof::batch_verify_with_transcripts(&mut transcripts, dlog_proofs_vec).is_ok();
 
         match xi_dlog_verify {
             true => Ok(()),
@@ -302,8 +765,8 @@ impl Keys {
     }
 }
 
-impl PartyPrivate {
-    pub fn set_private(key: Keys, shared_key: SharedKeys) -> PartyPrivate {
+impl<P: ECPoint> PartyPrivate<P> {
+    pub fn set_private(key: Keys<P>, shared_key: SharedKeys<P>) -> PartyPrivate<P> {
         let key_private = PartyPrivate {
             u_i: key.u_i,
             x_i: shared_key.x_i,
@@ -312,19 +775,27 @@ impl PartyPrivate {
         key_private
     }
 
-    pub fn y_i(&self) -> GE {
-        let g: GE = ECPoint::generator();
-        g * self.u_i.clone()
+    pub fn y_i(&self) -> P {
+        P::generator() * self.u_i.clone()
     }
 
     pub fn decrypt(&self, ciphertext: BigInt) -> RawPlaintext {
         Paillier::decrypt(&self.dk, &RawCiphertext::from(ciphertext))
     }
 
-    pub fn refresh_private_key(&self, factor: &FE, index: usize) -> Keys {
-        let u: FE = self.u_i.clone() + factor;
-        let y = &ECPoint::generator() * &u;
-        let (ek, dk) = Paillier::keypair().keys();
+    pub fn refresh_private_key(&self, factor: &P::Scalar, index: usize) -> Keys<P> {
+        self.refresh_private_key_with_rng(factor, index, &mut os_rng())
+    }
+
+    pub fn refresh_private_key_with_rng(
+        &self,
+        factor: &P::Scalar,
+        index: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Keys<P> {
+        let u = self.u_i.clone() + factor.clone();
+        let y = P::generator() * u.clone();
+        let (ek, dk) = Paillier::keypair_with_rng(rng).keys();
 
         Keys {
             u_i: u,
@@ -335,41 +806,58 @@ impl PartyPrivate {
         }
     }
 
-    pub fn update_private_key(&self, factor_u_i: &FE, factor_x_i: &FE) -> Self {
+    pub fn update_private_key(&self, factor_u_i: &P::Scalar, factor_x_i: &P::Scalar) -> Self {
         PartyPrivate {
-            u_i: self.u_i.clone() + factor_u_i,
-            x_i: self.x_i.clone() + factor_x_i,
+            u_i: self.u_i.clone() + factor_u_i.clone(),
+            x_i: self.x_i.clone() + factor_x_i.clone(),
             dk: self.dk.clone(),
         }
     }
 }
 
-impl SignKeys {
+impl<P: ECPoint> SignKeys<P> {
     pub fn create(
-        private: &PartyPrivate,
-        vss_scheme: &VerifiableSS,
+        private: &PartyPrivate<P>,
+        vss_scheme: &VerifiableSS<P>,
+        index: usize,
+        s: &Vec<usize>,
+    ) -> SignKeys<P> {
+        Self::create_with_rng(private, vss_scheme, index, s, &mut os_rng())
+    }
+
+    pub fn create_with_rng(
+        private: &PartyPrivate<P>,
+        vss_scheme: &VerifiableSS<P>,
         index: usize,
         s: &Vec<usize>,
-    ) -> SignKeys {
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> SignKeys<P> {
         let li = vss_scheme.map_share_to_new_params(index, s);
-        let w_i = li * &private.x_i;
-        let g: GE = ECPoint::generator();
-        let g_w_i = &g * &w_i;
-        let gamma_i: FE = ECScalar::new_random();
-        let g_gamma_i = &g * &gamma_i;
+        let w_i = li * private.x_i.clone();
+        let g = P::generator();
+        let g_w_i = g.clone() * w_i.clone();
+        let gamma_i = P::Scalar::new_random_with_rng(rng);
+        let g_gamma_i = g * gamma_i.clone();
         SignKeys {
             w_i,
             g_w_i,
-            k_i: ECScalar::new_random(),
+            k_i: ECScalar::new_random_with_rng(rng),
             gamma_i,
             g_gamma_i,
         }
     }
 
-    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1) {
-        let blind_factor = BigInt::sample(SECURITY);
-        let g: GE = ECPoint::generator();
-        let g_gamma_i = g * &self.gamma_i;
+    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1<P>) {
+        self.phase1_broadcast_with_rng(&mut os_rng())
+    }
+
+    pub fn phase1_broadcast_with_rng(
+        &self,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (SignBroadcastPhase1, SignDecommitPhase1<P>) {
+        let blind_factor = BigInt::sample_with_rng(SECURITY, rng);
+        let g = P::generator();
+        let g_gamma_i = g * self.gamma_i.clone();
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &g_gamma_i.bytes_compressed_to_big_int(),
             &blind_factor,
@@ -384,45 +872,47 @@ impl SignKeys {
         )
     }
 
-    pub fn phase2_delta_i(&self, alpha_vec: &Vec<FE>, beta_vec: &Vec<FE>) -> FE {
+    pub fn phase2_delta_i(&self, alpha_vec: &Vec<P::Scalar>, beta_vec: &Vec<P::Scalar>) -> P::Scalar {
         let vec_len = alpha_vec.len();
         assert_eq!(alpha_vec.len(), beta_vec.len());
         // assert_eq!(alpha_vec.len(), self.s.len() - 1);
-        let ki_gamma_i = self.k_i.mul(&self.gamma_i.get_element());
+        let ki_gamma_i = self.k_i.clone() * self.gamma_i.clone();
         let sum = (0..vec_len)
-            .map(|i| alpha_vec[i].add(&beta_vec[i].get_element()))
+            .map(|i| alpha_vec[i].clone() + beta_vec[i].clone())
             .fold(ki_gamma_i, |acc, x| acc + x);
         return sum;
     }
 
-    pub fn phase2_sigma_i(&self, miu_vec: &Vec<FE>, ni_vec: &Vec<FE>) -> FE {
+    pub fn phase2_sigma_i(&self, miu_vec: &Vec<P::Scalar>, ni_vec: &Vec<P::Scalar>) -> P::Scalar {
         let vec_len = miu_vec.len();
         assert_eq!(miu_vec.len(), ni_vec.len());
         //assert_eq!(miu_vec.len(), self.s.len() - 1);
-        let ki_w_i = self.k_i.mul(&self.w_i.get_element());
+        let ki_w_i = self.k_i.clone() * self.w_i.clone();
         let sum = (0..vec_len)
-            .map(|i| miu_vec[i].add(&ni_vec[i].get_element()))
+            .map(|i| miu_vec[i].clone() + ni_vec[i].clone())
             .fold(ki_w_i, |acc, x| acc + x);
         return sum;
     }
 
-    pub fn phase3_reconstruct_delta(delta_vec: &Vec<FE>) -> FE {
-        let sum = delta_vec.iter().fold(FE::zero(), |acc, x| acc + x);
+    pub fn phase3_reconstruct_delta(delta_vec: &Vec<P::Scalar>) -> P::Scalar {
+        let sum = delta_vec
+            .iter()
+            .fold(P::Scalar::zero(), |acc, x| acc + x.clone());
         sum.invert()
     }
 
     pub fn phase4(
-        delta_inv: &FE,
-        b_proof_vec: &Vec<&DLogProof>,
-        phase1_decommit_vec: Vec<SignDecommitPhase1>,
+        delta_inv: &P::Scalar,
+        b_proof_vec: &Vec<&DLogProof<P>>,
+        phase1_decommit_vec: Vec<SignDecommitPhase1<P>>,
         // blind_vec: &Vec<BigInt>,
         //  g_gamma_i_vec: &Vec<GE>,
         bc1_vec: &Vec<SignBroadcastPhase1>,
-    ) -> Result<GE, Error> {
+    ) -> Result<P, Error> {
         crate::console_log!("419");
         let test_b_vec_and_com = (0..b_proof_vec.len())
             .map(|i| {
-                b_proof_vec[i].pk.get_element() == phase1_decommit_vec[i].g_gamma_i.get_element()
+                b_proof_vec[i].pk == phase1_decommit_vec[i].g_gamma_i
                     && HashCommitment::create_commitment_with_user_defined_randomness(
                         &phase1_decommit_vec[i]
                             .g_gamma_i
@@ -442,8 +932,8 @@ impl SignKeys {
         crate::console_log!("438");
         match test_b_vec_and_com {
             true => Ok({
-                let gamma_sum = tail.fold(head.g_gamma_i.clone(), |acc, x| acc + &x.g_gamma_i);
-                let R = gamma_sum * delta_inv;
+                let gamma_sum = tail.fold(head.g_gamma_i.clone(), |acc, x| acc + x.g_gamma_i.clone());
+                let R = gamma_sum * delta_inv.clone();
                 R
             }),
             false => Err(InvalidKey),
@@ -451,19 +941,30 @@ impl SignKeys {
     }
 }
 
-impl LocalSignature {
+impl<P: ECPoint> LocalSignature<P> {
     pub fn phase5_local_sig(
-        k_i: &FE,
+        k_i: &P::Scalar,
         message: &BigInt,
-        R: &GE,
-        sigma_i: &FE,
-        pubkey: &GE,
-    ) -> LocalSignature {
-        let m_fe: FE = ECScalar::from(message);
-        let r: FE = ECScalar::from(&R.x_coor().unwrap().mod_floor(&FE::q()));
-        let s_i = m_fe * k_i + r * sigma_i;
-        let l_i: FE = ECScalar::new_random();
-        let rho_i: FE = ECScalar::new_random();
+        R: &P,
+        sigma_i: &P::Scalar,
+        pubkey: &P,
+    ) -> LocalSignature<P> {
+        Self::phase5_local_sig_with_rng(k_i, message, R, sigma_i, pubkey, &mut os_rng())
+    }
+
+    pub fn phase5_local_sig_with_rng(
+        k_i: &P::Scalar,
+        message: &BigInt,
+        R: &P,
+        sigma_i: &P::Scalar,
+        pubkey: &P,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> LocalSignature<P> {
+        let m_fe: P::Scalar = message_to_scalar(message);
+        let r: P::Scalar = ECScalar::from(&R.x_coor().unwrap().mod_floor(&P::Scalar::q()));
+        let s_i = m_fe * k_i.clone() + r * sigma_i.clone();
+        let l_i = P::Scalar::new_random_with_rng(rng);
+        let rho_i = P::Scalar::new_random_with_rng(rng);
         LocalSignature {
             l_i,
             rho_i,
@@ -474,15 +975,28 @@ impl LocalSignature {
         }
     }
 
+    /// The proofs are bound to the signing session `session_id` and to `index`, the key share
+    /// index this party signs with.
     pub fn phase5a_broadcast_5b_zkproof(
         &self,
-    ) -> (Phase5Com1, Phase5ADecom1, HomoELGamalProof, DLogProof) {
-        let blind_factor = BigInt::sample(SECURITY);
-        let g: GE = ECPoint::generator();
-        let A_i = &g * &self.rho_i;
-        let l_i_rho_i = self.l_i.mul(&self.rho_i.get_element());
-        let B_i = &g * &l_i_rho_i;
-        let V_i = &self.R * &self.s_i + &g * &self.l_i;
+        session_id: &[u8],
+        index: usize,
+    ) -> (Phase5Com1, Phase5ADecom1<P>, HomoELGamalProof<P>, DLogProof<P>) {
+        self.phase5a_broadcast_5b_zkproof_with_rng(session_id, index, &mut os_rng())
+    }
+
+    pub fn phase5a_broadcast_5b_zkproof_with_rng(
+        &self,
+        session_id: &[u8],
+        index: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Phase5Com1, Phase5ADecom1<P>, HomoELGamalProof<P>, DLogProof<P>) {
+        let blind_factor = BigInt::sample_with_rng(SECURITY, rng);
+        let g = P::generator();
+        let A_i = g.clone() * self.rho_i.clone();
+        let l_i_rho_i = self.l_i.clone() * self.rho_i.clone();
+        let B_i = g.clone() * l_i_rho_i;
+        let V_i = self.R.clone() * self.s_i.clone() + g.clone() * self.l_i.clone();
         let input_hash = HSha256::create_hash_from_ge(&[&V_i, &A_i, &B_i]).to_big_int();
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &input_hash,
@@ -499,8 +1013,14 @@ impl LocalSignature {
             D: V_i.clone(),
             E: B_i.clone(),
         };
-        let dlog_proof_rho = DLogProof::prove(&self.rho_i);
-        let proof = HomoELGamalProof::prove(&witness, &delta);
+        let mut transcript = Self::phase5_transcript(session_id, index);
+        let dlog_proof_rho = DLogProof::prove_with_transcript_and_rng(
+            &mut transcript.clone(),
+            &self.rho_i,
+            rng,
+        );
+        let proof =
+            HomoELGamalProof::prove_with_transcript_and_rng(&mut transcript, &witness, &delta, rng);
 
         (
             Phase5Com1 { com },
@@ -515,18 +1035,63 @@ impl LocalSignature {
         )
     }
 
+    /// Transcript of the phase 5B proofs of the signer holding key share `index`.
+    fn phase5_transcript(session_id: &[u8], index: usize) -> Transcript {
+        Transcript::for_party(b"gg18/sign/phase5", session_id, index as u64)
+    }
+
+    /// `indices[i]` is the key share index of the signer that sent `decom_vec[i]`, as passed
+    /// to its `phase5a_broadcast_5b_zkproof`.
+    #[allow(clippy::too_many_arguments)]
     pub fn phase5c(
         &self,
-        decom_vec: &Vec<Phase5ADecom1>,
+        session_id: &[u8],
+        indices: &[usize],
+        decom_vec: &Vec<Phase5ADecom1<P>>,
         com_vec: &Vec<Phase5Com1>,
-        elgamal_proofs: &Vec<HomoELGamalProof>,
-        dlog_proofs_rho: &[DLogProof],
-        v_i: &GE,
-        R: &GE,
-    ) -> Result<(Phase5Com2, Phase5DDecom2), Error> {
-        assert_eq!(decom_vec.len(), com_vec.len());
-
-        let g: GE = ECPoint::generator();
+        elgamal_proofs: &Vec<HomoELGamalProof<P>>,
+        dlog_proofs_rho: &[DLogProof<P>],
+        v_i: &P,
+        R: &P,
+    ) -> Result<(Phase5Com2, Phase5DDecom2<P>), Error> {
+        self.phase5c_with_rng(
+            session_id,
+            indices,
+            decom_vec,
+            com_vec,
+            elgamal_proofs,
+            dlog_proofs_rho,
+            v_i,
+            R,
+            &mut os_rng(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn phase5c_with_rng(
+        &self,
+        session_id: &[u8],
+        indices: &[usize],
+        decom_vec: &Vec<Phase5ADecom1<P>>,
+        com_vec: &Vec<Phase5Com1>,
+        elgamal_proofs: &Vec<HomoELGamalProof<P>>,
+        dlog_proofs_rho: &[DLogProof<P>],
+        v_i: &P,
+        R: &P,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Phase5Com2, Phase5DDecom2<P>), Error> {
+        if decom_vec.len() != com_vec.len()
+            || elgamal_proofs.len() != com_vec.len()
+            || indices.len() != com_vec.len()
+        {
+            return Err(InvalidCom);
+        }
+
+        let g = P::generator();
+        let mut transcripts = indices
+            .iter()
+            .map(|index| Self::phase5_transcript(session_id, *index))
+            .collect::<Vec<Transcript>>();
         let test_com_elgamal = (0..com_vec.len())
             .map(|i| {
                 let delta = HomoElGamalStatement {
@@ -547,35 +1112,32 @@ impl LocalSignature {
                     &input_hash,
                     &decom_vec[i].blind_factor,
                 ) == com_vec[i].com
-                    && elgamal_proofs[i].verify(&delta).is_ok()
-                    && DLogProof::verify(&dlog_proofs_rho[i]).is_ok()
+                    && elgamal_proofs[i]
+                        .verify_with_transcript(&mut transcripts[i].clone(), &delta)
+                        .is_ok()
             })
-            .all(|x| x == true);
-
-        let v_vec = (0..com_vec.len())
-            .map(|i| &decom_vec[i].V_i)
-            .collect::<Vec<&GE>>();
-        let a_vec = (0..com_vec.len())
-            .map(|i| &decom_vec[i].A_i)
-            .collect::<Vec<&GE>>();
-
-        let v = v_vec.iter().fold(v_i.clone(), |acc, x| acc + *x);
-        // V = -mG -ry - sum (vi)
-        let mut a_i_iter = a_vec.iter();
-        let head = a_i_iter.next().unwrap();
-        let tail = a_i_iter;
-        let a = tail.fold((*head).clone(), |acc, x| acc.add_point(&(*x).get_element()));
-
-        let r: FE = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()));
-        let yr = &self.y * &r;
-        let g: GE = ECPoint::generator();
-        let m_fe: FE = ECScalar::from(&self.m);
-        let gm = &g * &m_fe;
-        let v = v.sub_point(&gm.get_element()).sub_point(&yr.get_element());
-        let u_i = &v * &self.rho_i;
-        let t_i = &a * &self.l_i;
+            .all(|x| x == true)
+            && dlog_proofs_rho.len() == com_vec.len()
+            && DLogProof::batch_verify_with_transcripts_and_rng(
+                &mut transcripts,
+                dlog_proofs_rho,
+                rng,
+            )
+            .is_ok();
+
+        // V = -mG -ry + v_i + sum (V_j)
+        let v: P = decom_vec.iter().map(|decom| &decom.V_i).sum::<P>() + v_i.clone();
+        let a: P = decom_vec.iter().map(|decom| &decom.A_i).sum();
+
+        let r: P::Scalar = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&P::Scalar::q()));
+        let yr = self.y.clone() * r;
+        let m_fe: P::Scalar = message_to_scalar(&self.m);
+        let gm = g * m_fe;
+        let v = v - gm - yr;
+        let u_i = v * self.rho_i.clone();
+        let t_i = a * self.l_i.clone();
         let input_hash = HSha256::create_hash_from_ge(&[&u_i, &t_i]).to_big_int();
-        let blind_factor = BigInt::sample(SECURITY);
+        let blind_factor = BigInt::sample_with_rng(SECURITY, rng);
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &input_hash,
             &blind_factor,
@@ -598,10 +1160,10 @@ impl LocalSignature {
 
     pub fn phase5d(
         &self,
-        decom_vec2: &Vec<Phase5DDecom2>,
+        decom_vec2: &Vec<Phase5DDecom2<P>>,
         com_vec2: &Vec<Phase5Com2>,
-        decom_vec1: &Vec<Phase5ADecom1>,
-    ) -> Result<FE, Error> {
+        decom_vec1: &Vec<Phase5ADecom1<P>>,
+    ) -> Result<P::Scalar, Error> {
         assert_eq!(decom_vec2.len(), decom_vec1.len());
         assert_eq!(decom_vec2.len(), com_vec2.len());
 
@@ -617,24 +1179,11 @@ impl LocalSignature {
             })
             .all(|x| x == true);
 
-        let t_vec = (0..com_vec2.len())
-            .map(|i| &decom_vec2[i].t_i)
-            .collect::<Vec<&GE>>();
-        let u_vec = (0..com_vec2.len())
-            .map(|i| &decom_vec2[i].u_i)
-            .collect::<Vec<&GE>>();
-        let b_vec = (0..decom_vec1.len())
-            .map(|i| &decom_vec1[i].B_i)
-            .collect::<Vec<&GE>>();
-
-        let g: GE = ECPoint::generator();
-        let biased_sum_tb = t_vec
-            .iter()
-            .zip(b_vec)
-            .fold(g.clone(), |acc, x| acc + *x.0 + x.1);
-        let biased_sum_tb_minus_u = u_vec
-            .iter()
-            .fold(biased_sum_tb, |acc, x| acc.sub_point(&x.get_element()));
+        let g = P::generator();
+        let sum_t: P = decom_vec2.iter().map(|decom| &decom.t_i).sum();
+        let sum_b: P = decom_vec1.iter().map(|decom| &decom.B_i).sum();
+        let sum_u: P = decom_vec2.iter().map(|decom| &decom.u_i).sum();
+        let biased_sum_tb_minus_u = g.clone() + sum_t + sum_b - sum_u;
         match test_com {
             true => {
                 if g == biased_sum_tb_minus_u {
@@ -646,10 +1195,31 @@ impl LocalSignature {
             false => Err(InvalidCom),
         }
     }
-    pub fn output_signature(&self, s_vec: &Vec<FE>) -> Result<Signature, Error> {
-        let s = s_vec.iter().fold(self.s_i.clone(), |acc, x| acc + x);
-        let r: FE = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()));
-        let sig = Signature { r, s };
+    pub fn output_signature(&self, s_vec: &Vec<P::Scalar>) -> Result<Signature<P::Scalar>, Error> {
+        let mut s = s_vec.iter().fold(self.s_i.clone(), |acc, x| acc + x.clone());
+        let rx = self.R.x_coor().unwrap();
+        let q = P::Scalar::q();
+        let r: P::Scalar = ECScalar::from(&rx.mod_floor(&q));
+
+        /*
+         The recovery id lets a verifier pick R out of the (up to four) points whose
+         x coordinate reduces to r:
+         1. bit 0 is the parity of R.y
+         2. bit 1 is set if R.x overflowed the group order
+         Negating s to bring it into the lower half of the group negates R as well,
+         which flips the parity bit.
+        */
+        let mut recid = if self.R.y_coor().unwrap().is_odd() { 1 } else { 0 };
+        if rx >= q {
+            recid |= 2;
+        }
+        let s_bn = s.to_big_int();
+        let s_tag_bn = q - &s_bn;
+        if s_bn > s_tag_bn {
+            s = ECScalar::from(&s_tag_bn);
+            recid ^= 1;
+        }
+        let sig = Signature { r, s, recid };
         let ver = verify(&sig, &self.y, &self.m).is_ok();
         match ver {
             true => Ok(sig),
@@ -658,17 +1228,128 @@ impl LocalSignature {
     }
 }
 
-pub fn verify(sig: &Signature, y: &GE, message: &BigInt) -> Result<(), Error> {
+impl<S: ECScalar> Signature<S> {
+    /// ASN.1 DER encoding `SEQUENCE { INTEGER r, INTEGER s }`, as used by Bitcoin
+    /// and most X.509 / TLS stacks.
+    pub fn to_der(&self) -> Vec<u8> {
+        let r = der_integer(&self.r.to_big_int().to_bytes_be());
+        let s = der_integer(&self.s.to_big_int().to_bytes_be());
+        let mut der = Vec::with_capacity(2 + r.len() + s.len());
+        der.push(0x30);
+        der.push((r.len() + s.len()) as u8);
+        der.extend_from_slice(&r);
+        der.extend_from_slice(&s);
+        der
+    }
+
+    /// 64 byte compact encoding `r || s`, each big-endian and left padded to 32 bytes.
+    pub fn to_compact(&self) -> Vec<u8> {
+        let mut compact = fixed_bytes_be(&self.r.to_big_int(), 32);
+        compact.extend_from_slice(&fixed_bytes_be(&self.s.to_big_int(), 32));
+        compact
+    }
+
+    /// 65 byte encoding `r || s || v` with `v` the raw recovery id (0..=3).
+    /// Callers targeting Ethereum add 27 (or the EIP-155 offset) to the last byte.
+    pub fn to_rsv(&self) -> Vec<u8> {
+        let mut rsv = self.to_compact();
+        rsv.push(self.recid);
+        rsv
+    }
+}
+
+impl Signature<FE> {
+    pub fn to_libsecp256k1(&self) -> secp256k1::Signature {
+        let mut compact = [0u8; 64];
+        compact.copy_from_slice(&self.to_compact());
+        secp256k1::Signature::parse(&compact)
+    }
+}
+
+/// Recovers the public key that produced `sig` over `message`, `recid` selecting which of the
+/// candidate points sharing the x coordinate `r` was the nonce commitment R.
+pub fn recover_public_key(sig: &Signature, recid: u8, message: &BigInt) -> Result<GE, Error> {
+    let recovery_id = secp256k1::RecoveryId::parse(recid).map_err(|_| InvalidSig)?;
+    let pk = secp256k1::recover(
+        &to_libsecp256k1_message(message),
+        &sig.to_libsecp256k1(),
+        &recovery_id,
+    )
+    .map_err(|_| InvalidSig)?;
+    let serialized = pk.serialize();
+    Ok(ECPoint::from_coor(
+        &BigInt::from_bytes_be(&serialized[1..33]),
+        &BigInt::from_bytes_be(&serialized[33..65]),
+    ))
+}
+
+/// Same check as `verify`, delegated to libsecp256k1 so that threshold signatures can be
+/// cross-checked against an independent implementation.
+pub fn verify_with_libsecp256k1(sig: &Signature, y: &GE, message: &BigInt) -> Result<(), Error> {
+    match y.get_element() {
+        Some(pk)
+            if secp256k1::verify(&to_libsecp256k1_message(message), &sig.to_libsecp256k1(), &pk) =>
+        {
+            Ok(())
+        }
+        _ => Err(InvalidSig),
+    }
+}
+
+fn to_libsecp256k1_message(message: &BigInt) -> secp256k1::Message {
+    let mut bytes = [0u8; 32];
+    let m_fe: FE = message_to_scalar(message);
+    bytes.copy_from_slice(&fixed_bytes_be(&m_fe.to_big_int(), 32));
+    secp256k1::Message::parse(&bytes)
+}
+
+/// `message` is expected to come out of `bits2int` (see `message::MessageHashMode::hash`);
+/// integers wider than the group order go through `bits2int` again, as their big-endian bytes.
+fn message_to_scalar<S: ECScalar>(message: &BigInt) -> S {
+    ECScalar::from(&bits2int_for::<S>(&message.to_bytes_be()))
+}
+
+fn fixed_bytes_be(n: &BigInt, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    assert!(bytes.len() <= len);
+    let mut out = vec![0u8; len - bytes.len()];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+fn der_integer(bytes: &[u8]) -> Vec<u8> {
+    let bytes = match bytes.iter().position(|b| *b != 0) {
+        Some(first_non_zero) => &bytes[first_non_zero..],
+        // zero, including the empty encoding, is a single zero byte
+        None => &[0u8][..],
+    };
+    let mut out = vec![0x02];
+    if bytes[0] & 0x80 != 0 {
+        out.push(bytes.len() as u8 + 1);
+        out.push(0x00);
+    } else {
+        out.push(bytes.len() as u8);
+    }
+    out.extend_from_slice(bytes);
+    out
+}
+
+pub fn verify<P: ECPoint>(sig: &Signature<P::Scalar>, y: &P, message: &BigInt) -> Result<(), Error> {
+    let zero = P::Scalar::zero();
+    if sig.r == zero || sig.s == zero || y.is_identity() {
+        return Err(InvalidSig);
+    }
     let b = sig.s.invert();
-    let a: FE = ECScalar::from(message);
-    let u1 = a * &b;
-    let u2 = sig.r.clone() * &b;
-
-    let g: GE = ECPoint::generator();
-    let gu1 = &g * &u1;
-    let yu2 = y * &u2;
-    // can be faster using shamir trick
-    if sig.r.clone() == ECScalar::from(&(gu1 + yu2).x_coor().unwrap().mod_floor(&FE::q())) {
+    let a: P::Scalar = message_to_scalar(message);
+    let u1 = a * b.clone();
+    let u2 = sig.r.clone() * b;
+
+    let gu1_yu2 = P::multi_scalar_mul(&[P::generator(), y.clone()], &[u1, u2]);
+    let rx = match gu1_yu2.x_coor() {
+        Some(x) => x.mod_floor(&P::Scalar::q()),
+        None => return Err(InvalidSig),
+    };
+    if sig.r.clone() == ECScalar::from(&rx) {
         Ok(())
     } else {
         Err(InvalidSig)
@@ -10,13 +10,10 @@
 //! 2) A non-interactive version is implemented, with challenge `e` computed via Fiat-Shamir.
 
 use crate::curv::arithmetic::traits::*;
-use crate::curv::elliptic::curves::secp256_k1::{Secp256k1Point as Point, Secp256k1Scalar as Scalar};
-use crate::curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::curv::elliptic::curves::secp256_k1::FE;
+use crate::curv::elliptic::curves::traits::ECScalar;
 use crate::curv::arithmetic::num_bigint::BigInt;
-use sha2::Sha256;
-
-use crate::sha2::Digest;
-use crate::curv::cryptographic_primitives::hashing::ext::DigestExt;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
 
 use num_traits::pow::Pow;
 use crate::num_traits::One;
@@ -25,8 +22,10 @@ use crate::num_integer::Integer;
 use crate::paillier::{EncryptionKey, Randomness};
 use crate::paillier::zkproofs::DLogStatement;
 
+use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::marker::PhantomData;
 use zeroize::Zeroize;
 
 /// Represents the first round of the interactive version of the proof
@@ -48,15 +47,16 @@ impl AliceZkpRound1 {
         dlog_statement: &DLogStatement,
         a: &BigInt,
         q: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
     ) -> Self {
         let h1 = &dlog_statement.g;
         let h2 = &dlog_statement.ni;
         let N_tilde = &dlog_statement.N;
 //        let q_pow_3 = BigInt::pow(q.clone(), 3);
-        let alpha = BigInt::sample_below(&q.pow(3u32));
-        let beta = BigInt::from_paillier_key(alice_ek);
-        let gamma = BigInt::sample_below(&(q.pow(3u32) * N_tilde));
-        let ro = BigInt::sample_below(&(q * N_tilde));
+        let alpha = BigInt::sample_below_with_rng(&q.pow(3u32), rng);
+        let beta = BigInt::from_paillier_key_with_rng(alice_ek, rng);
+        let gamma = BigInt::sample_below_with_rng(&(q.pow(3u32) * N_tilde), rng);
+        let ro = BigInt::sample_below_with_rng(&(q * N_tilde), rng);
         let z = (BigInt::mod_pow(h1, a, N_tilde) * BigInt::mod_pow(h2, &ro, N_tilde)) % N_tilde;
         let u = ((alpha.borrow() * &alice_ek.n + 1u32)
             * BigInt::mod_pow(&beta, &alice_ek.n, &alice_ek.nn))
@@ -98,32 +98,75 @@ impl AliceZkpRound2 {
     }
 }
 
-/// Alice's proof
+/// Alice's proof that her plaintext is small compared to the order of the curve of `S`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AliceProof {
+#[serde(bound = "")]
+pub struct AliceProof<S: ECScalar = FE> {
     z: BigInt,
     e: BigInt,
     s: BigInt,
     s1: BigInt,
     s2: BigInt,
+    #[serde(skip)]
+    _curve: PhantomData<S>,
 }
 
-impl AliceProof {
+const ALICE_PROOF_LABEL: &[u8] = b"emerald-city/mta-alice-range-proof";
+/// Bit length of the challenge `e`.
+const ALICE_CHALLENGE_BITS: usize = 256;
+
+impl<S: ECScalar> AliceProof<S> {
+    fn challenge(
+        transcript: &mut Transcript,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
+        cipher: &BigInt,
+        z: &BigInt,
+        u: &BigInt,
+        w: &BigInt,
+    ) -> BigInt {
+        transcript.append_message(b"proof", ALICE_PROOF_LABEL);
+        transcript.append_bigint(b"N", &alice_ek.n);
+        transcript.append_bigint(b"N_tilde", &dlog_statement.N);
+        transcript.append_bigint(b"h1", &dlog_statement.g);
+        transcript.append_bigint(b"h2", &dlog_statement.ni);
+        transcript.append_bigint(b"cipher", cipher);
+        transcript.append_bigint(b"z", z);
+        transcript.append_bigint(b"u", u);
+        transcript.append_bigint(b"w", w);
+        transcript.challenge_bigint(b"challenge", ALICE_CHALLENGE_BITS)
+    }
+
     /// verify Alice's proof using the proof and public keys
     pub fn verify(
         &self,
         cipher: &BigInt,
         alice_ek: &EncryptionKey,
         dlog_statement: &DLogStatement,
+    ) -> bool {
+        self.verify_with_transcript(
+            &mut Transcript::new(ALICE_PROOF_LABEL),
+            cipher,
+            alice_ek,
+            dlog_statement,
+        )
+    }
+
+    /// verify Alice's proof against the transcript it was generated with
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        cipher: &BigInt,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
     ) -> bool {
         let N = &alice_ek.n;
         let NN = &alice_ek.nn;
         let N_tilde = &dlog_statement.N;
         let h1 = &dlog_statement.g;
         let h2 = &dlog_statement.ni;
-        let Gen = alice_ek.n.borrow() + 1u32;
 
-        if self.s1 > Scalar::group_order().pow(3u32) {
+        if self.s1 > S::q().pow(3u32) {
             return false;
         }
 
@@ -139,14 +182,7 @@ impl AliceProof {
 
         let u = (gs1 * BigInt::mod_pow(&self.s, N, NN) * cipher_e_inv) % NN;
 
-        let e = Sha256::new()
-            .chain_bigint(N)
-            .chain_bigint(&Gen)
-            .chain_bigint(cipher)
-            .chain_bigint(&self.z)
-            .chain_bigint(&u)
-            .chain_bigint(&w)
-            .result_bigint();
+        let e = Self::challenge(transcript, alice_ek, dlog_statement, cipher, &self.z, &u, &w);
         if e != self.e {
             return false;
         }
@@ -154,8 +190,7 @@ impl AliceProof {
         true
     }
     /// Create the proof using Alice's Paillier private keys and public ZKP setup.
-    /// Requires randomness used for encrypting Alice's secret a.
-    /// It is assumed that secp256k1 curve is used.
+    /// Requires randomness used for encrypting Alice's secret a, a scalar of the curve of `S`.
     pub fn generate(
         a: &BigInt,
         cipher: &BigInt,
@@ -163,22 +198,70 @@ impl AliceProof {
         dlog_statement: &DLogStatement,
         r: &BigInt,
     ) -> Self {
-        let round1 = AliceZkpRound1::from(
+        Self::generate_with_rng(a, cipher, alice_ek, dlog_statement, r, &mut os_rng())
+    }
+
+    /// As `generate`, drawing the proof's randomness from `rng`.
+    pub fn generate_with_rng(
+        a: &BigInt,
+        cipher: &BigInt,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
+        r: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        Self::generate_with_transcript_and_rng(
+            &mut Transcript::new(ALICE_PROOF_LABEL),
+            a,
+            cipher,
             alice_ek,
             dlog_statement,
+            r,
+            rng,
+        )
+    }
+
+    /// As `generate`, with the challenge drawn from `transcript`, which should be bound to
+    /// the session and to the parties running the MtA.
+    pub fn generate_with_transcript(
+        transcript: &mut Transcript,
+        a: &BigInt,
+        cipher: &BigInt,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
+        r: &BigInt,
+    ) -> Self {
+        Self::generate_with_transcript_and_rng(
+            transcript,
             a,
-            Scalar::group_order(),
-        );
+            cipher,
+            alice_ek,
+            dlog_statement,
+            r,
+            &mut os_rng(),
+        )
+    }
 
-        let Gen = alice_ek.n.borrow() + 1u32;
-        let e = Sha256::new()
-            .chain_bigint(&alice_ek.n)
-            .chain_bigint(&Gen)
-            .chain_bigint(cipher)
-            .chain_bigint(&round1.z)
-            .chain_bigint(&round1.u)
-            .chain_bigint(&round1.w)
-            .result_bigint();
+    pub fn generate_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        a: &BigInt,
+        cipher: &BigInt,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
+        r: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let round1 = AliceZkpRound1::from(alice_ek, dlog_statement, a, &S::q(), rng);
+
+        let e = Self::challenge(
+            transcript,
+            alice_ek,
+            dlog_statement,
+            cipher,
+            &round1.z,
+            &round1.u,
+            &round1.w,
+        );
 
         let round2 = AliceZkpRound2::from(alice_ek, &round1, &e, a, r);
 
@@ -188,6 +271,7 @@ impl AliceProof {
             s: round2.s,
             s1: round2.s1,
             s2: round2.s2,
+            _curve: PhantomData,
         }
     }
 }
@@ -537,21 +621,31 @@ impl AliceProof {
 pub trait SampleFromMultiplicativeGroup {
     fn from_modulo(N: &BigInt) -> BigInt;
     fn from_paillier_key(ek: &EncryptionKey) -> BigInt;
+    fn from_modulo_with_rng(N: &BigInt, rng: &mut (impl RngCore + CryptoRng)) -> BigInt;
+    fn from_paillier_key_with_rng(ek: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> BigInt;
 }
 
 impl SampleFromMultiplicativeGroup for BigInt {
     fn from_modulo(N: &BigInt) -> BigInt {
+        Self::from_modulo_with_rng(N, &mut os_rng())
+    }
+
+    fn from_paillier_key(ek: &EncryptionKey) -> BigInt {
+        Self::from_modulo(ek.n.borrow())
+    }
+
+    fn from_modulo_with_rng(N: &BigInt, rng: &mut (impl RngCore + CryptoRng)) -> BigInt {
         let One = BigInt::one();
         loop {
-            let r = Self::sample_below(N);
+            let r = Self::sample_below_with_rng(N, rng);
             if r.gcd(N) == One {
                 return r;
             }
         }
     }
 
-    fn from_paillier_key(ek: &EncryptionKey) -> BigInt {
-        Self::from_modulo(ek.n.borrow())
+    fn from_paillier_key_with_rng(ek: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> BigInt {
+        Self::from_modulo_with_rng(ek.n.borrow(), rng)
     }
 }
 
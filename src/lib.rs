@@ -34,6 +34,9 @@ pub mod curv;
 pub mod gg_2018;
 pub mod paillier;
 
+pub mod bitcoin;
+pub mod ethereum;
+
 #[macro_use]
 pub mod common;
 
@@ -45,6 +48,7 @@ pub enum Error {
     InvalidSS,
     InvalidCom,
     InvalidSig,
+    InvalidMessage,
 }
 #[derive(Copy, PartialEq, Eq, Clone, Debug)]
 pub enum ErrorKey {
@@ -53,6 +57,7 @@ pub enum ErrorKey {
 
 pub enum ErrorSS {
     VerifyShareError,
+    DecryptShareError,
 }
 
 #[cfg(target_arch = "wasm32")]
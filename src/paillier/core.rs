@@ -11,6 +11,7 @@ use crate::paillier::{
     DecryptionKey, EncryptionKey, Keypair, MinimalDecryptionKey, MinimalEncryptionKey, Paillier,
     RawCiphertext, RawPlaintext,
 };
+use rand::{CryptoRng, RngCore};
 use serde::*;
 
 impl Keypair {
@@ -102,8 +103,34 @@ impl<'e> From<MinimalDecryptionKey> for DecryptionKey {
     fn from(dk: MinimalDecryptionKey) -> Self {
         let p = dk.p;
         let q = dk.q;
-
-        DecryptionKey { p, q }
+        let n = &p * &q;
+        let pp = &p * &p;
+        let qq = &q * &q;
+        let pinv = BigInt::mod_inv(&p, &q);
+        let ppinv = BigInt::mod_inv(&pp, &qq);
+        let pminusone = &p - BigInt::one();
+        let qminusone = &q - BigInt::one();
+        let hp = h(&p, &pp, &n);
+        let hq = h(&q, &qq, &n);
+        let phi = &pminusone * &qminusone;
+        let dn = BigInt::mod_inv(&n, &phi);
+        let (dp, dq) = crt_decompose(dn, &pminusone, &qminusone);
+
+        DecryptionKey {
+            p,
+            q,
+            n,
+            pp,
+            qq,
+            pinv,
+            ppinv,
+            pminusone,
+            qminusone,
+            hp,
+            hq,
+            dp,
+            dq,
+        }
     }
 }
 
@@ -136,7 +163,11 @@ pub struct PrecomputedRandomness(BigInt);
 
 impl Randomness {
     pub fn sample(ek: &EncryptionKey) -> Randomness {
-        Randomness(BigInt::sample_below(&ek.n))
+        Randomness::sample_with_rng(ek, &mut os_rng())
+    }
+
+    pub fn sample_with_rng(ek: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> Randomness {
+        Randomness(BigInt::sample_below_with_rng(&ek.n, rng))
     }
 }
 
@@ -189,8 +220,12 @@ impl<'b> From<RawCiphertext<'b>> for BigInt {
 }
 
 impl<'m, 'd> Encrypt<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
-    fn encrypt(ek: &EncryptionKey, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
-        let r = Randomness::sample(&ek);
+    fn encrypt_with_rng(
+        ek: &EncryptionKey,
+        m: RawPlaintext<'m>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RawCiphertext<'d> {
+        let r = Randomness::sample_with_rng(ek, rng);
         let rn = BigInt::mod_pow(&r.0, &ek.n, &ek.nn);
         let gm: BigInt = (m.0.borrow() as &BigInt * &ek.n + BigInt::from(1 as u16)) % &ek.nn;
         let c = (gm * rn) % &ek.nn;
@@ -234,24 +269,24 @@ impl<'m, 'r, 'd>
 }
 
 impl<'m, 'd> Encrypt<DecryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
-    fn encrypt(dk: &DecryptionKey, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq);
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
-
-        let rp = BigInt::sample_below(&dk.p);
-        let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-        let gmp = (BigInt::from(1 as u16) + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-        let cp = (gmp * rnp) % &dk_pp;
-
-        let rq = BigInt::sample_below(&dk.q);
-        let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-        let gmq = (BigInt::from(1 as u16) + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-        let cq = (gmq * rnq) % &dk_qq;
-
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
+    fn encrypt_with_rng(
+        dk: &DecryptionKey,
+        m: RawPlaintext<'m>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RawCiphertext<'d> {
+        let (mp, mq) = crt_decompose(m.0.borrow(), &dk.pp, &dk.qq);
+
+        let rp = BigInt::sample_below_with_rng(&dk.p, rng);
+        let rnp = BigInt::mod_pow(&rp, &dk.n, &dk.pp);
+        let gmp = (BigInt::from(1 as u16) + mp * &dk.n) % &dk.pp; // TODO[Morten] maybe there's more to get here
+        let cp = (gmp * rnp) % &dk.pp;
+
+        let rq = BigInt::sample_below_with_rng(&dk.q, rng);
+        let rnq = BigInt::mod_pow(&rq, &dk.n, &dk.qq);
+        let gmq = (BigInt::from(1 as u16) + mq * &dk.n) % &dk.qq; // TODO[Morten] maybe there's more to get here
+        let cq = (gmq * rnq) % &dk.qq;
+
+        let c = crt_recombine(cp, cq, &dk.pp, &dk.qq, &dk.ppinv);
         RawCiphertext(Cow::Owned(c))
     }
 }
@@ -265,22 +300,18 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         r: &'r Randomness,
     ) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq);
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
-        let (rp, rq) = crt_decompose(&r.0, &dk_pp, &dk_qq);
-
-        let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-        let gmp = (BigInt::from(1 as u16) + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-        let cp = (gmp * rnp) % &dk_pp;
-
-        let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-        let gmq = (BigInt::from(1 as u16) + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-        let cq = (gmq * rnq) % &dk_qq;
-
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
+        let (mp, mq) = crt_decompose(m.0.borrow(), &dk.pp, &dk.qq);
+        let (rp, rq) = crt_decompose(&r.0, &dk.pp, &dk.qq);
+
+        let rnp = BigInt::mod_pow(&rp, &dk.n, &dk.pp);
+        let gmp = (BigInt::from(1 as u16) + mp * &dk.n) % &dk.pp; // TODO[Morten] maybe there's more to get here
+        let cp = (gmp * rnp) % &dk.pp;
+
+        let rnq = BigInt::mod_pow(&rq, &dk.n, &dk.qq);
+        let gmq = (BigInt::from(1 as u16) + mq * &dk.n) % &dk.qq; // TODO[Morten] maybe there's more to get here
+        let cq = (gmq * rnq) % &dk.qq;
+
+        let c = crt_recombine(cp, cq, &dk.pp, &dk.qq, &dk.ppinv);
         RawCiphertext(Cow::Owned(c))
     }
 }
@@ -298,9 +329,9 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         rn: &'r PrecomputedRandomness,
     ) -> RawCiphertext<'d> {
-        let dk_n = &dk.q * &dk.p;
-        let dk_nn = &dk_n * &dk_n;
-        let gm = (BigInt::from(1 as u16) + m.0.borrow() as &BigInt * &dk_n) % &dk_nn;
+        let dk_n = &dk.n;
+        let dk_nn = dk_n * dk_n;
+        let gm = (BigInt::from(1 as u16) + m.0.borrow() as &BigInt * dk_n) % &dk_nn;
         let c = (gm * &rn.0) % &dk_nn;
         RawCiphertext(Cow::Owned(c))
     }
@@ -316,8 +347,12 @@ impl<'ek, 'r> PrecomputeRandomness<&'ek EncryptionKey, &'r BigInt, PrecomputedRa
 }
 
 impl<'c, 'd> Rerandomize<EncryptionKey, RawCiphertext<'c>, RawCiphertext<'d>> for Paillier {
-    fn rerandomize(ek: &EncryptionKey, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
-        let r = BigInt::sample_below(&ek.n);
+    fn rerandomize_with_rng(
+        ek: &EncryptionKey,
+        c: RawCiphertext<'c>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RawCiphertext<'d> {
+        let r = BigInt::sample_below_with_rng(&ek.n, rng);
         let rn = BigInt::mod_pow(&r, &ek.n, &ek.nn);
         let d = (c.0.borrow() as &BigInt * rn) % &ek.nn;
         RawCiphertext(Cow::Owned(d))
@@ -338,29 +373,21 @@ impl<'c, 'm> Decrypt<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>> for Pai
 /// Efficient decryption using CRT based on [Paillier99, section 7](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.112.4035&rep=rep1&type=pdf)
 impl<'c, 'm> Decrypt<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
     fn decrypt(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> RawPlaintext<'m> {
-        let dk_qq = &dk.q * &dk.q;
-        let dk_pp = &dk.p * &dk.p;
-        let dk_n = &dk.p * &dk.q;
-        let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q);
-        let dk_qminusone = &dk.q - BigInt::one();
-        let dk_pminusone = &dk.p - BigInt::one();
-        let dk_hp = h(&dk.p, &dk_pp, &dk_n);
-        let dk_hq = h(&dk.q, &dk_qq, &dk_n);
-        let (cp, cq) = crt_decompose(c.0.borrow(), &dk_pp, &dk_qq);
+        let (cp, cq) = crt_decompose(c.0.borrow(), &dk.pp, &dk.qq);
         // decrypt in parallel with respectively p and q
 
         // process using p
-        let dp = BigInt::mod_pow(&cp, &dk_pminusone, &dk_pp);
+        let dp = BigInt::mod_pow(&cp, &dk.pminusone, &dk.pp);
         let lp = l(&dp, &dk.p);
-        let mp = (&lp * &dk_hp) % &dk.p;
+        let mp = (&lp * &dk.hp) % &dk.p;
 
         // process using q
-        let dq = BigInt::mod_pow(&cq, &dk_qminusone, &dk_qq);
+        let dq = BigInt::mod_pow(&cq, &dk.qminusone, &dk.qq);
         let lq = l(&dq, &dk.q);
-        let mq = (&lq * &dk_hq) % &dk.q;
+        let mq = (&lq * &dk.hq) % &dk.q;
 
         // perform CRT
-        let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk_pinv);
+        let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk.pinv);
         RawPlaintext(Cow::Owned(m))
     }
 }
@@ -373,11 +400,11 @@ impl<'c, 'm> Open<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, Randomness
 
 impl<'c, 'm> Open<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
     fn open(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
-        let dk_n = &dk.p * &dk.q;
-        let dk_nn = &dk_n * &dk_n;
+        let dk_n = &dk.n;
+        let dk_nn = dk_n * dk_n;
 
         let m = Self::decrypt(dk, c);
-        let gminv = (dk_nn.clone() + BigInt::one() - (m.0.borrow() as &BigInt) * &dk_n) % &dk_nn;
+        let gminv = (dk_nn.clone() + BigInt::one() - (m.0.borrow() as &BigInt) * dk_n) % &dk_nn;
         let rn = (c.0.borrow() as &BigInt * gminv) % &dk_nn;
         let r = extract_nroot(dk, &rn);
         (m, Randomness(r))
@@ -491,21 +518,12 @@ where
 
 /// Extract randomness component of a zero ciphertext.
 pub fn extract_nroot(dk: &DecryptionKey, z: &BigInt) -> BigInt {
-    let dk_n = &dk.p * &dk.q;
-
-    let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q);
-    let dk_qminusone = &dk.q - BigInt::one();
-    let dk_pminusone = &dk.p - BigInt::one();
-
-    let dk_phi = &dk_pminusone * &dk_qminusone;
-    let dk_dn = BigInt::mod_inv(&dk_n, &dk_phi);
-    let (dk_dp, dk_dq) = crt_decompose(dk_dn, &dk_pminusone, &dk_qminusone);
     let (zp, zq) = crt_decompose(z, &dk.p, &dk.q);
 
-    let rp = BigInt::mod_pow(&zp, &dk_dp, &dk.p);
-    let rq = BigInt::mod_pow(&zq, &dk_dq, &dk.q);
+    let rp = BigInt::mod_pow(&zp, &dk.dp, &dk.p);
+    let rq = BigInt::mod_pow(&zq, &dk.dq, &dk.q);
 
-    let r = crt_recombine(rp, rq, &dk.p, &dk.q, &dk_pinv);
+    let r = crt_recombine(rp, rq, &dk.p, &dk.q, &dk.pinv);
     r
 }
 
@@ -513,18 +531,13 @@ pub fn extract_nroot(dk: &DecryptionKey, z: &BigInt) -> BigInt {
 mod tests {
 
     use super::*;
+    use crate::paillier::test_keypair;
 
     extern crate serde_json;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
 
-    fn test_keypair() -> Keypair {
-        let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
-        let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
-        Keypair { p, q }
-    }
-
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_correct_encryption_decryption() {
@@ -602,6 +615,32 @@ mod tests {
         let dk_serialized = serde_json::to_string(&dk).unwrap();
         let dk_recovered: DecryptionKey = serde_json::from_str(&dk_serialized).unwrap();
         assert_eq!(dk, dk_recovered);
+
+        // only the minimal form is serialized
+        let minimal: MinimalDecryptionKey = serde_json::from_str(&dk_serialized).unwrap();
+        assert_eq!(serde_json::to_string(&minimal).unwrap(), dk_serialized);
+        assert_eq!(DecryptionKey::from(minimal), dk);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_decryption_key_precomputation() {
+        let (ek, dk) = test_keypair().keys();
+        assert_eq!(dk.n, ek.n);
+        assert_eq!((&dk.p * &dk.pinv) % &dk.q, BigInt::one());
+        assert_eq!((&dk.pp * &dk.ppinv) % &dk.qq, BigInt::one());
+        let phi = &dk.pminusone * &dk.qminusone;
+        let dn = BigInt::mod_inv(&dk.n, &phi);
+        assert_eq!(dk.dp, &dn % &dk.pminusone);
+        assert_eq!(dk.dq, &dn % &dk.qminusone);
+
+        // encryption under the decryption key agrees with the public one
+        let m = RawPlaintext::from(BigInt::from(10 as u16));
+        let r = Randomness::sample(&ek);
+        let c_dk = Paillier::encrypt_with_chosen_randomness(&dk, m.clone(), &r);
+        let c_ek = Paillier::encrypt_with_chosen_randomness(&ek, m.clone(), &r);
+        assert_eq!(c_dk, c_ek);
+        assert_eq!(Paillier::decrypt(&dk, c_dk), m);
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -0,0 +1,394 @@
+//! Damgård–Jurik generalisation of Paillier, with plaintexts in Z_{N^s} and ciphertexts in
+//! Z*_{N^(s+1)}, from [DJ01](https://www.brics.dk/RS/00/45/BRICS-RS-00-45.pdf).
+//!
+//! A plaintext m is encrypted as (1 + N)^m r^{N^s} mod N^(s+1), so s = 1 is Paillier itself and
+//! a larger s grows the plaintext space by a factor N per step while the ciphertext expansion
+//! drops to (s + 1) / s. The schemes share the `Paillier` entry point and the traits, the key
+//! types selecting which one is used, and the encodings carry over through `PlaintextModulus`.
+
+use std::borrow::{Borrow, Cow};
+use std::num::NonZeroU32;
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::*;
+use crate::paillier::encoding::PlaintextModulus;
+use crate::paillier::traits::*;
+use crate::paillier::{Keypair, Paillier, RawCiphertext, RawPlaintext};
+use num_integer::Integer;
+use num_traits::{One, Pow, Zero};
+use rand::{CryptoRng, RngCore};
+use serde::*;
+
+/// Public Damgård–Jurik key with no precomputed values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MinimalDJEncryptionKey {
+    pub n: BigInt,
+    pub s: NonZeroU32,
+}
+
+/// Private Damgård–Jurik key with no precomputed values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MinimalDJDecryptionKey {
+    pub p: BigInt,
+    pub q: BigInt,
+    pub s: NonZeroU32,
+}
+
+/// Public Damgård–Jurik key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DJEncryptionKey {
+    pub n: BigInt,
+    pub s: NonZeroU32,
+    pub ns: BigInt,  // N^s, the plaintext modulus
+    pub ns1: BigInt, // N^(s+1), the ciphertext modulus
+}
+
+/// Private Damgård–Jurik key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DJDecryptionKey {
+    pub p: BigInt,
+    pub q: BigInt,
+    pub n: BigInt,
+    pub s: NonZeroU32,
+    pub ns: BigInt,
+    pub ns1: BigInt,
+    pub lambda: BigInt,     // lcm(p - 1, q - 1)
+    pub lambda_inv: BigInt, // lambda^-1 mod N^s
+}
+
+impl Keypair {
+    /// Damgård–Jurik keys for plaintexts mod N^s.
+    pub fn dj_keys(&self, s: NonZeroU32) -> (DJEncryptionKey, DJDecryptionKey) {
+        (
+            DJEncryptionKey::from(MinimalDJEncryptionKey {
+                n: &self.p * &self.q,
+                s,
+            }),
+            DJDecryptionKey::from(MinimalDJDecryptionKey {
+                p: self.p.clone(),
+                q: self.q.clone(),
+                s,
+            }),
+        )
+    }
+}
+
+impl From<MinimalDJEncryptionKey> for DJEncryptionKey {
+    fn from(ek: MinimalDJEncryptionKey) -> Self {
+        let ns = Pow::pow(&ek.n, ek.s.get());
+        let ns1 = &ns * &ek.n;
+        DJEncryptionKey {
+            n: ek.n,
+            s: ek.s,
+            ns,
+            ns1,
+        }
+    }
+}
+
+impl<'e> From<&'e DJEncryptionKey> for MinimalDJEncryptionKey {
+    fn from(ek: &'e DJEncryptionKey) -> Self {
+        MinimalDJEncryptionKey {
+            n: ek.n.clone(),
+            s: ek.s,
+        }
+    }
+}
+
+impl From<MinimalDJDecryptionKey> for DJDecryptionKey {
+    fn from(dk: MinimalDJDecryptionKey) -> Self {
+        let DJEncryptionKey { n, s, ns, ns1 } = DJEncryptionKey::from(MinimalDJEncryptionKey {
+            n: &dk.p * &dk.q,
+            s: dk.s,
+        });
+        let lambda = (&dk.p - BigInt::one()).lcm(&(&dk.q - BigInt::one()));
+        let lambda_inv = BigInt::mod_inv(&lambda, &ns);
+        DJDecryptionKey {
+            p: dk.p,
+            q: dk.q,
+            n,
+            s,
+            ns,
+            ns1,
+            lambda,
+            lambda_inv,
+        }
+    }
+}
+
+impl<'d> From<&'d DJDecryptionKey> for MinimalDJDecryptionKey {
+    fn from(dk: &'d DJDecryptionKey) -> Self {
+        MinimalDJDecryptionKey {
+            p: dk.p.clone(),
+            q: dk.q.clone(),
+            s: dk.s,
+        }
+    }
+}
+
+impl<'d> From<&'d DJDecryptionKey> for DJEncryptionKey {
+    fn from(dk: &'d DJDecryptionKey) -> Self {
+        DJEncryptionKey {
+            n: dk.n.clone(),
+            s: dk.s,
+            ns: dk.ns.clone(),
+            ns1: dk.ns1.clone(),
+        }
+    }
+}
+
+impl Serialize for DJEncryptionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MinimalDJEncryptionKey::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DJEncryptionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let minimal = MinimalDJEncryptionKey::deserialize(deserializer)?;
+        Ok(DJEncryptionKey::from(minimal))
+    }
+}
+
+impl Serialize for DJDecryptionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MinimalDJDecryptionKey::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DJDecryptionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let minimal = MinimalDJDecryptionKey::deserialize(deserializer)?;
+        Ok(DJDecryptionKey::from(minimal))
+    }
+}
+
+impl PlaintextModulus for DJEncryptionKey {
+    fn plaintext_modulus(&self) -> &BigInt {
+        &self.ns
+    }
+}
+
+impl PlaintextModulus for DJDecryptionKey {
+    fn plaintext_modulus(&self) -> &BigInt {
+        &self.ns
+    }
+}
+
+/// (1 + N)^m mod N^(s+1).
+fn gm(ek: &DJEncryptionKey, m: &BigInt) -> BigInt {
+    BigInt::mod_pow(&(&ek.n + BigInt::one()), &(m % &ek.ns), &ek.ns1)
+}
+
+impl<'m, 'd> Encrypt<DJEncryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
+    fn encrypt_with_rng(
+        ek: &DJEncryptionKey,
+        m: RawPlaintext<'m>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RawCiphertext<'d> {
+        let r = BigInt::sample_below_with_rng(&ek.n, rng);
+        let rns = BigInt::mod_pow(&r, &ek.ns, &ek.ns1);
+        let c = BigInt::mod_mul(&gm(ek, m.0.borrow()), &rns, &ek.ns1);
+        RawCiphertext(Cow::Owned(c))
+    }
+}
+
+impl<'c, 'd> Rerandomize<DJEncryptionKey, RawCiphertext<'c>, RawCiphertext<'d>> for Paillier {
+    fn rerandomize_with_rng(
+        ek: &DJEncryptionKey,
+        c: RawCiphertext<'c>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RawCiphertext<'d> {
+        let r = BigInt::sample_below_with_rng(&ek.n, rng);
+        let rns = BigInt::mod_pow(&r, &ek.ns, &ek.ns1);
+        let d = BigInt::mod_mul(c.0.borrow(), &rns, &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+/// Recovers i from (1 + N)^i mod N^(s+1) one power of N at a time, as in [DJ01, theorem 1].
+fn dlog(dk: &DJDecryptionKey, a: &BigInt) -> BigInt {
+    let mut i = BigInt::zero();
+    let mut nj = BigInt::one();
+    for j in 1..=dk.s.get() {
+        nj *= &dk.n;
+        let nj1 = &nj * &dk.n;
+        // L(a mod N^(j+1)) = i_j + sum_{k >= 2} binom(i_j, k) N^(k-1) mod N^j, where i_j = i mod N^j
+        let mut t1 = ((a % &nj1) - BigInt::one()) / &dk.n;
+        let mut t2 = i.clone();
+        let mut i_k = i.clone();
+        let mut k_factorial = BigInt::one();
+        let mut nk = BigInt::one(); // N^(k-1)
+        for k in 2..=j {
+            i_k = BigInt::mod_sub(&i_k, &BigInt::one(), &nj);
+            t2 = BigInt::mod_mul(&t2, &i_k, &nj);
+            k_factorial *= BigInt::from(k);
+            nk *= &dk.n;
+            let term = BigInt::mod_mul(
+                &BigInt::mod_mul(&t2, &nk, &nj),
+                &BigInt::mod_inv(&k_factorial, &nj),
+                &nj,
+            );
+            t1 = BigInt::mod_sub(&t1, &term, &nj);
+        }
+        i = t1;
+    }
+    i
+}
+
+impl<'c, 'm> Decrypt<DJDecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
+    fn decrypt(dk: &DJDecryptionKey, c: RawCiphertext<'c>) -> RawPlaintext<'m> {
+        Self::decrypt(dk, &c)
+    }
+}
+
+impl<'c, 'm> Decrypt<DJDecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
+    fn decrypt(dk: &DJDecryptionKey, c: &'c RawCiphertext<'c>) -> RawPlaintext<'m> {
+        // c^lambda = (1 + N)^(m lambda mod N^s), the randomness having order dividing lambda N^s
+        let a = BigInt::mod_pow(c.0.borrow(), &dk.lambda, &dk.ns1);
+        let m = BigInt::mod_mul(&dlog(dk, &a), &dk.lambda_inv, &dk.ns);
+        RawPlaintext(Cow::Owned(m))
+    }
+}
+
+impl<'c1, 'c2, 'd> Add<DJEncryptionKey, RawCiphertext<'c1>, RawCiphertext<'c2>, RawCiphertext<'d>>
+    for Paillier
+{
+    fn add(
+        ek: &DJEncryptionKey,
+        c1: RawCiphertext<'c1>,
+        c2: RawCiphertext<'c2>,
+    ) -> RawCiphertext<'d> {
+        let d = BigInt::mod_mul(c1.0.borrow(), c2.0.borrow(), &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+impl<'c, 'm, 'd> Add<DJEncryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>
+    for Paillier
+{
+    fn add(ek: &DJEncryptionKey, c: RawCiphertext<'c>, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
+        let d = BigInt::mod_mul(c.0.borrow(), &gm(ek, m.0.borrow()), &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+impl<'c, 'm, 'd> Add<DJEncryptionKey, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>
+    for Paillier
+{
+    fn add(ek: &DJEncryptionKey, m: RawPlaintext<'m>, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
+        let d = BigInt::mod_mul(&gm(ek, m.0.borrow()), c.0.borrow(), &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+impl<'c, 'm, 'd> Mul<DJEncryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>
+    for Paillier
+{
+    fn mul(ek: &DJEncryptionKey, c: RawCiphertext<'c>, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
+        let d = BigInt::mod_pow(c.0.borrow(), m.0.borrow(), &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+impl<'c, 'm, 'd> Mul<DJEncryptionKey, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>
+    for Paillier
+{
+    fn mul(ek: &DJEncryptionKey, m: RawPlaintext<'m>, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
+        let d = BigInt::mod_pow(c.0.borrow(), m.0.borrow(), &ek.ns1);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::paillier::test_keypair;
+    use crate::paillier::encoding::EncodedCiphertext;
+    use crate::paillier::DecryptionKey;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn test_dj_keys(s: u32) -> (DJEncryptionKey, DJDecryptionKey) {
+        test_keypair().dj_keys(NonZeroU32::new(s).unwrap())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_s_one_is_paillier() {
+        let keypair = test_keypair();
+        let (ek, _) = keypair.dj_keys(NonZeroU32::new(1).unwrap());
+        let dk = DecryptionKey::from(&keypair);
+
+        let m = BigInt::from(10u32);
+        let c: RawCiphertext = Paillier::encrypt(&ek, RawPlaintext::from(&m));
+        let p: RawPlaintext = Paillier::decrypt(&dk, c);
+        assert_eq!(p, RawPlaintext::from(m));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_encrypt_decrypt() {
+        for s in 2..=3 {
+            let (ek, dk) = test_dj_keys(s);
+            // a plaintext much larger than N
+            let m = &ek.ns - BigInt::from(12345u32);
+            let c: RawCiphertext = Paillier::encrypt(&ek, RawPlaintext::from(&m));
+            assert!(c.0.bits() > ek.n.bits() * s as usize);
+            let c = Paillier::rerandomize(&ek, c);
+            let p: RawPlaintext = Paillier::decrypt(&dk, c);
+            assert_eq!(p, RawPlaintext::from(m));
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_add_mul() {
+        let (ek, dk) = test_dj_keys(2);
+        let m1 = &ek.n * BigInt::from(3u32);
+        let m2 = &ek.n + BigInt::from(7u32);
+        let c1: RawCiphertext = Paillier::encrypt(&ek, RawPlaintext::from(&m1));
+        let c2: RawCiphertext = Paillier::encrypt(&ek, RawPlaintext::from(&m2));
+
+        let c: RawCiphertext = Paillier::add(&ek, c1.clone(), c2);
+        let p: RawPlaintext = Paillier::decrypt(&dk, c);
+        assert_eq!(p, RawPlaintext::from(&m1 + &m2));
+
+        let c: RawCiphertext = Paillier::add(&ek, c1.clone(), RawPlaintext::from(&m2));
+        let p: RawPlaintext = Paillier::decrypt(&dk, c);
+        assert_eq!(p, RawPlaintext::from(&m1 + &m2));
+
+        let c: RawCiphertext = Paillier::mul(&ek, RawPlaintext::from(&ek.n), c1);
+        let p: RawPlaintext = Paillier::decrypt(&dk, c);
+        assert_eq!(p, RawPlaintext::from((&m1 * &ek.n) % &ek.ns));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_signed_encoding() {
+        let (ek, dk) = test_dj_keys(2);
+        let c: EncodedCiphertext<i64> = Paillier::encrypt(&ek, -5i64);
+        let c = Paillier::mul(&ek, &c, 3i64);
+        assert_eq!(Paillier::decrypt(&dk, &c), Ok(-15));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_serialize_keys() {
+        let (ek, dk) = test_dj_keys(2);
+        let ek_json = serde_json::to_string(&ek).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DJEncryptionKey>(&ek_json).unwrap(),
+            ek
+        );
+        let dk_json = serde_json::to_string(&dk).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DJDecryptionKey>(&dk_json).unwrap(),
+            dk
+        );
+        let zero = ek_json.replace("\"s\":2", "\"s\":0");
+        assert!(serde_json::from_str::<DJEncryptionKey>(&zero).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Fixed-point decimals, carried as signed integers scaled by `10^DECIMALS`.
+//!
+//! The scale is part of the type, so only values of the same scale can be added together.
+//! Multiplication is by integer plaintexts only: a product of two scaled values would need a
+//! division to get back to the scale, which the scheme cannot do homomorphically.
+
+use crate::paillier::traits::Add;
+use crate::paillier::traits::Decrypt;
+use crate::paillier::traits::Encrypt;
+use crate::paillier::traits::Mul;
+use crate::paillier::traits::Rerandomize;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use super::signed::{decode, encode};
+use super::{EncodedCiphertext, PlaintextModulus, PlaintextOverflow};
+use crate::paillier::{Paillier, RawCiphertext, RawPlaintext};
+use rand::{CryptoRng, RngCore};
+
+/// Decimal number `mantissa / 10^DECIMALS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedPoint<const DECIMALS: u32> {
+    pub mantissa: i64,
+}
+
+impl<const DECIMALS: u32> FixedPoint<DECIMALS> {
+    pub const SCALE: i64 = 10i64.pow(DECIMALS);
+
+    pub fn from_mantissa(mantissa: i64) -> Self {
+        FixedPoint { mantissa }
+    }
+
+    /// Rounds `x` to the nearest multiple of `10^-DECIMALS`, or returns `None` if the result
+    /// does not fit.
+    pub fn from_f64(x: f64) -> Option<Self> {
+        let mantissa = (x * Self::SCALE as f64).round();
+        // i64::MAX as f64 rounds up to 2^63, which is out of range
+        if mantissa >= i64::MIN as f64 && mantissa < i64::MAX as f64 {
+            Some(FixedPoint {
+                mantissa: mantissa as i64,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / Self::SCALE as f64
+    }
+}
+
+impl<EK, const D: u32> Encrypt<EK, FixedPoint<D>, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'p, 'c> Self: Encrypt<EK, RawPlaintext<'p>, RawCiphertext<'c>>,
+    EK: PlaintextModulus,
+{
+    fn encrypt_with_rng(
+        ek: &EK,
+        m: FixedPoint<D>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<FixedPoint<D>> {
+        let m = encode(m.mantissa, ek.plaintext_modulus());
+        let c = Self::encrypt_with_rng(ek, RawPlaintext::from(m), rng);
+        EncodedCiphertext {
+            raw: c.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C, const D: u32> Rerandomize<EK, C, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'c, 'd> Self: Rerandomize<EK, RawCiphertext<'c>, RawCiphertext<'d>>,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn rerandomize_with_rng(
+        ek: &EK,
+        c: C,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::rerandomize_with_rng(ek, RawCiphertext::from(&c.borrow().raw), rng);
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<DK, C, const D: u32> Decrypt<DK, C, Result<FixedPoint<D>, PlaintextOverflow>> for Paillier
+where
+    for<'c, 'p> Self: Decrypt<DK, RawCiphertext<'c>, RawPlaintext<'p>>,
+    DK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn decrypt(dk: &DK, c: C) -> Result<FixedPoint<D>, PlaintextOverflow> {
+        let m = Self::decrypt(dk, RawCiphertext::from(&c.borrow().raw));
+        decode(&m.into(), dk.plaintext_modulus()).map(FixedPoint::from_mantissa)
+    }
+}
+
+impl<EK, C1, C2, const D: u32> Add<EK, C1, C2, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'c1, 'c2, 'd> Self: Add<EK, RawCiphertext<'c1>, RawCiphertext<'c2>, RawCiphertext<'d>>,
+    C1: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+    C2: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn add(ek: &EK, c1: C1, c2: C2) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c1.borrow().raw),
+            RawCiphertext::from(&c2.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C, const D: u32> Add<EK, C, FixedPoint<D>, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'c, 'p, 'd> Self: Add<EK, RawCiphertext<'c>, RawPlaintext<'p>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn add(ek: &EK, c: C, p: FixedPoint<D>) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c.borrow().raw),
+            RawPlaintext::from(encode(p.mantissa, ek.plaintext_modulus())),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C, const D: u32> Add<EK, FixedPoint<D>, C, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Add<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn add(ek: &EK, m: FixedPoint<D>, c: C) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::add(
+            ek,
+            RawPlaintext::from(encode(m.mantissa, ek.plaintext_modulus())),
+            RawCiphertext::from(&c.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C, const D: u32> Mul<EK, C, i64, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'c, 'm, 'd> Self: Mul<EK, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn mul(ek: &EK, c: C, m: i64) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::mul(
+            ek,
+            RawCiphertext::from(&c.borrow().raw),
+            RawPlaintext::from(encode(m, ek.plaintext_modulus())),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C, const D: u32> Mul<EK, i64, C, EncodedCiphertext<FixedPoint<D>>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Mul<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<FixedPoint<D>>>,
+{
+    fn mul(ek: &EK, m: i64, c: C) -> EncodedCiphertext<FixedPoint<D>> {
+        let d = Self::mul(
+            ek,
+            RawPlaintext::from(encode(m, ek.plaintext_modulus())),
+            RawCiphertext::from(&c.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::paillier::test_keypair;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    type Cents = FixedPoint<2>;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(Cents::from_f64(12.346), Some(Cents::from_mantissa(1235)));
+        assert_eq!(Cents::from_f64(-0.5), Some(Cents::from_mantissa(-50)));
+        assert_eq!(Cents::from_mantissa(-1999).to_f64(), -19.99);
+        assert_eq!(Cents::from_f64(1e17), None);
+        assert_eq!(Cents::from_f64(f64::NAN), None);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_fixed_point_balances() {
+        let (ek, dk) = test_keypair().keys();
+
+        let balances = [12.5, -3.75, 0.01];
+        let total = balances
+            .iter()
+            .map(|b| Paillier::encrypt(&ek, Cents::from_f64(*b).unwrap()))
+            .fold(Paillier::encrypt(&ek, Cents::from_mantissa(0)), |acc, c| {
+                Paillier::add(&ek, &acc, &c)
+            });
+        let m: Result<Cents, _> = Paillier::decrypt(&dk, &total);
+        assert_eq!(m, Ok(Cents::from_mantissa(876)));
+
+        let c = Paillier::add(&ek, &total, Cents::from_f64(-10.0).unwrap());
+        let c = Paillier::mul(&ek, &c, -3i64);
+        let m: Result<Cents, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m.unwrap().to_f64(), 3.72);
+    }
+}
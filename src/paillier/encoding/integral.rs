@@ -8,17 +8,46 @@ use crate::paillier::traits::Rerandomize;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 
-use super::{pack, unpack, EncodedCiphertext};
+use super::{
+    pack, unpack, ComponentMismatch, EncodedCiphertext, PlaintextModulus, PlaintextOverflow,
+    TooManyComponents,
+};
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::*;
 use crate::paillier::{Paillier, RawCiphertext, RawPlaintext};
+use rand::{CryptoRng, RngCore};
+
+/// Bits given to each `u64` of a packed vector.
+///
+/// The 64 bits above the value leave room for a product with a `u64`, or for the sum of up to
+/// 2^64 components, without carrying into the next component. A component that no longer
+/// fits in a `u64` is detected on decryption.
+pub const COMPONENT_BITSIZE: usize = 128;
+
+/// Fails if a vector of `components` packed components does not fit below the plaintext
+/// modulus of `ek`.
+fn check_fits<EK: PlaintextModulus>(ek: &EK, components: usize) -> Result<(), TooManyComponents> {
+    let capacity = (ek.plaintext_modulus().bits() - 1) / COMPONENT_BITSIZE;
+    if components <= capacity {
+        Ok(())
+    } else {
+        Err(TooManyComponents {
+            components,
+            capacity,
+        })
+    }
+}
 
 impl<EK> Encrypt<EK, u64, EncodedCiphertext<u64>> for Paillier
 where
     for<'p, 'c> Self: Encrypt<EK, RawPlaintext<'p>, RawCiphertext<'c>>,
 {
-    fn encrypt(ek: &EK, m: u64) -> EncodedCiphertext<u64> {
-        let c = Self::encrypt(ek, RawPlaintext::from(BigInt::from(m)));
+    fn encrypt_with_rng(
+        ek: &EK,
+        m: u64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<u64> {
+        let c = Self::encrypt_with_rng(ek, RawPlaintext::from(BigInt::from(m)), rng);
         EncodedCiphertext {
             raw: c.into(),
             components: 1,
@@ -27,18 +56,26 @@ where
     }
 }
 
-impl<'m, EK> Encrypt<EK, &'m [u64], EncodedCiphertext<Vec<u64>>> for Paillier
+impl<'m, EK> Encrypt<EK, &'m [u64], Result<EncodedCiphertext<Vec<u64>>, TooManyComponents>>
+    for Paillier
 where
     for<'p, 'c> Self: Encrypt<EK, RawPlaintext<'p>, RawCiphertext<'c>>,
+    EK: PlaintextModulus,
 {
-    fn encrypt(ek: &EK, m: &'m [u64]) -> EncodedCiphertext<Vec<u64>> {
-        let m_packed = pack(m, 64);
-        let c = Self::encrypt(ek, RawPlaintext::from(m_packed));
-        EncodedCiphertext {
+    /// Fails if `m` has more components than fit below the plaintext modulus.
+    fn encrypt_with_rng(
+        ek: &EK,
+        m: &'m [u64],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<EncodedCiphertext<Vec<u64>>, TooManyComponents> {
+        check_fits(ek, m.len())?;
+        let m_packed = pack(m, COMPONENT_BITSIZE);
+        let c = Self::encrypt_with_rng(ek, RawPlaintext::from(m_packed), rng);
+        Ok(EncodedCiphertext {
             raw: c.into(),
             components: m.len(),
             _phantom: PhantomData,
-        }
+        })
     }
 }
 
@@ -47,8 +84,12 @@ where
     for<'c, 'd> Self: Rerandomize<EK, RawCiphertext<'c>, RawCiphertext<'d>>,
     C: Borrow<EncodedCiphertext<u64>>,
 {
-    fn rerandomize(ek: &EK, c: C) -> EncodedCiphertext<u64> {
-        let d = Self::rerandomize(ek, RawCiphertext::from(&c.borrow().raw));
+    fn rerandomize_with_rng(
+        ek: &EK,
+        c: C,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<u64> {
+        let d = Self::rerandomize_with_rng(ek, RawCiphertext::from(&c.borrow().raw), rng);
         EncodedCiphertext {
             raw: d.into(),
             components: c.borrow().components,
@@ -62,8 +103,12 @@ where
     for<'c, 'd> Self: Rerandomize<EK, RawCiphertext<'c>, RawCiphertext<'d>>,
     C: Borrow<EncodedCiphertext<Vec<u64>>>,
 {
-    fn rerandomize(ek: &EK, c: C) -> EncodedCiphertext<Vec<u64>> {
-        let d = Self::rerandomize(ek, RawCiphertext::from(&c.borrow().raw));
+    fn rerandomize_with_rng(
+        ek: &EK,
+        c: C,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<Vec<u64>> {
+        let d = Self::rerandomize_with_rng(ek, RawCiphertext::from(&c.borrow().raw), rng);
         EncodedCiphertext {
             raw: d.into(),
             components: c.borrow().components,
@@ -83,14 +128,23 @@ where
     }
 }
 
-impl<DK, C> Decrypt<DK, C, Vec<u64>> for Paillier
+impl<DK, C> Decrypt<DK, C, Result<Vec<u64>, PlaintextOverflow>> for Paillier
 where
     for<'c, 'p> Self: Decrypt<DK, RawCiphertext<'c>, RawPlaintext<'p>>,
     C: Borrow<EncodedCiphertext<Vec<u64>>>,
 {
-    fn decrypt(dk: &DK, c: C) -> Vec<u64> {
+    /// Fails if a component went past `u64::MAX`, e.g. after too many additions.
+    fn decrypt(dk: &DK, c: C) -> Result<Vec<u64>, PlaintextOverflow> {
         let m = Self::decrypt(dk, RawCiphertext::from(&c.borrow().raw));
-        unpack(m.into(), 64, c.borrow().components)
+        unpack(m.into(), COMPONENT_BITSIZE, c.borrow().components)
+    }
+}
+
+fn check_components(left: usize, right: usize) -> Result<(), ComponentMismatch> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(ComponentMismatch { left, right })
     }
 }
 
@@ -114,22 +168,54 @@ where
     }
 }
 
-impl<EK, C1, C2> Add<EK, C1, C2, EncodedCiphertext<Vec<u64>>> for Paillier
+impl<EK, C1, C2> Add<EK, C1, C2, Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch>>
+    for Paillier
 where
     for<'c1, 'c2, 'd> Self: Add<EK, RawCiphertext<'c1>, RawCiphertext<'c2>, RawCiphertext<'d>>,
     C1: Borrow<EncodedCiphertext<Vec<u64>>>,
     C2: Borrow<EncodedCiphertext<Vec<u64>>>,
 {
-    fn add(ek: &EK, c1: C1, c2: C2) -> EncodedCiphertext<Vec<u64>> {
+    fn add(ek: &EK, c1: C1, c2: C2) -> Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch> {
         let c1 = c1.borrow();
         let c2 = c2.borrow();
-        assert_eq!(c1.components, c2.components); // TODO[Morten] expand one if needed
+        check_components(c1.components, c2.components)?; // TODO[Morten] expand one if needed
 
         let d = Self::add(
             ek,
             RawCiphertext::from(&c1.raw),
             RawCiphertext::from(&c2.raw),
         );
+        Ok(EncodedCiphertext {
+            raw: d.into(),
+            components: c1.components,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Spreads an encrypted scalar over `components` slots, by raising it to the packed all-ones
+/// vector.
+fn broadcast<'d, EK>(ek: &EK, c: &EncodedCiphertext<u64>, components: usize) -> RawCiphertext<'d>
+where
+    for<'c, 'm, 'e> Paillier: Mul<EK, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'e>>,
+{
+    let ones = pack(&vec![1u64; components], COMPONENT_BITSIZE);
+    Paillier::mul(ek, RawCiphertext::from(&c.raw), RawPlaintext::from(ones))
+}
+
+impl<'c2, EK, C1> Add<EK, C1, &'c2 EncodedCiphertext<u64>, EncodedCiphertext<Vec<u64>>> for Paillier
+where
+    for<'c, 'd, 'e> Self: Add<EK, RawCiphertext<'c>, RawCiphertext<'d>, RawCiphertext<'e>>,
+    for<'c, 'm, 'd> Self: Mul<EK, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>,
+    C1: Borrow<EncodedCiphertext<Vec<u64>>>,
+{
+    fn add(ek: &EK, c1: C1, c2: &'c2 EncodedCiphertext<u64>) -> EncodedCiphertext<Vec<u64>> {
+        let c1 = c1.borrow();
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c1.raw),
+            broadcast(ek, c2, c1.components),
+        );
         EncodedCiphertext {
             raw: d.into(),
             components: c1.components,
@@ -138,21 +224,26 @@ where
     }
 }
 
-// impl<'c1, 'c2, EK> Add<EK, &'c1 Ciphertext<Vec<u64>>, &'c2 Ciphertext<u64>, Ciphertext<Vec<u64>>> for Paillier
-// where Self: Add<EK, &'c1 BigInt, &'c2 BigInt, BigInt>
-// {
-//     fn add(ek: &EK, c1: &'c1 Ciphertext<Vec<u64>>, c2: &'c2 Ciphertext<u64>) -> Ciphertext<Vec<u64>> {
-//         unimplemented!()
-//     }
-// }
-
-// impl<'c1, 'c2, EK> Add<EK, &'c1 Ciphertext<u64>, &'c2 Ciphertext<Vec<u64>>, Ciphertext<Vec<u64>>> for Paillier
-// where Self: Add<EK, &'c1 BigInt, &'c2 BigInt, BigInt>
-// {
-//     fn add(ek: &EK, c1: &'c1 Ciphertext<u64>, c2: &'c2 Ciphertext<Vec<u64>>) -> Ciphertext<Vec<u64>> {
-//         unimplemented!()
-//     }
-// }
+impl<'c1, EK, C2> Add<EK, &'c1 EncodedCiphertext<u64>, C2, EncodedCiphertext<Vec<u64>>> for Paillier
+where
+    for<'c, 'd, 'e> Self: Add<EK, RawCiphertext<'c>, RawCiphertext<'d>, RawCiphertext<'e>>,
+    for<'c, 'm, 'd> Self: Mul<EK, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>,
+    C2: Borrow<EncodedCiphertext<Vec<u64>>>,
+{
+    fn add(ek: &EK, c1: &'c1 EncodedCiphertext<u64>, c2: C2) -> EncodedCiphertext<Vec<u64>> {
+        let c2 = c2.borrow();
+        let d = Self::add(
+            ek,
+            broadcast(ek, c1, c2.components),
+            RawCiphertext::from(&c2.raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: c2.components,
+            _phantom: PhantomData,
+        }
+    }
+}
 
 impl<EK, C> Add<EK, C, u64, EncodedCiphertext<u64>> for Paillier
 where
@@ -185,7 +276,7 @@ where
         let d = Self::add(
             ek,
             RawCiphertext::from(&c.raw),
-            RawPlaintext::from(pack(&m2_expanded, 64)),
+            RawPlaintext::from(pack(&m2_expanded, COMPONENT_BITSIZE)),
         );
         EncodedCiphertext {
             raw: d.into(),
@@ -195,15 +286,32 @@ where
     }
 }
 
-// impl<'m2, EK, C1> Add<EK, C1, &'m2 [u64], Ciphertext<Vec<u64>>> for Paillier
-// where
-//     for<'c1> Self: Add<EK, &'c1 BigInt, BigInt, BigInt>,
-//     C1: Borrow<Ciphertext<Vec<u64>>>,
-// {
-//     fn add(ek: &EK, c1: C1, m2: &'m2 [u64]) -> Ciphertext<Vec<u64>> {
-//         unimplemented!()
-//     }
-// }
+impl<'m2, EK, C1>
+    Add<EK, C1, &'m2 [u64], Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch>> for Paillier
+where
+    for<'c, 'p, 'd> Self: Add<EK, RawCiphertext<'c>, RawPlaintext<'p>, RawCiphertext<'d>>,
+    C1: Borrow<EncodedCiphertext<Vec<u64>>>,
+{
+    fn add(
+        ek: &EK,
+        c1: C1,
+        m2: &'m2 [u64],
+    ) -> Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch> {
+        let c1 = c1.borrow();
+        check_components(c1.components, m2.len())?;
+
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c1.raw),
+            RawPlaintext::from(pack(m2, COMPONENT_BITSIZE)),
+        );
+        Ok(EncodedCiphertext {
+            raw: d.into(),
+            components: c1.components,
+            _phantom: PhantomData,
+        })
+    }
+}
 
 impl<EK, C2> Add<EK, u64, C2, EncodedCiphertext<u64>> for Paillier
 where
@@ -224,25 +332,54 @@ where
     }
 }
 
-// impl<EK, C2> Add<EK, u64, C2, Ciphertext<Vec<u64>>> for Paillier
-// where
-//     for<'c2> Self: Add<EK, BigInt, &'c2 BigInt, BigInt>,
-//     C2: Borrow<Ciphertext<Vec<u64>>>,
-// {
-//     fn add(ek: &EK, m1: u64, c2: C2) -> Ciphertext<Vec<u64>> {
-//         unimplemented!()
-//     }
-// }
-
-// impl<'m1, EK, C2> Add<EK, &'m1 [u64], C2, Ciphertext<Vec<u64>>> for Paillier
-// where
-//     for<'c2> Self: Add<EK, BigInt, &'c2 BigInt, BigInt>,
-//     C2: Borrow<Ciphertext<Vec<u64>>>,
-// {
-//     fn add(ek: &EK, m1: &'m1 [u64], c2: C2) -> Ciphertext<Vec<u64>> {
-//         unimplemented!()
-//     }
-// }
+impl<EK, C2> Add<EK, u64, C2, EncodedCiphertext<Vec<u64>>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Add<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    C2: Borrow<EncodedCiphertext<Vec<u64>>>,
+{
+    fn add(ek: &EK, m1: u64, c2: C2) -> EncodedCiphertext<Vec<u64>> {
+        let c2 = c2.borrow();
+
+        let m1_expanded = vec![m1; c2.components];
+        let d = Self::add(
+            ek,
+            RawPlaintext::from(pack(&m1_expanded, COMPONENT_BITSIZE)),
+            RawCiphertext::from(&c2.raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: c2.components,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'m1, EK, C2>
+    Add<EK, &'m1 [u64], C2, Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Add<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    C2: Borrow<EncodedCiphertext<Vec<u64>>>,
+{
+    fn add(
+        ek: &EK,
+        m1: &'m1 [u64],
+        c2: C2,
+    ) -> Result<EncodedCiphertext<Vec<u64>>, ComponentMismatch> {
+        let c2 = c2.borrow();
+        check_components(m1.len(), c2.components)?;
+
+        let d = Self::add(
+            ek,
+            RawPlaintext::from(pack(m1, COMPONENT_BITSIZE)),
+            RawCiphertext::from(&c2.raw),
+        );
+        Ok(EncodedCiphertext {
+            raw: d.into(),
+            components: c2.components,
+            _phantom: PhantomData,
+        })
+    }
+}
 
 impl<EK, C> Mul<EK, C, u64, EncodedCiphertext<u64>> for Paillier
 where
@@ -324,23 +461,17 @@ where
 mod tests {
 
     use super::*;
-    use crate::paillier::Keypair;
+    use crate::paillier::test_keypair;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
 
-    fn test_keypair() -> Keypair {
-        let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
-        let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
-        Keypair { p, q }
-    }
-
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_scalar_encrypt_decrypt() {
         let (ek, dk) = test_keypair().keys();
 
-        let m = 10;
+        let m = 10u64;
         let c = Paillier::encrypt(&ek, m);
 
         let recovered_m = Paillier::decrypt(&dk, &c);
@@ -353,10 +484,10 @@ mod tests {
         let (ek, dk) = test_keypair().keys();
 
         let m = vec![1, 2, 3];
-        let c = Paillier::encrypt(&ek, &*m);
-        let recovered_m = Paillier::decrypt(&dk, &c);
+        let c = Paillier::encrypt(&ek, &*m).unwrap();
+        let recovered_m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
 
-        assert_eq!(recovered_m, m);
+        assert_eq!(recovered_m, Ok(m));
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -364,8 +495,8 @@ mod tests {
     fn test_scalar_add_plaintext_scalar() {
         let (ek, dk) = test_keypair().keys();
 
-        let c1 = Paillier::encrypt(&ek, 10);
-        let m2 = 20;
+        let c1 = Paillier::encrypt(&ek, 10u64);
+        let m2 = 20u64;
 
         let c = Paillier::add(&ek, &c1, m2);
         let m = Paillier::decrypt(&dk, &c);
@@ -377,8 +508,8 @@ mod tests {
     fn test_scalar_add_ciphertext_scalar() {
         let (ek, dk) = test_keypair().keys();
 
-        let c1 = Paillier::encrypt(&ek, 10);
-        let c2 = Paillier::encrypt(&ek, 20);
+        let c1 = Paillier::encrypt(&ek, 10u64);
+        let c2 = Paillier::encrypt(&ek, 20u64);
 
         let c = Paillier::add(&ek, &c1, &c2);
         let m = Paillier::decrypt(&dk, &c);
@@ -391,50 +522,118 @@ mod tests {
         let (ek, dk) = test_keypair().keys();
 
         let m1 = vec![1, 2, 3];
-        let c1 = Paillier::encrypt(&ek, &*m1);
+        let c1 = Paillier::encrypt(&ek, &*m1).unwrap();
         let m2 = vec![3, 2, 1];
-        let c2 = Paillier::encrypt(&ek, &*m2);
+        let c2 = Paillier::encrypt(&ek, &*m2).unwrap();
+
+        let c = Paillier::add(&ek, &c1, &c2).unwrap();
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![4, 4, 4]));
+
+        let c3 = Paillier::encrypt(&ek, &[1, 2][..]).unwrap();
+        let d: Result<EncodedCiphertext<Vec<u64>>, _> = Paillier::add(&ek, &c1, &c3);
+        assert_eq!(d, Err(ComponentMismatch { left: 3, right: 2 }));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_add_vector_scalar() {
+        let (ek, dk) = test_keypair().keys();
+
+        let m1 = vec![1, 2, 3];
+        let c1 = Paillier::encrypt(&ek, &*m1).unwrap();
+        let m2 = 3u64;
+        let c2 = Paillier::encrypt(&ek, m2);
 
         let c = Paillier::add(&ek, &c1, &c2);
-        let m: Vec<_> = Paillier::decrypt(&dk, &c);
-        assert_eq!(m, vec![4, 4, 4]);
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![4, 5, 6]));
     }
 
-    // #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)] #[test]
-    // fn test_add_vector_scalar() {
-    //     let (ek, dk) = test_keypair().keys();
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_add_scalar_vector() {
+        let (ek, dk) = test_keypair().keys();
+
+        let m1 = vec![1, 2, 3];
+        let c1 = Paillier::encrypt(&ek, &*m1).unwrap();
+        let m2 = 3u64;
+        let c2 = Paillier::encrypt(&ek, m2);
+
+        let c = Paillier::add(&ek, &c2, &c1);
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![4, 5, 6]));
+    }
 
-    //     let m1 = vec![1, 2, 3];
-    //     let c1 = Paillier::encrypt(&ek, &*m1);
-    //     let m2 = 3;
-    //     let c2 = Paillier::encrypt(&ek, m2);
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_vector_add_plaintext() {
+        let (ek, dk) = test_keypair().keys();
 
-    //     let c = Paillier::add(&ek, &c1, &c2);
-    //     let m: Vec<_> = Paillier::decrypt(&dk, &c);
-    //     assert_eq!(m, vec![2, 4, 6]);
-    // }
+        let c1 = Paillier::encrypt(&ek, &[1, 2, 3][..]).unwrap();
 
-    // #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)] #[test]
-    // fn test_add_scalar_vector() {
-    //     let (ek, dk) = test_keypair().keys();
+        let c = Paillier::add(&ek, &c1, &[10, 20, 30][..]).unwrap();
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![11, 22, 33]));
 
-    //     let m1 = vec![1, 2, 3];
-    //     let c1 = Paillier::encrypt(&ek, &*m1);
-    //     let m2 = 3;
-    //     let c2 = Paillier::encrypt(&ek, m2);
+        let c = Paillier::add(&ek, &[10, 20, 30][..], &c1).unwrap();
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![11, 22, 33]));
 
-    //     let c = Paillier::add(&ek, &c1, &c2);
-    //     let m: Vec<_> = Paillier::decrypt(&dk, &c);
-    //     assert_eq!(m, vec![2, 4, 6]);
-    // }
+        let c = Paillier::add(&ek, 7u64, &c1);
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![8, 9, 10]));
+
+        let c = Paillier::encrypt(&ek, &[u64::MAX, 1][..]).unwrap();
+        let d = Paillier::add(&ek, &c, &[0, 5][..]).unwrap();
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &d);
+        assert_eq!(m, Ok(vec![u64::MAX, 6]));
+
+        let d: Result<EncodedCiphertext<Vec<u64>>, _> = Paillier::add(&ek, &c1, &[1, 2][..]);
+        assert_eq!(d, Err(ComponentMismatch { left: 3, right: 2 }));
+        let d: Result<EncodedCiphertext<Vec<u64>>, _> = Paillier::add(&ek, &[1][..], &c1);
+        assert_eq!(d, Err(ComponentMismatch { left: 1, right: 3 }));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_vector_component_overflow() {
+        let (ek, dk) = test_keypair().keys();
+
+        // the carry stays in the headroom of the first component instead of reaching the second
+        let c = Paillier::encrypt(&ek, &[u64::MAX, 1][..]).unwrap();
+        let d = Paillier::add(&ek, &c, &c).unwrap();
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &d);
+        assert_eq!(m, Err(PlaintextOverflow));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_vector_too_long_for_modulus() {
+        let (ek, dk) = test_keypair().keys();
+        let m = vec![1u64; 15];
+        let c = Paillier::encrypt(&ek, &*m).unwrap();
+        let recovered_m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(recovered_m, Ok(m));
+
+        let m = vec![1u64; 16];
+        let c: Result<EncodedCiphertext<Vec<u64>>, _> = Paillier::encrypt(&ek, &*m);
+        assert_eq!(
+            c,
+            Err(TooManyComponents {
+                components: 16,
+                capacity: 15
+            })
+        );
+    }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[test]
     fn test_scalar_mul_plaintext_scalar() {
         let (ek, dk) = test_keypair().keys();
 
-        let c = Paillier::encrypt(&ek, 10);
-        let d = Paillier::mul(&ek, &c, 20);
+        let c = Paillier::encrypt(&ek, 10u64);
+        let d = Paillier::mul(&ek, &c, 20u64);
         let m = Paillier::decrypt(&dk, &d);
         assert_eq!(m, 200);
     }
@@ -445,12 +644,12 @@ mod tests {
         let (ek, dk) = test_keypair().keys();
 
         let m1 = vec![1, 2, 3];
-        let c1 = Paillier::encrypt(&ek, &*m1);
+        let c1 = Paillier::encrypt(&ek, &*m1).unwrap();
         let m2 = 4;
 
         let c = Paillier::mul(&ek, &c1, m2);
-        let m: Vec<_> = Paillier::decrypt(&dk, &c);
-        assert_eq!(m, vec![4, 8, 12]);
+        let m: Result<Vec<u64>, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Ok(vec![4, 8, 12]));
     }
 
 }
@@ -1,11 +1,86 @@
 //! Various coding schemes to be used in conjunction with the core Paillier encryption scheme.
 
+use std::error::Error;
+use std::fmt;
 use std::marker::PhantomData;
 
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::ConvertFrom;
+use crate::paillier::{DecryptionKey, EncryptionKey};
+pub mod fixed_point;
 pub mod integral;
-use num_traits::One;
+pub mod signed;
+use num_traits::{One, Zero};
+
+/// Decrypted plaintext that does not fit the range of the encoding, e.g. after a
+/// homomorphic sum went past `i64::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaintextOverflow;
+
+impl fmt::Display for PlaintextOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "decrypted plaintext is out of the range of its encoding")
+    }
+}
+
+impl Error for PlaintextOverflow {}
+
+/// Homomorphic operation on two packed vectors, or a packed vector and a plaintext vector,
+/// whose numbers of components differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl fmt::Display for ComponentMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "packed vectors have {} and {} components",
+            self.left, self.right
+        )
+    }
+}
+
+impl Error for ComponentMismatch {}
+
+/// Vector with more components than can be packed below the plaintext modulus of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyComponents {
+    pub components: usize,
+    pub capacity: usize,
+}
+
+impl fmt::Display for TooManyComponents {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} packed components do not fit in a plaintext space holding {}",
+            self.components, self.capacity
+        )
+    }
+}
+
+impl Error for TooManyComponents {}
+
+/// Keys exposing the modulus of the plaintext space, which encodings of negative values
+/// wrap around.
+pub trait PlaintextModulus {
+    fn plaintext_modulus(&self) -> &BigInt;
+}
+
+impl PlaintextModulus for EncryptionKey {
+    fn plaintext_modulus(&self) -> &BigInt {
+        &self.n
+    }
+}
+
+impl PlaintextModulus for DecryptionKey {
+    fn plaintext_modulus(&self) -> &BigInt {
+        &self.n
+    }
+}
 
 /// Encrypted message with type information.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -30,11 +105,13 @@ where
     packed
 }
 
+/// Splits `packed_components` back into `component_count` components, failing if one of them
+/// does not fit in a `T` or if the packed value has bits above the last component.
 fn unpack<T>(
     mut packed_components: BigInt,
     component_bitsize: usize,
     component_count: usize,
-) -> Vec<T>
+) -> Result<Vec<T>, PlaintextOverflow>
 where
     T: ConvertFrom<BigInt>,
 {
@@ -42,12 +119,18 @@ where
     let mut components: Vec<T> = vec![];
     for _ in 0..component_count {
         let raw_component = &packed_components % &mask; // TODO replace with bitwise AND
+        if raw_component.bits() > 8 * std::mem::size_of::<T>() {
+            return Err(PlaintextOverflow);
+        }
         let component = T::_from(&raw_component);
         components.push(component);
         packed_components = &packed_components >> component_bitsize;
     }
+    if !packed_components.is_zero() {
+        return Err(PlaintextOverflow);
+    }
     components.reverse();
-    components
+    Ok(components)
 }
 
 #[cfg(test)]
@@ -70,6 +153,11 @@ fn test_pack() {
             + BigInt::from(3 as u32) * (BigInt::from(1 as u32) << 0 * component_bitsize)
     );
 
-    let unpacked: Vec<u64> = unpack(packed, component_bitsize, 3);
+    let unpacked: Vec<u64> = unpack(packed.clone(), component_bitsize, 3).unwrap();
     assert_eq!(unpacked, v);
+
+    let unpacked: Result<Vec<u64>, _> = unpack(packed, component_bitsize, 2);
+    assert_eq!(unpacked, Err(PlaintextOverflow));
+    let unpacked: Result<Vec<u64>, _> = unpack(pack(&[1u128 << 64], 128), 128, 1);
+    assert_eq!(unpacked, Err(PlaintextOverflow));
 }
@@ -0,0 +1,251 @@
+//! Signed integers, with negative values mapped to the top half of the plaintext space.
+//!
+//! A value `m` is encoded as `m mod N`, so that the homomorphic operations of the scheme are
+//! the integer operations as long as the results stay small compared to `N`. On decryption,
+//! plaintexts below `N / 2` are read as non-negative and the others as `x - N`; results that
+//! do not fit in an `i64` are reported as [`PlaintextOverflow`].
+
+use crate::paillier::traits::Add;
+use crate::paillier::traits::Decrypt;
+use crate::paillier::traits::Encrypt;
+use crate::paillier::traits::Mul;
+use crate::paillier::traits::Rerandomize;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use super::{EncodedCiphertext, PlaintextModulus, PlaintextOverflow};
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::paillier::{Paillier, RawCiphertext, RawPlaintext};
+use num_traits::ToPrimitive;
+use rand::{CryptoRng, RngCore};
+
+/// Maps `m` into Z_N.
+pub fn encode(m: i64, n: &BigInt) -> BigInt {
+    let abs = BigInt::from(m.unsigned_abs());
+    if m < 0 {
+        n - abs
+    } else {
+        abs
+    }
+}
+
+/// Inverse of [`encode`] for `m` in Z_N.
+pub fn decode(m: &BigInt, n: &BigInt) -> Result<i64, PlaintextOverflow> {
+    if let Some(m) = m.to_i64() {
+        return Ok(m);
+    }
+    // |m - N| <= 2^63
+    match (n - m).to_u64() {
+        Some(abs) if abs <= 1 << 63 => Ok(abs.wrapping_neg() as i64),
+        _ => Err(PlaintextOverflow),
+    }
+}
+
+impl<EK> Encrypt<EK, i64, EncodedCiphertext<i64>> for Paillier
+where
+    for<'p, 'c> Self: Encrypt<EK, RawPlaintext<'p>, RawCiphertext<'c>>,
+    EK: PlaintextModulus,
+{
+    fn encrypt_with_rng(
+        ek: &EK,
+        m: i64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<i64> {
+        let m = encode(m, ek.plaintext_modulus());
+        let c = Self::encrypt_with_rng(ek, RawPlaintext::from(m), rng);
+        EncodedCiphertext {
+            raw: c.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C> Rerandomize<EK, C, EncodedCiphertext<i64>> for Paillier
+where
+    for<'c, 'd> Self: Rerandomize<EK, RawCiphertext<'c>, RawCiphertext<'d>>,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn rerandomize_with_rng(
+        ek: &EK,
+        c: C,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> EncodedCiphertext<i64> {
+        let d = Self::rerandomize_with_rng(ek, RawCiphertext::from(&c.borrow().raw), rng);
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<DK, C> Decrypt<DK, C, Result<i64, PlaintextOverflow>> for Paillier
+where
+    for<'c, 'p> Self: Decrypt<DK, RawCiphertext<'c>, RawPlaintext<'p>>,
+    DK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn decrypt(dk: &DK, c: C) -> Result<i64, PlaintextOverflow> {
+        let m = Self::decrypt(dk, RawCiphertext::from(&c.borrow().raw));
+        decode(&m.into(), dk.plaintext_modulus())
+    }
+}
+
+impl<EK, C1, C2> Add<EK, C1, C2, EncodedCiphertext<i64>> for Paillier
+where
+    for<'c1, 'c2, 'd> Self: Add<EK, RawCiphertext<'c1>, RawCiphertext<'c2>, RawCiphertext<'d>>,
+    C1: Borrow<EncodedCiphertext<i64>>,
+    C2: Borrow<EncodedCiphertext<i64>>,
+{
+    fn add(ek: &EK, c1: C1, c2: C2) -> EncodedCiphertext<i64> {
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c1.borrow().raw),
+            RawCiphertext::from(&c2.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C> Add<EK, C, i64, EncodedCiphertext<i64>> for Paillier
+where
+    for<'c, 'p, 'd> Self: Add<EK, RawCiphertext<'c>, RawPlaintext<'p>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn add(ek: &EK, c: C, p: i64) -> EncodedCiphertext<i64> {
+        let d = Self::add(
+            ek,
+            RawCiphertext::from(&c.borrow().raw),
+            RawPlaintext::from(encode(p, ek.plaintext_modulus())),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C> Add<EK, i64, C, EncodedCiphertext<i64>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Add<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn add(ek: &EK, m: i64, c: C) -> EncodedCiphertext<i64> {
+        let d = Self::add(
+            ek,
+            RawPlaintext::from(encode(m, ek.plaintext_modulus())),
+            RawCiphertext::from(&c.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C> Mul<EK, C, i64, EncodedCiphertext<i64>> for Paillier
+where
+    for<'c, 'm, 'd> Self: Mul<EK, RawCiphertext<'c>, RawPlaintext<'m>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn mul(ek: &EK, c: C, m: i64) -> EncodedCiphertext<i64> {
+        let d = Self::mul(
+            ek,
+            RawCiphertext::from(&c.borrow().raw),
+            RawPlaintext::from(encode(m, ek.plaintext_modulus())),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EK, C> Mul<EK, i64, C, EncodedCiphertext<i64>> for Paillier
+where
+    for<'m, 'c, 'd> Self: Mul<EK, RawPlaintext<'m>, RawCiphertext<'c>, RawCiphertext<'d>>,
+    EK: PlaintextModulus,
+    C: Borrow<EncodedCiphertext<i64>>,
+{
+    fn mul(ek: &EK, m: i64, c: C) -> EncodedCiphertext<i64> {
+        let d = Self::mul(
+            ek,
+            RawPlaintext::from(encode(m, ek.plaintext_modulus())),
+            RawCiphertext::from(&c.borrow().raw),
+        );
+        EncodedCiphertext {
+            raw: d.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::paillier::test_keypair;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_encode_decode() {
+        let n = BigInt::from(1u32) << 100;
+        for m in [0, 1, -1, 42, -42, i64::MAX, i64::MIN].iter() {
+            assert_eq!(decode(&encode(*m, &n), &n), Ok(*m));
+        }
+        assert_eq!(decode(&(BigInt::from(1u32) << 63), &n), Err(PlaintextOverflow));
+        assert_eq!(
+            decode(&(&n - (BigInt::from(1u32) << 63) - BigInt::from(1u32)), &n),
+            Err(PlaintextOverflow)
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_signed_add_mul() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c1 = Paillier::encrypt(&ek, -10i64);
+        let c2 = Paillier::encrypt(&ek, 3i64);
+
+        let c = Paillier::add(&ek, &c1, &c2);
+        assert_eq!(Paillier::decrypt(&dk, &c), Ok(-7));
+        let c = Paillier::add(&ek, &c1, 25i64);
+        assert_eq!(Paillier::decrypt(&dk, &c), Ok(15));
+        let c = Paillier::mul(&ek, &c1, -4i64);
+        assert_eq!(Paillier::decrypt(&dk, &c), Ok(40));
+        let c = Paillier::mul(&ek, 5i64, &c2);
+        assert_eq!(Paillier::decrypt(&dk, &c), Ok(15));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_signed_overflow() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, i64::MAX);
+        let c = Paillier::add(&ek, &c, 1i64);
+        let m: Result<i64, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Err(PlaintextOverflow));
+
+        let c = Paillier::encrypt(&ek, i64::MIN);
+        let c = Paillier::add(&ek, -1i64, &c);
+        let m: Result<i64, _> = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, Err(PlaintextOverflow));
+    }
+}
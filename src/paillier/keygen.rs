@@ -1,139 +1,672 @@
 //! Key generation following standard recommendations.
+//!
+//! Primes are searched for by sieving a window of odd candidates against a table of small
+//! primes and running Miller-Rabin on the survivors: first to the base 2, which throws out
+//! nearly every composite for the price of one exponentiation, then to random bases. The
+//! exponentiations work in Montgomery form with a fixed 4-bit window, so the sequence of
+//! operations they perform does not depend on the bits of the candidate.
+//!
+//! The search can be driven chunk by chunk through [`KeypairSearch`], or awaited through
+//! [`Paillier::keypair_with_modulus_size_async`], which yields between chunks and reports
+//! progress so that a browser tab stays responsive while the keys are generated.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use crate::curv::arithmetic::num_bigint::BigInt;
 use crate::curv::arithmetic::traits::*;
 use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
 use crate::paillier::traits::*;
 use crate::paillier::{Keypair, Paillier};
+
 impl KeyGeneration<Keypair> for Paillier {
-    fn keypair_with_modulus_size(bit_length: usize) -> Keypair {
-        let p = BigInt::sample_prime(bit_length / 2);
-        let q = BigInt::sample_prime(bit_length / 2);
-        Keypair { p, q }
+    fn keypair_with_modulus_size_and_rng(
+        bit_length: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Keypair {
+        let mut search = KeypairSearch::new_with_rng(bit_length, rng);
+        loop {
+            if let Some(keypair) = search.step_with_rng(usize::MAX, rng) {
+                return keypair;
+            }
+        }
+    }
+}
+
+/// Number of sieve survivors tested between two yields of the async key generation.
+pub const KEYGEN_CHUNK: usize = 4;
+
+impl Paillier {
+    /// Generates a keypair in chunks of [`KEYGEN_CHUNK`] candidates, calling `on_progress`
+    /// after each chunk and awaiting `yield_now()` between them.
+    ///
+    /// [`yield_now`] only hands control back to the executor: in a browser it resolves as a
+    /// microtask, before the page renders or handles any event. There, pass a future that
+    /// resolves from a `setTimeout` callback instead, as the wasm API does.
+    pub async fn keypair_with_modulus_size_async<F, Y, Fut>(
+        bit_length: usize,
+        on_progress: F,
+        yield_now: Y,
+    ) -> Keypair
+    where
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let mut rng = os_rng();
+        Self::keypair_with_modulus_size_and_rng_async(bit_length, &mut rng, on_progress, yield_now)
+            .await
+    }
+
+    /// Same as [`Paillier::keypair_with_modulus_size_async`], drawing the randomness from `rng`.
+    pub async fn keypair_with_modulus_size_and_rng_async<R, F, Y, Fut>(
+        bit_length: usize,
+        rng: &mut R,
+        on_progress: F,
+        yield_now: Y,
+    ) -> Keypair
+    where
+        R: RngCore + CryptoRng,
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        KeypairSearch::new_with_rng(bit_length, rng)
+            .run_async(rng, on_progress, yield_now)
+            .await
+    }
+}
+
+/// Future that is pending exactly once, waking its task right away. The task is polled again
+/// as soon as the executor gets to it, so this lets other tasks run but not the event loop of
+/// a browser.
+pub struct YieldNow {
+    yielded: bool,
+}
+
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Progress of a keypair search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyGenProgress {
+    /// Primes found so far, out of two.
+    pub primes_found: usize,
+    /// Sieve survivors put through Miller-Rabin while looking for the current prime.
+    pub candidates_tested: usize,
+    /// Expected number of such candidates before a prime turns up.
+    pub expected_candidates: f64,
+}
+
+impl KeyGenProgress {
+    /// Estimated fraction of the search done, between 0 and 1: the chance that the current
+    /// prime would have been found by now, added to the primes already found. The length of
+    /// a prime search is random, so this is meant for a progress bar, not as a deadline.
+    pub fn fraction(&self) -> f64 {
+        if self.primes_found >= 2 {
+            return 1.0;
+        }
+        let current = 1.0 - (-(self.candidates_tested as f64) / self.expected_candidates).exp();
+        (self.primes_found as f64 + current) / 2.0
+    }
+}
+
+/// Search for the two primes of a keypair, driven by [`KeypairSearch::step`].
+pub struct KeypairSearch {
+    search: PrimeSearch,
+    p: Option<BigInt>,
+    primes_found: usize,
+}
+
+impl KeypairSearch {
+    pub fn new(bit_length: usize) -> KeypairSearch {
+        KeypairSearch::new_with_rng(bit_length, &mut os_rng())
+    }
+
+    pub fn new_with_rng(bit_length: usize, rng: &mut (impl RngCore + CryptoRng)) -> KeypairSearch {
+        KeypairSearch {
+            search: PrimeSearch::new_with_rng(bit_length / 2, rng),
+            p: None,
+            primes_found: 0,
+        }
+    }
+
+    /// Search for two safe primes whose product has `bit_length` bits, as the factors of a
+    /// ring-Pedersen modulus.
+    pub fn new_safe(bit_length: usize) -> KeypairSearch {
+        KeypairSearch::new_safe_with_rng(bit_length, &mut os_rng())
+    }
+
+    pub fn new_safe_with_rng(
+        bit_length: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> KeypairSearch {
+        KeypairSearch {
+            search: PrimeSearch::new_safe_with_rng(bit_length / 2, rng),
+            p: None,
+            primes_found: 0,
+        }
+    }
+
+    /// Tests at most `budget` candidates, returning the keypair once both primes are found.
+    pub fn step(&mut self, budget: usize) -> Option<Keypair> {
+        self.step_with_rng(budget, &mut os_rng())
+    }
+
+    pub fn step_with_rng(
+        &mut self,
+        mut budget: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Option<Keypair> {
+        while budget > 0 {
+            let tested = self.search.candidates_tested();
+            let prime = self.search.step_with_rng(budget, rng)?;
+            budget -= self.search.candidates_tested() - tested;
+            // q is searched for from a fresh random start: continuing from p would make
+            // the two primes close enough for Fermat's factorization method
+            self.search = self.search.restart_with_rng(rng);
+            match self.p.take() {
+                Some(p) if p != prime => {
+                    self.primes_found = 2;
+                    return Some(Keypair { p, q: prime });
+                }
+                Some(p) => self.p = Some(p),
+                None => {
+                    self.primes_found = 1;
+                    self.p = Some(prime);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn progress(&self) -> KeyGenProgress {
+        KeyGenProgress {
+            primes_found: self.primes_found,
+            candidates_tested: self.search.candidates_tested(),
+            expected_candidates: self.search.expected_candidates(),
+        }
+    }
+
+    /// Runs the search to the end in chunks of [`KEYGEN_CHUNK`] candidates, calling
+    /// `on_progress` after each chunk and awaiting `yield_now()` between them.
+    pub async fn run_async<R, F, Y, Fut>(
+        mut self,
+        rng: &mut R,
+        mut on_progress: F,
+        mut yield_now: Y,
+    ) -> Keypair
+    where
+        R: RngCore + CryptoRng,
+        F: FnMut(&KeyGenProgress),
+        Y: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let keypair = self.step_with_rng(KEYGEN_CHUNK, rng);
+            on_progress(&self.progress());
+            match keypair {
+                Some(keypair) => return keypair,
+                None => yield_now().await,
+            }
+        }
     }
 }
 
 pub trait PrimeSampable {
     fn sample_prime(bitsize: usize) -> Self;
+    fn sample_prime_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self;
+    /// Samples a prime p of `bitsize` bits such that (p - 1) / 2 is prime too.
+    fn sample_safe_prime(bitsize: usize) -> Self;
+    fn sample_safe_prime_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self;
 }
 
 impl PrimeSampable for BigInt {
     fn sample_prime(bitsize: usize) -> Self {
-        // See Practical Considerations section inside the section 11.5 "Prime Number Generation"
-        // Applied Cryptography, Bruce Schneier.
-        let one = BigInt::one();
-        let two = &one + &one;
+        Self::sample_prime_with_rng(bitsize, &mut os_rng())
+    }
+
+    fn sample_prime_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut search = PrimeSearch::new_with_rng(bitsize, rng);
         loop {
-            let mut candidate = Self::sample(bitsize);
-            // We flip the LSB to make sure tue candidate is odd.
-            //  BitManipulation::set_bit(&mut candidate, 0, true);
-            BigInt::set_bit(&mut candidate, 0, true);
-
-            // To ensure the appropiate size
-            // we set the MSB of the candidate.
-            BigInt::set_bit(&mut candidate, bitsize - 1, true);
-
-            // If no prime number is found in 500 iterations,
-            // restart the loop (re-seed).
-            // FIXME: Why 500?
-            for _ in 0..500 {
-                if is_prime(&candidate) {
-                    return candidate;
-                }
-                candidate = candidate + &two;
+            if let Some(prime) = search.step_with_rng(usize::MAX, rng) {
+                return prime;
+            }
+        }
+    }
+
+    fn sample_safe_prime(bitsize: usize) -> Self {
+        Self::sample_safe_prime_with_rng(bitsize, &mut os_rng())
+    }
+
+    fn sample_safe_prime_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut search = PrimeSearch::new_safe_with_rng(bitsize, rng);
+        loop {
+            if let Some(prime) = search.step_with_rng(usize::MAX, rng) {
+                return prime;
             }
         }
     }
 }
 
-// Runs the following three tests on a given `candidate` to determine
-// primality:
-//
-// 1. Divide the candidate by the first 999 small prime numbers.
-// 2. Run Fermat's Little Theorem against the candidate.
-// 3. Run five rounds of the Miller-Rabin test on the candidate.
-fn is_prime(candidate: &BigInt) -> bool {
-    // First, simple trial divide
-    for p in SMALL_PRIMES.iter() {
-        let prime = BigInt::from(*p);
-        let r = candidate % &prime;
-        if !NumberTests::is_zero(&r) {
-            continue;
-        } else {
-            return false;
+/// Number of odd candidates covered by one sieve window.
+const SIEVE_WINDOW: usize = 4096;
+
+/// Incremental search for a random prime of `bitsize` bits.
+///
+/// The search starts from a random odd number with its two top bits set, so that the product
+/// of two such primes has exactly twice their size, and walks up through the odd numbers
+/// after it. The residues of the window start modulo the small primes are kept, so moving to
+/// the next window and sieving it costs no big number division.
+///
+/// A safe prime search walks through the candidates q for (p - 1) / 2 instead, and also sieves
+/// out those for which 2q + 1 has a small factor.
+pub struct PrimeSearch {
+    bitsize: usize,
+    safe: bool,
+    base: BigInt,
+    residues: Vec<u32>,
+    composite: Vec<bool>,
+    offset: usize,
+    tested: usize,
+    expected: f64,
+}
+
+impl PrimeSearch {
+    pub fn new(bitsize: usize) -> PrimeSearch {
+        PrimeSearch::new_with_rng(bitsize, &mut os_rng())
+    }
+
+    pub fn new_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> PrimeSearch {
+        // below this size candidates could be small primes themselves and get sieved out
+        assert!(bitsize >= 16, "primes must have at least 16 bits");
+        // primes are about 2 / (bitsize * ln 2) of the odd numbers, and the sieve keeps
+        // prod (1 - 1/p) of them
+        let kept: f64 = SMALL_PRIMES[1..]
+            .iter()
+            .map(|p| 1.0 - 1.0 / *p as f64)
+            .product();
+        let expected = bitsize as f64 * std::f64::consts::LN_2 / 2.0 * kept;
+        PrimeSearch::with_expected(bitsize, false, expected, rng)
+    }
+
+    /// Search for a safe prime of `bitsize` bits.
+    pub fn new_safe(bitsize: usize) -> PrimeSearch {
+        PrimeSearch::new_safe_with_rng(bitsize, &mut os_rng())
+    }
+
+    pub fn new_safe_with_rng(bitsize: usize, rng: &mut (impl RngCore + CryptoRng)) -> PrimeSearch {
+        assert!(bitsize >= 17, "safe primes must have at least 17 bits");
+        // q and 2q + 1 are each prime with probability about 2 / (bitsize * ln 2), and the
+        // sieve keeps prod (1 - 2/p) of the odd q
+        let kept: f64 = SMALL_PRIMES[1..]
+            .iter()
+            .map(|p| 1.0 - 2.0 / *p as f64)
+            .product();
+        let expected = (bitsize as f64 * std::f64::consts::LN_2 / 2.0).powi(2) * kept;
+        PrimeSearch::with_expected(bitsize - 1, true, expected, rng)
+    }
+
+    fn with_expected(
+        bitsize: usize,
+        safe: bool,
+        expected: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> PrimeSearch {
+        let mut search = PrimeSearch {
+            bitsize,
+            safe,
+            base: BigInt::zero(),
+            residues: Vec::new(),
+            composite: vec![false; SIEVE_WINDOW],
+            offset: 0,
+            tested: 0,
+            expected,
+        };
+        search.reseed(rng);
+        search
+    }
+
+    /// A new search for the same kind of prime, from a fresh random start.
+    fn restart_with_rng(&self, rng: &mut (impl RngCore + CryptoRng)) -> PrimeSearch {
+        PrimeSearch::with_expected(self.bitsize, self.safe, self.expected, rng)
+    }
+
+    /// Candidates put through Miller-Rabin so far.
+    pub fn candidates_tested(&self) -> usize {
+        self.tested
+    }
+
+    /// Expected number of candidates put through Miller-Rabin before a prime is found.
+    pub fn expected_candidates(&self) -> f64 {
+        self.expected
+    }
+
+    /// Tests at most `budget` sieve survivors and returns the first prime among them.
+    pub fn step(&mut self, budget: usize) -> Option<BigInt> {
+        self.step_with_rng(budget, &mut os_rng())
+    }
+
+    pub fn step_with_rng(
+        &mut self,
+        mut budget: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Option<BigInt> {
+        while budget > 0 {
+            if self.offset == SIEVE_WINDOW {
+                self.next_window(rng);
+            }
+            let i = self.offset;
+            self.offset += 1;
+            if self.composite[i] {
+                continue;
+            }
+            budget -= 1;
+            self.tested += 1;
+            let candidate = &self.base + BigInt::from(2 * i as u64);
+            if !self.safe {
+                if miller_rabin(&candidate, rng) {
+                    return Some(candidate);
+                }
+                continue;
+            }
+            let safe_prime = (&candidate << 1) + BigInt::one();
+            if miller_rabin(&candidate, rng) && miller_rabin(&safe_prime, rng) {
+                return Some(safe_prime);
+            }
         }
+        None
     }
-    // Second, do a little Fermat test on the candidate
-    if !fermat(candidate) {
-        return false;
+
+    fn reseed(&mut self, rng: &mut (impl RngCore + CryptoRng)) {
+        let window = BigInt::from(2 * SIEVE_WINDOW as u64);
+        let top_bits = BigInt::from(3u32) << (self.bitsize - 2);
+        let base = loop {
+            let base = BigInt::sample_with_rng(self.bitsize, rng) | top_bits.clone() | BigInt::one();
+            if (&base + &window).bits() == self.bitsize {
+                break base;
+            }
+        };
+        let bytes = base.to_bytes_be();
+        self.residues = SMALL_PRIMES[1..]
+            .iter()
+            .map(|p| {
+                let p = *p as u64;
+                bytes.iter().fold(0u64, |r, b| (r * 256 + *b as u64) % p) as u32
+            })
+            .collect();
+        self.base = base;
+        self.sieve();
     }
 
-    // Finally, do a Miller-Rabin test
-    // NIST recommendation is 5 rounds for 512 and 1024 bits. For 1536 bits, the recommendation is 4 rounds.
-    if !miller_rabin(candidate, 5) {
-        return false;
+    fn next_window(&mut self, rng: &mut (impl RngCore + CryptoRng)) {
+        let window = BigInt::from(2 * SIEVE_WINDOW as u64);
+        if (&self.base + &window + &window).bits() > self.bitsize {
+            return self.reseed(rng);
+        }
+        self.base = &self.base + &window;
+        for (r, p) in self.residues.iter_mut().zip(SMALL_PRIMES[1..].iter()) {
+            *r = ((*r as usize + 2 * SIEVE_WINDOW) % *p as usize) as u32;
+        }
+        self.sieve();
+    }
+
+    /// Marks the candidates base + 2i of the window that one of the odd small primes divides,
+    /// and in a safe prime search those q for which it divides 2q + 1.
+    fn sieve(&mut self) {
+        self.composite.iter_mut().for_each(|c| *c = false);
+        for (r, p) in self.residues.iter().zip(SMALL_PRIMES[1..].iter()) {
+            let p = *p as usize;
+            // base + 2i = 0 mod p  <=>  i = -r / 2 mod p
+            mark(&mut self.composite, p, (p - *r as usize) % p * p.div_ceil(2) % p);
+            if self.safe {
+                // 2(base + 2i) + 1 = 0 mod p  <=>  i = ((p - 1) / 2 - r) / 2 mod p
+                let i = ((p - 1) / 2 + p - *r as usize) % p * p.div_ceil(2) % p;
+                mark(&mut self.composite, p, i);
+            }
+        }
+        self.offset = 0;
     }
-    true
 }
 
-/// Perform test based on Fermat's little theorem
-/// This might be performed more than once, see Handbook of Applied Cryptography [Algorithm 4.9 p136]
-fn fermat(candidate: &BigInt) -> bool {
-    let random = BigInt::sample_below(candidate);
-    let result = BigInt::mod_pow(&random, &(candidate - &BigInt::one()), candidate);
+/// Tests `candidate` for primality: trial division by the small primes, then Miller-Rabin.
+pub fn is_probable_prime(candidate: &BigInt) -> bool {
+    is_probable_prime_with_rng(candidate, &mut os_rng())
+}
 
-    result == BigInt::one()
+pub fn is_probable_prime_with_rng(
+    candidate: &BigInt,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> bool {
+    if candidate < &BigInt::from(2u32) {
+        return false;
+    }
+    for p in SMALL_PRIMES.iter() {
+        let prime = BigInt::from(*p);
+        if candidate == &prime {
+            return true;
+        }
+        if NumberTests::is_zero(&(candidate % &prime)) {
+            return false;
+        }
+    }
+    miller_rabin(candidate, rng)
 }
 
-/// Perform Miller-Rabin primality test
-fn miller_rabin(candidate: &BigInt, limit: usize) -> bool {
-    // Iterations recommended for which  p < (1/2)^{80}
-    //  500 bits => 6 iterations
-    // 1000 bits => 3 iterations
-    // 2000 bits => 2 iterations
+/// Marks every `p`-th candidate of the window starting at `i`.
+fn mark(composite: &mut [bool], p: usize, mut i: usize) {
+    while i < SIEVE_WINDOW {
+        composite[i] = true;
+        i += p;
+    }
+}
 
-    let (s, d) = rewrite(&(candidate - &BigInt::one()));
-    let one = BigInt::one();
-    let two = &one + &one;
+/// Number of Miller-Rabin rounds to random bases that bring the chance of a random composite
+/// passing under 2^-100, following FIPS 186-4 appendix C.3.
+fn miller_rabin_rounds(bits: usize) -> usize {
+    match bits {
+        b if b >= 1536 => 4,
+        b if b >= 1024 => 5,
+        b if b >= 512 => 7,
+        _ => 40,
+    }
+}
 
-    for _ in 0..limit {
-        let basis = BigInt::sample_range(&two, &(candidate - &two));
-        let mut y = BigInt::mod_pow(&basis, &d, candidate);
+/// Miller-Rabin to the base 2, then to random bases. `candidate` must be odd and have no
+/// small factors.
+fn miller_rabin(candidate: &BigInt, rng: &mut (impl RngCore + CryptoRng)) -> bool {
+    let mont = Montgomery::new(candidate);
+    let two = BigInt::from(2u32);
+    let n_minus_one = candidate - BigInt::one();
+    let (s, d) = rewrite(&n_minus_one);
+    if !strong_probable_prime(&mont, &two, s, &d) {
+        return false;
+    }
+    (0..miller_rabin_rounds(candidate.bits())).all(|_| {
+        let basis = BigInt::sample_range_with_rng(&two, &n_minus_one, rng);
+        strong_probable_prime(&mont, &basis, s, &d)
+    })
+}
 
-        if y == one || y == (candidate - &one) {
-            continue;
-        } else {
-            let mut counter = BigInt::one();
-            while counter < (&s - &one) {
-                y = BigInt::mod_pow(&y, &two, candidate);
-                if y == one {
-                    return false;
-                } else if y == candidate - &one {
-                    break;
-                }
-                counter = counter + BigInt::one();
-            }
+/// One round of Miller-Rabin: whether the modulus of `mont`, with n - 1 = 2^s * d, is a
+/// strong probable prime to the base `basis`.
+fn strong_probable_prime(mont: &Montgomery, basis: &BigInt, s: usize, d: &BigInt) -> bool {
+    let mut y = mont.pow(&mont.to_montgomery(basis), d);
+    if y == mont.one || y == mont.minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        y = mont.mul(&y, &y);
+        if y == mont.minus_one {
+            return true;
+        }
+        if y == mont.one {
             return false;
         }
     }
-    true
+    false
 }
 
 /// Rewrite a number n = 2^s * d
 /// (i.e., 2^s is the largest power of 2 that divides the candidate).
-fn rewrite(n: &BigInt) -> (BigInt, BigInt) {
+fn rewrite(n: &BigInt) -> (usize, BigInt) {
     let mut d = n.clone();
-    let mut s = BigInt::zero();
-    let one = BigInt::one();
+    let mut s = 0;
 
     while BigInt::is_even(&d) {
-        d = d >> 1_usize;
-        s = &s + &one;
+        d >>= 1_usize;
+        s += 1;
     }
 
     (s, d)
 }
 
+/// Arithmetic modulo an odd n in Montgomery form, x -> xR mod n with R = 2^(32 * limbs), on
+/// little-endian u32 limbs.
+///
+/// Products are reduced word by word (CIOS) and the final subtraction is masked rather than
+/// branched on; exponentiation uses a fixed window over all the limbs of the exponent and
+/// reads its table by touching every entry. Limb products are u64, which wasm32 has natively.
+struct Montgomery {
+    modulus: BigInt,
+    n: Vec<u32>,
+    n0inv: u32, // -n^-1 mod 2^32
+    one: Vec<u32>,
+    minus_one: Vec<u32>,
+}
+
+impl Montgomery {
+    fn new(modulus: &BigInt) -> Montgomery {
+        assert!(!BigInt::is_even(modulus) && modulus > &BigInt::one());
+        let len = modulus.bits().div_ceil(32);
+        let n = to_limbs(modulus, len);
+        // Newton's iteration doubles the number of correct low bits of the inverse each time
+        let mut inv = 1u32;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+        let r = (BigInt::one() << (32 * len)) % modulus;
+        Montgomery {
+            modulus: modulus.clone(),
+            n,
+            n0inv: inv.wrapping_neg(),
+            one: to_limbs(&r, len),
+            minus_one: to_limbs(&(modulus - &r), len),
+        }
+    }
+
+    fn to_montgomery(&self, x: &BigInt) -> Vec<u32> {
+        let x = (x.clone() << (32 * self.n.len())) % &self.modulus;
+        to_limbs(&x, self.n.len())
+    }
+
+    #[cfg(test)]
+    fn out_of_montgomery(&self, x: &[u32]) -> BigInt {
+        let mut unit = vec![0u32; self.n.len()];
+        unit[0] = 1;
+        BigInt::from_slice(&self.mul(x, &unit))
+    }
+
+    /// abR^-1 mod n, for a, b < n.
+    fn mul(&self, a: &[u32], b: &[u32]) -> Vec<u32> {
+        let len = self.n.len();
+        let mut t = vec![0u32; len + 2];
+        for bi in b[..len].iter() {
+            let bi = *bi as u64;
+            let mut carry = 0u64;
+            for j in 0..len {
+                let s = t[j] as u64 + a[j] as u64 * bi + carry;
+                t[j] = s as u32;
+                carry = s >> 32;
+            }
+            let s = t[len] as u64 + carry;
+            t[len] = s as u32;
+            t[len + 1] = (s >> 32) as u32;
+
+            // add m * n, with m chosen to clear the low limb, and shift down by one limb
+            let m = t[0].wrapping_mul(self.n0inv) as u64;
+            let mut carry = (t[0] as u64 + m * self.n[0] as u64) >> 32;
+            for j in 1..len {
+                let s = t[j] as u64 + m * self.n[j] as u64 + carry;
+                t[j - 1] = s as u32;
+                carry = s >> 32;
+            }
+            let s = t[len] as u64 + carry;
+            t[len - 1] = s as u32;
+            t[len] = t[len + 1] + (s >> 32) as u32;
+        }
+
+        // t < 2n: keep t - n unless the subtraction borrows
+        let mut diff = vec![0u32; len];
+        let mut borrow = 0u64;
+        for j in 0..len {
+            let s = (t[j] as u64).wrapping_sub(self.n[j] as u64 + borrow);
+            diff[j] = s as u32;
+            borrow = s >> 63;
+        }
+        let borrow = ((t[len] as u64).wrapping_sub(borrow) >> 63) as u32;
+        let keep = borrow.wrapping_neg();
+        (0..len).map(|j| (t[j] & keep) | (diff[j] & !keep)).collect()
+    }
+
+    /// base^exp in Montgomery form, for base in Montgomery form and exp < R.
+    fn pow(&self, base: &[u32], exp: &BigInt) -> Vec<u32> {
+        let mut table = vec![self.one.clone()];
+        for k in 1..16 {
+            let next = self.mul(&table[k - 1], base);
+            table.push(next);
+        }
+        let exp = to_limbs(exp, self.n.len());
+        let mut acc = self.one.clone();
+        for w in (0..self.n.len() * 8).rev() {
+            for _ in 0..4 {
+                acc = self.mul(&acc, &acc);
+            }
+            let nibble = (exp[w / 8] >> (4 * (w % 8))) & 0xf;
+            acc = self.mul(&acc, &select(&table, nibble));
+        }
+        acc
+    }
+}
+
+/// Reads `table[index]` through a masked pass over all the entries.
+fn select(table: &[Vec<u32>], index: u32) -> Vec<u32> {
+    let mut out = vec![0u32; table[0].len()];
+    for (k, entry) in table.iter().enumerate() {
+        // all ones when k == index, zero otherwise
+        let mask = (((k as u32) ^ index).wrapping_sub(1) >> 31).wrapping_neg();
+        for (o, e) in out.iter_mut().zip(entry.iter()) {
+            *o |= *e & mask;
+        }
+    }
+    out
+}
+
+fn to_limbs(x: &BigInt, len: usize) -> Vec<u32> {
+    let mut limbs = vec![0u32; len];
+    for (i, b) in x.to_bytes_le().iter().enumerate() {
+        limbs[i / 4] |= (*b as u32) << (8 * (i % 4));
+    }
+    limbs
+}
+
 // BoringSSL's table.
 // https://boringssl.googlesource.com/boringssl/+/master/crypto/bn/prime.c
 #[cfg_attr(rustfmt, rustfmt_skip)]
@@ -325,3 +858,147 @@ static SMALL_PRIMES: [u32; 2048] = [
     17609, 17623, 17627, 17657, 17659, 17669, 17681, 17683, 17707, 17713, 17729,
     17737, 17747, 17749, 17761, 17783, 17789, 17791, 17807, 17827, 17837, 17839,
     17851, 17863 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_montgomery_pow() {
+        for bits in [32usize, 255, 256, 1024].iter() {
+            let modulus = BigInt::sample(*bits) | (BigInt::one() << (bits - 1)) | BigInt::one();
+            let mont = Montgomery::new(&modulus);
+            let base = BigInt::sample_below(&modulus);
+            let exp = BigInt::sample_below(&modulus);
+            let result = mont.out_of_montgomery(&mont.pow(&mont.to_montgomery(&base), &exp));
+            assert_eq!(result, BigInt::mod_pow(&base, &exp, &modulus));
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_is_probable_prime() {
+        let mersenne = |e: usize| (BigInt::one() << e) - BigInt::one();
+        for prime in [mersenne(61), mersenne(89), mersenne(127), mersenne(521)].iter() {
+            assert!(is_probable_prime(prime));
+        }
+        assert!(is_probable_prime(&BigInt::from(17863u32)));
+        assert!(is_probable_prime(&BigInt::from(65537u32)));
+        // a prime p = 1 mod 4, whose Miller-Rabin rounds can end in a square root of -1
+        assert!(is_probable_prime(&BigInt::from(1000000009u32)));
+
+        assert!(!is_probable_prime(&BigInt::one()));
+        assert!(!is_probable_prime(&(mersenne(61) * mersenne(89))));
+        assert!(!is_probable_prime(&(mersenne(127) * mersenne(127))));
+        // 561 is a Carmichael number, 3215031751 a strong pseudoprime to the bases 2, 3, 5, 7
+        assert!(!is_probable_prime(&BigInt::from(561u32)));
+        assert!(!is_probable_prime(&BigInt::from(3215031751u64)));
+        // 149491 * 747451 * 34233211 is a strong pseudoprime to the bases up to 23 and has no
+        // factor in the small prime table: only the random bases catch it
+        let psp = BigInt::from(3825123056546413051u64);
+        let (s, d) = rewrite(&(&psp - BigInt::one()));
+        assert!(strong_probable_prime(&Montgomery::new(&psp), &BigInt::from(2u32), s, &d));
+        assert!(!is_probable_prime(&psp));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_prime_search_in_chunks() {
+        let mut search = PrimeSearch::new(256);
+        let mut chunks = 0;
+        let prime = loop {
+            chunks += 1;
+            if let Some(prime) = search.step(1) {
+                break prime;
+            }
+        };
+        assert_eq!(chunks, search.candidates_tested());
+        assert_eq!(prime.bits(), 256);
+        assert_eq!(&prime >> 254, BigInt::from(3u32));
+        assert!(is_probable_prime(&prime));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keypair_with_seeded_rng() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let keypair = Paillier::keypair_with_modulus_size_and_rng(512, &mut StdRng::seed_from_u64(7));
+        let again = Paillier::keypair_with_modulus_size_and_rng(512, &mut StdRng::seed_from_u64(7));
+        let other = Paillier::keypair_with_modulus_size_and_rng(512, &mut StdRng::seed_from_u64(8));
+        assert_eq!((keypair.p.clone(), keypair.q.clone()), (again.p, again.q));
+        assert_ne!(keypair.p, other.p);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_sample_safe_prime() {
+        let p = BigInt::sample_safe_prime(256);
+        assert_eq!(p.bits(), 256);
+        assert_eq!(&p >> 254, BigInt::from(3u32));
+        assert!(is_probable_prime(&p));
+        assert!(is_probable_prime(&(&p >> 1)));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_safe_keypair_search() {
+        let mut search = KeypairSearch::new_safe(512);
+        let Keypair { p, q } = loop {
+            if let Some(keypair) = search.step(KEYGEN_CHUNK) {
+                break keypair;
+            }
+        };
+        assert_eq!((&p * &q).bits(), 512);
+        assert_ne!(p, q);
+        for prime in [p, q] {
+            assert!(is_probable_prime(&prime));
+            assert!(is_probable_prime(&(&prime >> 1)));
+        }
+        assert_eq!(search.progress().fraction(), 1.0);
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_keypair_async() {
+        let mut progress = Vec::new();
+        let mut yields = 0;
+        let keypair = {
+            let on_progress = |p: &KeyGenProgress| progress.push(*p);
+            let yield_counted = || {
+                yields += 1;
+                yield_now()
+            };
+            let mut future = Box::pin(Paillier::keypair_with_modulus_size_async(
+                512,
+                on_progress,
+                yield_counted,
+            ));
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                if let Poll::Ready(keypair) = future.as_mut().poll(&mut cx) {
+                    break keypair;
+                }
+            }
+        };
+        assert_eq!((&keypair.p * &keypair.q).bits(), 512);
+        assert_ne!(keypair.p, keypair.q);
+        assert_eq!(yields + 1, progress.len());
+        let fractions: Vec<f64> = progress.iter().map(|p| p.fraction()).collect();
+        assert!(fractions.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(fractions.last(), Some(&1.0));
+    }
+}
@@ -1,6 +1,8 @@
 pub mod core;
+pub mod damgard_jurik;
 pub mod encoding;
 pub mod keygen;
+pub mod threshold;
 pub mod traits;
 pub mod zkproofs;
 
@@ -23,6 +25,15 @@ pub struct Keypair {
     pub q: BigInt, // TODO[Morten] okay to make non-public?
 }
 
+/// Fixed keypair with a 2048 bit modulus for the unit tests, which would otherwise spend most
+/// of their time generating keys. Its primes are not safe primes.
+#[cfg(test)]
+pub(crate) fn test_keypair() -> Keypair {
+    let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+    let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+    Keypair { p, q }
+}
+
 /// Public encryption key with no precomputed values.
 ///
 /// Used e.g. for serialization of `EncryptionKey`.
@@ -48,11 +59,24 @@ pub struct EncryptionKey {
     pub nn: BigInt, // the modulus squared
 }
 
-/// Private decryption key.
+/// Private decryption key, with the values used by CRT decryption precomputed.
+///
+/// Serialized as `MinimalDecryptionKey`; the rest is recomputed when the key is loaded.
 #[derive(Clone, Debug, PartialEq)]
 pub struct DecryptionKey {
-    pub p: BigInt, // first prime
-    pub q: BigInt, // second prime
+    pub p: BigInt,     // first prime
+    pub q: BigInt,     // second prime
+    pub n: BigInt,     // the modulus
+    pub pp: BigInt,    // p^2
+    pub qq: BigInt,    // q^2
+    pub pinv: BigInt,  // p^-1 mod q
+    pub ppinv: BigInt, // p^-2 mod q^2
+    pub pminusone: BigInt,
+    pub qminusone: BigInt,
+    pub hp: BigInt, // L_p(g^(p-1) mod p^2)^-1 mod p
+    pub hq: BigInt, // L_q(g^(q-1) mod q^2)^-1 mod q
+    pub dp: BigInt, // n^-1 mod (p-1), the exponent extracting n-th roots mod p
+    pub dq: BigInt, // n^-1 mod (q-1), the exponent extracting n-th roots mod q
 }
 
 /// Unencrypted message without type information.
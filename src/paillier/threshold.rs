@@ -0,0 +1,426 @@
+//! Threshold decryption for Paillier, following Damgård and Jurik (PKC 2001, section 4) with s = 1,
+//! which adapts Shoup's threshold RSA.
+//!
+//! The modulus is a product of safe primes p = 2p' + 1 and q = 2q' + 1. With m = p'q', the
+//! decryption exponent d satisfies d = 0 mod m and d = 1 mod N, and is shared by a degree t
+//! polynomial over Z_{Nm}. Party i publishes c^{2Δs_i} with a proof that the exponent matches its
+//! verification key v^{Δs_i}, where Δ = n!, and any t + 1 valid shares interpolate c^{4Δ²d}, from
+//! which the plaintext is read off without anyone learning d.
+//!
+//! The shares here are produced by a trusted dealer; `DecryptionKeyShare` only holds a point of
+//! the polynomial, so shares coming from a distributed key generation plug in the same way.
+
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::paillier::{is_probable_prime, EncryptionKey, Keypair, PrimeSampable};
+use crate::paillier::{RawCiphertext, RawPlaintext};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+const DECRYPTION_SHARE_PROOF_LABEL: &[u8] = b"emerald-city/paillier-decryption-share";
+/// Bit length of the challenge of a decryption share proof.
+const CHALLENGE_BITS: usize = 256;
+
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum ThresholdError {
+    /// p or q is not a safe prime, or they are equal.
+    NotSafePrimes,
+    /// The threshold is not below the number of parties.
+    InvalidThreshold,
+    /// The decryption share of the party with this index failed verification.
+    InvalidShare(usize),
+    /// Fewer than t + 1 valid shares from distinct parties were given.
+    NotEnoughShares,
+}
+
+impl fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThresholdError::NotSafePrimes => write!(f, "the key is not made of two safe primes"),
+            ThresholdError::InvalidThreshold => write!(f, "invalid threshold"),
+            ThresholdError::InvalidShare(index) => {
+                write!(f, "invalid decryption share from party {}", index)
+            }
+            ThresholdError::NotEnoughShares => write!(f, "not enough decryption shares"),
+        }
+    }
+}
+
+impl Error for ThresholdError {}
+
+/// Public parameters of a shared key: the encryption key, and what is needed to check and
+/// combine decryption shares.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdPublicKey {
+    pub ek: EncryptionKey,
+    /// Any `threshold + 1` parties can decrypt.
+    pub threshold: usize,
+    pub parties: usize,
+    /// n! for n parties.
+    pub delta: BigInt,
+    /// A random square in Z*_{N^2}.
+    pub v: BigInt,
+    /// v^{Δs_i} for the party with index i, at position i - 1.
+    pub verification_keys: Vec<BigInt>,
+}
+
+/// The share s_i of the decryption exponent held by the party with index i, counted from 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecryptionKeyShare {
+    pub index: usize,
+    pub s_i: BigInt,
+}
+
+/// c^{2Δs_i} for a ciphertext c, with a proof that it was computed with the key share.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecryptionShare {
+    pub index: usize,
+    pub c_i: BigInt,
+    pub proof: DecryptionShareProof,
+}
+
+/// Proof that log_{c^4}(c_i^2) = log_v(v_i).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecryptionShareProof {
+    pub e: BigInt,
+    pub z: BigInt,
+}
+
+/// Generates a fresh key of `modulus_bits` bits and shares it among `parties` parties.
+pub fn keygen(
+    modulus_bits: usize,
+    threshold: usize,
+    parties: usize,
+) -> Result<(ThresholdPublicKey, Vec<DecryptionKeyShare>), ThresholdError> {
+    keygen_with_rng(modulus_bits, threshold, parties, &mut os_rng())
+}
+
+pub fn keygen_with_rng(
+    modulus_bits: usize,
+    threshold: usize,
+    parties: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(ThresholdPublicKey, Vec<DecryptionKeyShare>), ThresholdError> {
+    if threshold >= parties {
+        return Err(ThresholdError::InvalidThreshold);
+    }
+    let p = BigInt::sample_safe_prime_with_rng(modulus_bits / 2, rng);
+    let mut q = BigInt::sample_safe_prime_with_rng(modulus_bits / 2, rng);
+    while p == q {
+        q = BigInt::sample_safe_prime_with_rng(modulus_bits / 2, rng);
+    }
+    deal_with_rng(&Keypair { p, q }, threshold, parties, rng)
+}
+
+/// Shares the decryption key of `keypair`, whose primes must be safe primes, so that any
+/// `threshold + 1` out of `parties` parties can decrypt.
+pub fn deal(
+    keypair: &Keypair,
+    threshold: usize,
+    parties: usize,
+) -> Result<(ThresholdPublicKey, Vec<DecryptionKeyShare>), ThresholdError> {
+    deal_with_rng(keypair, threshold, parties, &mut os_rng())
+}
+
+pub fn deal_with_rng(
+    keypair: &Keypair,
+    threshold: usize,
+    parties: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(ThresholdPublicKey, Vec<DecryptionKeyShare>), ThresholdError> {
+    if threshold >= parties {
+        return Err(ThresholdError::InvalidThreshold);
+    }
+    let is_safe_prime = |p: &BigInt| is_probable_prime(p) && is_probable_prime(&(p >> 1));
+    if keypair.p == keypair.q || !is_safe_prime(&keypair.p) || !is_safe_prime(&keypair.q) {
+        return Err(ThresholdError::NotSafePrimes);
+    }
+
+    let ek = EncryptionKey::from(keypair);
+    let m = (&keypair.p >> 1) * (&keypair.q >> 1);
+    let nm = &ek.n * &m;
+    let d = &m * BigInt::mod_inv(&m, &ek.n);
+
+    // f(X) = d + a_1 X + ... + a_t X^t over Z_{Nm}
+    let mut coefficients = vec![d];
+    coefficients.extend((0..threshold).map(|_| BigInt::sample_below_with_rng(&nm, rng)));
+    let shares = (1..=parties)
+        .map(|index| {
+            let x = BigInt::from(index as u64);
+            let s_i = coefficients
+                .iter()
+                .rev()
+                .fold(BigInt::zero(), |acc, a| (acc * &x + a) % &nm);
+            DecryptionKeyShare { index, s_i }
+        })
+        .collect::<Vec<_>>();
+
+    let delta = (1..=parties as u64).fold(BigInt::one(), |acc, i| acc * BigInt::from(i));
+    let r = BigInt::sample_below_with_rng(&ek.nn, rng);
+    let v = BigInt::mod_mul(&r, &r, &ek.nn);
+    let verification_keys = shares
+        .iter()
+        .map(|share| BigInt::mod_pow(&v, &(&delta * &share.s_i), &ek.nn))
+        .collect();
+
+    let public = ThresholdPublicKey {
+        ek,
+        threshold,
+        parties,
+        delta,
+        v,
+        verification_keys,
+    };
+    Ok((public, shares))
+}
+
+impl DecryptionKeyShare {
+    /// Computes this party's share of the decryption of `c`.
+    pub fn decrypt(&self, pk: &ThresholdPublicKey, c: &RawCiphertext) -> DecryptionShare {
+        self.decrypt_with_rng(pk, c, &mut os_rng())
+    }
+
+    pub fn decrypt_with_rng(
+        &self,
+        pk: &ThresholdPublicKey,
+        c: &RawCiphertext,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> DecryptionShare {
+        let nn = &pk.ek.nn;
+        let c: &BigInt = c.0.borrow();
+        let exponent = &pk.delta * &self.s_i;
+        let c_i = BigInt::mod_pow(c, &(&exponent << 1), nn);
+
+        // r hides e * exponent, which is below 2^CHALLENGE_BITS * Δ * N^2
+        let r_bits = nn.bits() + pk.delta.bits() + 2 * CHALLENGE_BITS;
+        let r = BigInt::sample_with_rng(r_bits, rng);
+        let c4 = BigInt::mod_pow(c, &BigInt::from(4u32), nn);
+        let a = BigInt::mod_pow(&c4, &r, nn);
+        let b = BigInt::mod_pow(&pk.v, &r, nn);
+        let e = challenge(pk, self.index, c, &c_i, &a, &b);
+        let z = r + &e * exponent;
+
+        DecryptionShare {
+            index: self.index,
+            c_i,
+            proof: DecryptionShareProof { e, z },
+        }
+    }
+}
+
+impl DecryptionShare {
+    /// Checks the share against the verification key of its party.
+    pub fn verify(&self, pk: &ThresholdPublicKey, c: &RawCiphertext) -> Result<(), ThresholdError> {
+        let invalid = ThresholdError::InvalidShare(self.index);
+        if self.index == 0 || self.index > pk.verification_keys.len() {
+            return Err(invalid);
+        }
+        let nn = &pk.ek.nn;
+        let c: &BigInt = c.0.borrow();
+        let v_i = &pk.verification_keys[self.index - 1];
+        if self.c_i.gcd(&pk.ek.n) != BigInt::one() {
+            return Err(invalid);
+        }
+
+        // a = c^{4z} / c_i^{2e}, b = v^z / v_i^e
+        let c4 = BigInt::mod_pow(c, &BigInt::from(4u32), nn);
+        let c_i2_e = BigInt::mod_pow(&self.c_i, &(&self.proof.e << 1), nn);
+        let a = BigInt::mod_mul(
+            &BigInt::mod_pow(&c4, &self.proof.z, nn),
+            &BigInt::mod_inv(&c_i2_e, nn),
+            nn,
+        );
+        let v_i_e = BigInt::mod_pow(v_i, &self.proof.e, nn);
+        let b = BigInt::mod_mul(
+            &BigInt::mod_pow(&pk.v, &self.proof.z, nn),
+            &BigInt::mod_inv(&v_i_e, nn),
+            nn,
+        );
+
+        if challenge(pk, self.index, c, &self.c_i, &a, &b) == self.proof.e {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+}
+
+impl ThresholdPublicKey {
+    /// Combines the first `threshold + 1` decryption shares of `c` that are valid and come from
+    /// distinct parties into the plaintext. Invalid shares are skipped, so a party sending a
+    /// bad share cannot stop the decryption as long as enough honest shares are given.
+    pub fn combine<'m>(
+        &self,
+        c: &RawCiphertext,
+        shares: &[DecryptionShare],
+    ) -> Result<RawPlaintext<'m>, ThresholdError> {
+        let mut used: Vec<&DecryptionShare> = Vec::with_capacity(self.threshold + 1);
+        for share in shares {
+            if used.len() > self.threshold {
+                break;
+            }
+            if used.iter().all(|u| u.index != share.index) && share.verify(self, c).is_ok() {
+                used.push(share);
+            }
+        }
+        if used.len() <= self.threshold {
+            return Err(ThresholdError::NotEnoughShares);
+        }
+
+        let nn = &self.ek.nn;
+        let n = &self.ek.n;
+        // c' = prod c_i^{2μ_i} = c^{4Δ²d}, with μ_i = Δ prod_{j != i} j / (j - i)
+        let combined = used.iter().fold(BigInt::one(), |acc, share| {
+            let i = share.index;
+            let mut numerator = self.delta.clone();
+            let mut denominator = BigInt::one();
+            let mut negative = false;
+            for j in used.iter().map(|u| u.index).filter(|j| *j != i) {
+                numerator *= BigInt::from(j as u64);
+                denominator *= BigInt::from((j as i64 - i as i64).unsigned_abs());
+                negative ^= j < i;
+            }
+            let mu = numerator / denominator;
+            let base = if negative {
+                BigInt::mod_inv(&share.c_i, nn)
+            } else {
+                share.c_i.clone()
+            };
+            BigInt::mod_mul(&acc, &BigInt::mod_pow(&base, &(mu << 1), nn), nn)
+        });
+
+        // c' = 1 + 4Δ²mN mod N^2
+        let l = (combined - BigInt::one()) / n;
+        let four_delta_squared = (&self.delta * &self.delta) << 2;
+        let m = BigInt::mod_mul(&l, &BigInt::mod_inv(&(four_delta_squared % n), n), n);
+        Ok(RawPlaintext::from(m))
+    }
+}
+
+fn challenge(
+    pk: &ThresholdPublicKey,
+    index: usize,
+    c: &BigInt,
+    c_i: &BigInt,
+    a: &BigInt,
+    b: &BigInt,
+) -> BigInt {
+    let mut transcript = Transcript::new(DECRYPTION_SHARE_PROOF_LABEL);
+    transcript.append_bigint(b"N", &pk.ek.n);
+    transcript.append_bigint(b"v", &pk.v);
+    transcript.append_bigint(b"v_i", &pk.verification_keys[index - 1]);
+    transcript.append_bigint(b"c", c);
+    transcript.append_bigint(b"c_i", c_i);
+    transcript.append_bigint(b"a", a);
+    transcript.append_bigint(b"b", b);
+    transcript.challenge_bigint(b"challenge", CHALLENGE_BITS)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::paillier::test_keypair;
+    use crate::paillier::{Encrypt, Paillier};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn test_key(threshold: usize, parties: usize) -> (ThresholdPublicKey, Vec<DecryptionKeyShare>) {
+        keygen_with_rng(512, threshold, parties, &mut StdRng::seed_from_u64(48)).unwrap()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_threshold_decrypt() {
+        let (pk, shares) = test_key(2, 5);
+        let m = BigInt::from(1_234_567u64);
+        let c = Paillier::encrypt(&pk.ek, RawPlaintext::from(&m));
+
+        let decryption_shares = shares
+            .iter()
+            .map(|share| share.decrypt(&pk, &c))
+            .collect::<Vec<_>>();
+        for share in decryption_shares.iter() {
+            assert!(share.verify(&pk, &c).is_ok());
+        }
+        // any 3 out of 5
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]].iter() {
+            let subset = subset
+                .iter()
+                .map(|i| decryption_shares[*i].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(pk.combine(&c, &subset), Ok(RawPlaintext::from(&m)));
+        }
+        assert_eq!(
+            pk.combine(&c, &decryption_shares[..2]),
+            Err(ThresholdError::NotEnoughShares)
+        );
+        let repeated = vec![decryption_shares[0].clone(); 3];
+        assert_eq!(
+            pk.combine(&c, &repeated),
+            Err(ThresholdError::NotEnoughShares)
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_invalid_decryption_share() {
+        let (pk, shares) = test_key(1, 3);
+        let c = Paillier::encrypt(&pk.ek, RawPlaintext::from(BigInt::from(7u32)));
+        let other = Paillier::encrypt(&pk.ek, RawPlaintext::from(BigInt::from(8u32)));
+
+        // a share of another ciphertext
+        let wrong = shares[1].decrypt(&pk, &other);
+        assert_eq!(wrong.verify(&pk, &c), Err(ThresholdError::InvalidShare(2)));
+
+        // a tampered share
+        let mut tampered = shares[0].decrypt(&pk, &c);
+        tampered.c_i = BigInt::mod_mul(&tampered.c_i, &tampered.c_i, &pk.ek.nn);
+        let good = shares[2].decrypt(&pk, &c);
+        assert_eq!(
+            pk.combine(&c, &[tampered.clone(), good.clone()]),
+            Err(ThresholdError::NotEnoughShares)
+        );
+        // invalid shares are skipped when enough valid ones are left
+        let also_good = shares[1].decrypt(&pk, &c);
+        assert_eq!(
+            pk.combine(&c, &[tampered, wrong, good, also_good]),
+            Ok(RawPlaintext::from(BigInt::from(7u32)))
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_threshold_zero() {
+        let (pk, shares) = test_key(0, 2);
+        let m = BigInt::from(42u32);
+        let c = Paillier::encrypt(&pk.ek, RawPlaintext::from(&m));
+        for share in shares.iter() {
+            let decryption_share = share.decrypt(&pk, &c);
+            assert_eq!(pk.combine(&c, &[decryption_share]), Ok(RawPlaintext::from(&m)));
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_deal_requires_safe_primes() {
+        let keypair = test_keypair();
+        assert_eq!(
+            deal(&keypair, 1, 3).err(),
+            Some(ThresholdError::NotSafePrimes)
+        );
+        assert_eq!(
+            keygen(512, 3, 3).err(),
+            Some(ThresholdError::InvalidThreshold)
+        );
+    }
+}
@@ -1,5 +1,8 @@
 //! Abstract operations exposed by the library.
 
+use crate::curv::arithmetic::traits::os_rng;
+use rand::{CryptoRng, RngCore};
+
 /// Secure generation of fresh key pairs.
 pub trait KeyGeneration<KP> {
     /// Generate fresh key pair with currently recommended security level (2048 bit modulus).
@@ -7,10 +10,21 @@ pub trait KeyGeneration<KP> {
         Self::keypair_with_modulus_size(2048)
     }
 
+    fn keypair_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> KP {
+        Self::keypair_with_modulus_size_and_rng(2048, rng)
+    }
+
     /// Generate fresh key pair with security level specified as the `bit_length` of the modulus.
     ///
     /// Currently recommended security level is a minimum of 2048 bits.
-    fn keypair_with_modulus_size(big_length: usize) -> KP;
+    fn keypair_with_modulus_size(bit_length: usize) -> KP {
+        Self::keypair_with_modulus_size_and_rng(bit_length, &mut os_rng())
+    }
+
+    fn keypair_with_modulus_size_and_rng(
+        bit_length: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> KP;
 }
 
 pub trait PrecomputeRandomness<EK, R, PR> {
@@ -20,7 +34,12 @@ pub trait PrecomputeRandomness<EK, R, PR> {
 /// Encryption of plaintext.
 pub trait Encrypt<EK, PT, CT> {
     /// Encrypt plaintext `m` under key `ek` into a ciphertext.
-    fn encrypt(ek: &EK, m: PT) -> CT;
+    fn encrypt(ek: &EK, m: PT) -> CT {
+        Self::encrypt_with_rng(ek, m, &mut os_rng())
+    }
+
+    /// Encrypt plaintext `m` under key `ek`, drawing the randomness from `rng`.
+    fn encrypt_with_rng(ek: &EK, m: PT, rng: &mut (impl RngCore + CryptoRng)) -> CT;
 }
 
 pub trait EncryptWithChosenRandomness<EK, PT, R, CT> {
@@ -59,5 +78,9 @@ pub trait Mul<EK, CT1, PT2, CT> {
 pub trait Rerandomize<EK, CT1, CT> {
     /// Rerandomise ciphertext `c` to hide any history of which homomorphic operations were
     /// used to compute it, making it look exactly like a fresh encryption of the same plaintext.
-    fn rerandomize(ek: &EK, c: CT1) -> CT;
+    fn rerandomize(ek: &EK, c: CT1) -> CT {
+        Self::rerandomize_with_rng(ek, c, &mut os_rng())
+    }
+
+    fn rerandomize_with_rng(ek: &EK, c: CT1, rng: &mut (impl RngCore + CryptoRng)) -> CT;
 }
@@ -0,0 +1,680 @@
+/*
+    zk-paillier
+
+    Copyright 2018 by Kzen Networks
+
+    zk-paillier is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/zk-paillier/blob/master/LICENSE>
+*/
+
+//! Non-interactive proofs about the content of Paillier ciphertexts.
+//!
+//! All of them are Fiat-Shamir transforms of sigma protocols for knowledge of an N-th root mod
+//! N^2: an encryption of zero is exactly such an N-th power, and the other statements reduce to
+//! it once the claimed plaintext is divided out of the ciphertext. The prover either knows the
+//! randomness of the encryption or holds the decryption key and recovers it with
+//! `extract_nroot`.
+
+use crate::curv::arithmetic::num_bigint::BigInt;
+use crate::curv::arithmetic::traits::*;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::paillier::RawCiphertext;
+use crate::paillier::{extract_nroot, Decrypt, DecryptionKey, EncryptionKey, Paillier};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+use std::error::Error;
+use std::fmt;
+
+const ZERO_ENCRYPTION_PROOF_LABEL: &[u8] = b"emerald-city/paillier-zero-encryption";
+const KNOWN_PLAINTEXT_PROOF_LABEL: &[u8] = b"emerald-city/paillier-known-plaintext";
+const CORRECT_DECRYPTION_PROOF_LABEL: &[u8] = b"emerald-city/paillier-correct-decryption";
+const EQUAL_PLAINTEXT_PROOF_LABEL: &[u8] = b"emerald-city/paillier-equal-plaintext";
+/// Bit length of the challenges; it has to stay below the bit length of the prime factors.
+const CHALLENGE_BITS: usize = 256;
+/// Extra bits of the integer mask of a plaintext, making the response statistically hiding.
+const MASK_EXTRA_BITS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CiphertextProofError;
+
+impl fmt::Display for CiphertextProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ciphertext proof doesn't match the statement")
+    }
+}
+
+impl Error for CiphertextProofError {}
+
+/// Proof that `c` is an encryption of zero, i.e. an N-th power mod N^2.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NIZeroEncryptionProof {
+    pub a: BigInt,
+    pub z: BigInt,
+}
+
+/// Proof that `c` encrypts a given plaintext, by the party that encrypted it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NIKnownPlaintextProof {
+    pub a: BigInt,
+    pub z: BigInt,
+}
+
+/// Proof by the owner of the decryption key that `plaintext` is the decryption of `c`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NICorrectDecryptionProof {
+    pub plaintext: BigInt,
+    pub a: BigInt,
+    pub z: BigInt,
+}
+
+/// Proof that `c1` under `ek1` and `c2` under `ek2` encrypt the same value, below both moduli.
+///
+/// The response `z` is bounded by a quarter of the smaller modulus, so the plaintext that a
+/// valid proof binds the ciphertexts to is below half of either modulus in absolute value, and
+/// reduces to the same value under both keys. Proving needs a plaintext of at most
+/// [`NIEqualPlaintextProof::max_plaintext_bits`] bits, leaving room for the challenge and the
+/// statistical mask.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NIEqualPlaintextProof {
+    pub a1: BigInt,
+    pub a2: BigInt,
+    pub z: BigInt,
+    pub w1: BigInt,
+    pub w2: BigInt,
+}
+
+impl NIZeroEncryptionProof {
+    pub fn prove(dk: &DecryptionKey, c: &BigInt) -> NIZeroEncryptionProof {
+        Self::prove_with_rng(dk, c, &mut os_rng())
+    }
+
+    pub fn prove_with_rng(
+        dk: &DecryptionKey,
+        c: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NIZeroEncryptionProof {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(ZERO_ENCRYPTION_PROOF_LABEL),
+            dk,
+            c,
+            rng,
+        )
+    }
+
+    pub fn verify(&self, ek: &EncryptionKey, c: &BigInt) -> Result<(), CiphertextProofError> {
+        self.verify_with_transcript(&mut Transcript::new(ZERO_ENCRYPTION_PROOF_LABEL), ek, c)
+    }
+
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        dk: &DecryptionKey,
+        c: &BigInt,
+    ) -> NIZeroEncryptionProof {
+        Self::prove_with_transcript_and_rng(transcript, dk, c, &mut os_rng())
+    }
+
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        dk: &DecryptionKey,
+        c: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NIZeroEncryptionProof {
+        transcript.append_message(b"proof", ZERO_ENCRYPTION_PROOF_LABEL);
+        let r = extract_nroot(dk, c);
+        let (a, z) = prove_nroot(transcript, &EncryptionKey::from(&dk.n), c, &r, rng);
+        NIZeroEncryptionProof { a, z }
+    }
+
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        ek: &EncryptionKey,
+        c: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        transcript.append_message(b"proof", ZERO_ENCRYPTION_PROOF_LABEL);
+        verify_nroot(transcript, ek, c, &self.a, &self.z)
+    }
+}
+
+impl NIKnownPlaintextProof {
+    /// `r` is the randomness `c` was encrypted with.
+    pub fn prove(ek: &EncryptionKey, c: &BigInt, m: &BigInt, r: &BigInt) -> NIKnownPlaintextProof {
+        Self::prove_with_rng(ek, c, m, r, &mut os_rng())
+    }
+
+    pub fn prove_with_rng(
+        ek: &EncryptionKey,
+        c: &BigInt,
+        m: &BigInt,
+        r: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NIKnownPlaintextProof {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(KNOWN_PLAINTEXT_PROOF_LABEL),
+            ek,
+            c,
+            m,
+            r,
+            rng,
+        )
+    }
+
+    pub fn verify(
+        &self,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        m: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        self.verify_with_transcript(&mut Transcript::new(KNOWN_PLAINTEXT_PROOF_LABEL), ek, c, m)
+    }
+
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        m: &BigInt,
+        r: &BigInt,
+    ) -> NIKnownPlaintextProof {
+        Self::prove_with_transcript_and_rng(transcript, ek, c, m, r, &mut os_rng())
+    }
+
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        m: &BigInt,
+        r: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NIKnownPlaintextProof {
+        transcript.append_message(b"proof", KNOWN_PLAINTEXT_PROOF_LABEL);
+        transcript.append_bigint(b"m", m);
+        let (a, z) = prove_nroot(transcript, ek, &remove_plaintext(ek, c, m), r, rng);
+        NIKnownPlaintextProof { a, z }
+    }
+
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        m: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        transcript.append_message(b"proof", KNOWN_PLAINTEXT_PROOF_LABEL);
+        transcript.append_bigint(b"m", m);
+        verify_nroot(
+            transcript,
+            ek,
+            &remove_plaintext(ek, c, m),
+            &self.a,
+            &self.z,
+        )
+    }
+}
+
+impl NICorrectDecryptionProof {
+    /// Decrypts `c` and proves that the result, which the proof carries, is its plaintext.
+    pub fn prove(dk: &DecryptionKey, c: &BigInt) -> NICorrectDecryptionProof {
+        Self::prove_with_rng(dk, c, &mut os_rng())
+    }
+
+    pub fn prove_with_rng(
+        dk: &DecryptionKey,
+        c: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NICorrectDecryptionProof {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(CORRECT_DECRYPTION_PROOF_LABEL),
+            dk,
+            c,
+            rng,
+        )
+    }
+
+    pub fn verify(&self, ek: &EncryptionKey, c: &BigInt) -> Result<(), CiphertextProofError> {
+        self.verify_with_transcript(&mut Transcript::new(CORRECT_DECRYPTION_PROOF_LABEL), ek, c)
+    }
+
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        dk: &DecryptionKey,
+        c: &BigInt,
+    ) -> NICorrectDecryptionProof {
+        Self::prove_with_transcript_and_rng(transcript, dk, c, &mut os_rng())
+    }
+
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        dk: &DecryptionKey,
+        c: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> NICorrectDecryptionProof {
+        let ek = EncryptionKey::from(&dk.n);
+        let plaintext: BigInt = Paillier::decrypt(dk, RawCiphertext::from(c)).into();
+        transcript.append_message(b"proof", CORRECT_DECRYPTION_PROOF_LABEL);
+        transcript.append_bigint(b"m", &plaintext);
+        let c0 = remove_plaintext(&ek, c, &plaintext);
+        let r = extract_nroot(dk, &c0);
+        let (a, z) = prove_nroot(transcript, &ek, &c0, &r, rng);
+        NICorrectDecryptionProof { plaintext, a, z }
+    }
+
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        ek: &EncryptionKey,
+        c: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        if self.plaintext >= ek.n {
+            return Err(CiphertextProofError);
+        }
+        transcript.append_message(b"proof", CORRECT_DECRYPTION_PROOF_LABEL);
+        transcript.append_bigint(b"m", &self.plaintext);
+        let c0 = remove_plaintext(ek, c, &self.plaintext);
+        verify_nroot(transcript, ek, &c0, &self.a, &self.z)
+    }
+}
+
+impl NIEqualPlaintextProof {
+    /// `m` is the common plaintext and `r1`, `r2` the randomness of the two encryptions. Fails
+    /// if `m` is negative or longer than [`NIEqualPlaintextProof::max_plaintext_bits`].
+    pub fn prove(
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        m: &BigInt,
+        r1: &BigInt,
+        r2: &BigInt,
+    ) -> Result<NIEqualPlaintextProof, CiphertextProofError> {
+        Self::prove_with_rng(ek1, ek2, c1, c2, m, r1, r2, &mut os_rng())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_rng(
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        m: &BigInt,
+        r1: &BigInt,
+        r2: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<NIEqualPlaintextProof, CiphertextProofError> {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(EQUAL_PLAINTEXT_PROOF_LABEL),
+            ek1,
+            ek2,
+            c1,
+            c2,
+            m,
+            r1,
+            r2,
+            rng,
+        )
+    }
+
+    pub fn verify(
+        &self,
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        self.verify_with_transcript(
+            &mut Transcript::new(EQUAL_PLAINTEXT_PROOF_LABEL),
+            ek1,
+            ek2,
+            c1,
+            c2,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        m: &BigInt,
+        r1: &BigInt,
+        r2: &BigInt,
+    ) -> Result<NIEqualPlaintextProof, CiphertextProofError> {
+        Self::prove_with_transcript_and_rng(transcript, ek1, ek2, c1, c2, m, r1, r2, &mut os_rng())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        m: &BigInt,
+        r1: &BigInt,
+        r2: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<NIEqualPlaintextProof, CiphertextProofError> {
+        if *m < BigInt::zero() || m.bits() > Self::max_plaintext_bits(ek1, ek2) {
+            return Err(CiphertextProofError);
+        }
+        // m is masked over the integers, so that the same response z opens both commitments;
+        // mu and e * m are both below 2^(response bits - 1), so z stays below the bound
+        let mu = BigInt::sample_with_rng(Self::response_bits(ek1, ek2) - 1, rng);
+        let rho1 = sample_unit(&ek1.n, rng);
+        let rho2 = sample_unit(&ek2.n, rng);
+        let a1 = commit(ek1, &mu, &rho1);
+        let a2 = commit(ek2, &mu, &rho2);
+
+        let e = Self::challenge(transcript, ek1, ek2, c1, c2, &a1, &a2);
+        let z = mu + &e * m;
+        let w1 = BigInt::mod_mul(&rho1, &BigInt::mod_pow(r1, &e, &ek1.n), &ek1.n);
+        let w2 = BigInt::mod_mul(&rho2, &BigInt::mod_pow(r2, &e, &ek2.n), &ek2.n);
+        Ok(NIEqualPlaintextProof { a1, a2, z, w1, w2 })
+    }
+
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+    ) -> Result<(), CiphertextProofError> {
+        let units = [
+            (c1, &ek1.n),
+            (c2, &ek2.n),
+            (&self.a1, &ek1.n),
+            (&self.a2, &ek2.n),
+            (&self.w1, &ek1.n),
+            (&self.w2, &ek2.n),
+        ];
+        if !units.iter().all(|(x, n)| is_unit(x, n)) || self.w1 >= ek1.n || self.w2 >= ek2.n {
+            return Err(CiphertextProofError);
+        }
+        // two accepting responses to different challenges extract a plaintext of absolute value
+        // below 2^(response bits), less than half of either modulus
+        if self.z < BigInt::zero() || self.z.bits() > Self::response_bits(ek1, ek2) {
+            return Err(CiphertextProofError);
+        }
+
+        let e = Self::challenge(transcript, ek1, ek2, c1, c2, &self.a1, &self.a2);
+        // g^z w^N = a c^e under both keys
+        let holds = |ek: &EncryptionKey, c: &BigInt, a: &BigInt, w: &BigInt| {
+            commit(ek, &self.z, w) == BigInt::mod_mul(a, &BigInt::mod_pow(c, &e, &ek.nn), &ek.nn)
+        };
+        if holds(ek1, c1, &self.a1, &self.w1) && holds(ek2, c2, &self.a2, &self.w2) {
+            Ok(())
+        } else {
+            Err(CiphertextProofError)
+        }
+    }
+
+    /// Largest bit length of a plaintext that the equality of its encryptions under `ek1` and
+    /// `ek2` can be proven for.
+    pub fn max_plaintext_bits(ek1: &EncryptionKey, ek2: &EncryptionKey) -> usize {
+        Self::response_bits(ek1, ek2) - 1 - CHALLENGE_BITS - MASK_EXTRA_BITS
+    }
+
+    /// Bit length of the responses the verifier accepts: 2^(response bits) is at most a
+    /// quarter of the smaller modulus.
+    fn response_bits(ek1: &EncryptionKey, ek2: &EncryptionKey) -> usize {
+        ek1.n.bits().min(ek2.n.bits()) - 2
+    }
+
+    fn challenge(
+        transcript: &mut Transcript,
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        a1: &BigInt,
+        a2: &BigInt,
+    ) -> BigInt {
+        transcript.append_message(b"proof", EQUAL_PLAINTEXT_PROOF_LABEL);
+        transcript.append_bigint(b"N1", &ek1.n);
+        transcript.append_bigint(b"N2", &ek2.n);
+        transcript.append_bigint(b"c1", c1);
+        transcript.append_bigint(b"c2", c2);
+        transcript.append_bigint(b"a1", a1);
+        transcript.append_bigint(b"a2", a2);
+        transcript.challenge_bigint(b"challenge", CHALLENGE_BITS)
+    }
+}
+
+/// Proves knowledge of r with c = r^N mod N^2: a = rho^N, z = rho r^e mod N.
+fn prove_nroot(
+    transcript: &mut Transcript,
+    ek: &EncryptionKey,
+    c: &BigInt,
+    r: &BigInt,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> (BigInt, BigInt) {
+    let rho = sample_unit(&ek.n, rng);
+    let a = BigInt::mod_pow(&rho, &ek.n, &ek.nn);
+    let e = nroot_challenge(transcript, ek, c, &a);
+    let z = BigInt::mod_mul(&rho, &BigInt::mod_pow(r, &e, &ek.n), &ek.n);
+    (a, z)
+}
+
+fn verify_nroot(
+    transcript: &mut Transcript,
+    ek: &EncryptionKey,
+    c: &BigInt,
+    a: &BigInt,
+    z: &BigInt,
+) -> Result<(), CiphertextProofError> {
+    if !is_unit(c, &ek.n) || !is_unit(a, &ek.n) || !is_unit(z, &ek.n) || z >= &ek.n {
+        return Err(CiphertextProofError);
+    }
+    let e = nroot_challenge(transcript, ek, c, a);
+    // z^N = a c^e mod N^2
+    let lhs = BigInt::mod_pow(z, &ek.n, &ek.nn);
+    let rhs = BigInt::mod_mul(a, &BigInt::mod_pow(c, &e, &ek.nn), &ek.nn);
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(CiphertextProofError)
+    }
+}
+
+fn nroot_challenge(
+    transcript: &mut Transcript,
+    ek: &EncryptionKey,
+    c: &BigInt,
+    a: &BigInt,
+) -> BigInt {
+    transcript.append_bigint(b"N", &ek.n);
+    transcript.append_bigint(b"c", c);
+    transcript.append_bigint(b"a", a);
+    transcript.challenge_bigint(b"challenge", CHALLENGE_BITS)
+}
+
+/// g^m w^N mod N^2 for g = 1 + N, which is the encryption of m with randomness w.
+fn commit(ek: &EncryptionKey, m: &BigInt, w: &BigInt) -> BigInt {
+    let gm = (BigInt::one() + (m % &ek.n) * &ek.n) % &ek.nn;
+    BigInt::mod_mul(&gm, &BigInt::mod_pow(w, &ek.n, &ek.nn), &ek.nn)
+}
+
+/// c g^-m mod N^2, an encryption of zero if c encrypts m.
+fn remove_plaintext(ek: &EncryptionKey, c: &BigInt, m: &BigInt) -> BigInt {
+    let minus_m = (&ek.n - m % &ek.n) % &ek.n;
+    let g_minus_m = (BigInt::one() + minus_m * &ek.n) % &ek.nn;
+    BigInt::mod_mul(c, &g_minus_m, &ek.nn)
+}
+
+fn sample_unit(n: &BigInt, rng: &mut (impl RngCore + CryptoRng)) -> BigInt {
+    loop {
+        let x = BigInt::sample_below_with_rng(n, rng);
+        if is_unit(&x, n) {
+            return x;
+        }
+    }
+}
+
+fn is_unit(x: &BigInt, n: &BigInt) -> bool {
+    x.gcd(n) == BigInt::one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paillier::test_keypair;
+    use crate::paillier::Randomness;
+    use crate::paillier::{EncryptWithChosenRandomness, KeyGeneration, RawPlaintext};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn encrypt(ek: &EncryptionKey, m: &BigInt, r: &BigInt) -> BigInt {
+        Paillier::encrypt_with_chosen_randomness(ek, RawPlaintext::from(m), &Randomness::from(r))
+            .into()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_zero_encryption_proof() {
+        let (ek, dk) = test_keypair().keys();
+        let r = BigInt::sample_below(&ek.n);
+        let zero = encrypt(&ek, &BigInt::from(0u32), &r);
+        let proof = NIZeroEncryptionProof::prove(&dk, &zero);
+        assert!(proof.verify(&ek, &zero).is_ok());
+
+        let one = encrypt(&ek, &BigInt::from(1u32), &r);
+        let proof = NIZeroEncryptionProof::prove(&dk, &one);
+        assert_eq!(proof.verify(&ek, &one), Err(CiphertextProofError));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_known_plaintext_proof() {
+        let (ek, _) = test_keypair().keys();
+        let m = BigInt::from(42u32);
+        let r = BigInt::sample_below(&ek.n);
+        let c = encrypt(&ek, &m, &r);
+        let proof = NIKnownPlaintextProof::prove(&ek, &c, &m, &r);
+        assert!(proof.verify(&ek, &c, &m).is_ok());
+        assert!(proof.verify(&ek, &c, &BigInt::from(43u32)).is_err());
+
+        let mut transcript = Transcript::for_party(b"sign", b"session", 1);
+        assert!(proof
+            .verify_with_transcript(&mut transcript, &ek, &c, &m)
+            .is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_correct_decryption_proof() {
+        let (ek, dk) = test_keypair().keys();
+        let m = BigInt::from(1_000_001u32);
+        let c = encrypt(&ek, &m, &BigInt::sample_below(&ek.n));
+        let proof = NICorrectDecryptionProof::prove(&dk, &c);
+        assert_eq!(proof.plaintext, m);
+        assert!(proof.verify(&ek, &c).is_ok());
+
+        let mut forged = proof;
+        forged.plaintext = BigInt::from(1_000_000u32);
+        assert!(forged.verify(&ek, &c).is_err());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_equal_plaintext_proof() {
+        let (ek1, _) = test_keypair().keys();
+        let mut rng = StdRng::seed_from_u64(49);
+        let (ek2, _) = Paillier::keypair_with_modulus_size_and_rng(1024, &mut rng).keys();
+
+        let m = BigInt::sample(NIEqualPlaintextProof::max_plaintext_bits(&ek1, &ek2));
+        let r1 = BigInt::sample_below(&ek1.n);
+        let r2 = BigInt::sample_below(&ek2.n);
+        let c1 = encrypt(&ek1, &m, &r1);
+        let c2 = encrypt(&ek2, &m, &r2);
+        let proof = NIEqualPlaintextProof::prove(&ek1, &ek2, &c1, &c2, &m, &r1, &r2).unwrap();
+        assert!(proof.verify(&ek1, &ek2, &c1, &c2).is_ok());
+        assert!(proof.verify(&ek2, &ek1, &c2, &c1).is_err());
+
+        let other = encrypt(&ek2, &(&m + BigInt::one()), &r2);
+        let proof = NIEqualPlaintextProof::prove(&ek1, &ek2, &c1, &other, &m, &r1, &r2).unwrap();
+        assert!(proof.verify(&ek1, &ek2, &c1, &other).is_err());
+
+        let big = BigInt::one() << NIEqualPlaintextProof::max_plaintext_bits(&ek1, &ek2);
+        let result = NIEqualPlaintextProof::prove(&ek1, &ek2, &c1, &c2, &big, &r1, &r2);
+        assert_eq!(result, Err(CiphertextProofError));
+    }
+
+    /// Runs the prover with a mask of `mask_bits` bits and no check on the plaintext.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_unbounded(
+        ek1: &EncryptionKey,
+        ek2: &EncryptionKey,
+        c1: &BigInt,
+        c2: &BigInt,
+        m: &BigInt,
+        r1: &BigInt,
+        r2: &BigInt,
+        mask_bits: usize,
+    ) -> NIEqualPlaintextProof {
+        let mut rng = StdRng::seed_from_u64(49);
+        let mu = BigInt::sample_with_rng(mask_bits, &mut rng);
+        let rho1 = sample_unit(&ek1.n, &mut rng);
+        let rho2 = sample_unit(&ek2.n, &mut rng);
+        let a1 = commit(ek1, &mu, &rho1);
+        let a2 = commit(ek2, &mu, &rho2);
+        let mut transcript = Transcript::new(EQUAL_PLAINTEXT_PROOF_LABEL);
+        let e = NIEqualPlaintextProof::challenge(&mut transcript, ek1, ek2, c1, c2, &a1, &a2);
+        let z = mu + &e * m;
+        let w1 = BigInt::mod_mul(&rho1, &BigInt::mod_pow(r1, &e, &ek1.n), &ek1.n);
+        let w2 = BigInt::mod_mul(&rho2, &BigInt::mod_pow(r2, &e, &ek2.n), &ek2.n);
+        NIEqualPlaintextProof { a1, a2, z, w1, w2 }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[test]
+    fn test_equal_plaintext_proof_rejects_wrapped_plaintext() {
+        let mut rng = StdRng::seed_from_u64(50);
+        let (ek1, _) = Paillier::keypair_with_modulus_size_and_rng(1024, &mut rng).keys();
+        let (ek2, _) = Paillier::keypair_with_modulus_size_and_rng(1024, &mut rng).keys();
+
+        // just above both moduli, M decrypts to M - N1 under one key and M - N2 under the other
+        let big = ek1.n.clone().max(ek2.n.clone()) + BigInt::from(5u32);
+        let r1 = BigInt::sample_below(&ek1.n);
+        let r2 = BigInt::sample_below(&ek2.n);
+        let c1 = encrypt(&ek1, &big, &r1);
+        let c2 = encrypt(&ek2, &big, &r2);
+
+        // with the mask of the earlier 2^512 slack, the algebra holds under both keys and only
+        // the bound on z catches the wrapped plaintext
+        let mask_bits = ek1.n.bits().min(ek2.n.bits()) + CHALLENGE_BITS + MASK_EXTRA_BITS;
+        let proof = prove_unbounded(&ek1, &ek2, &c1, &c2, &big, &r1, &r2, mask_bits);
+        let mut transcript = Transcript::new(EQUAL_PLAINTEXT_PROOF_LABEL);
+        let e = NIEqualPlaintextProof::challenge(
+            &mut transcript,
+            &ek1,
+            &ek2,
+            &c1,
+            &c2,
+            &proof.a1,
+            &proof.a2,
+        );
+        let keys = [
+            (&ek1, &c1, &proof.a1, &proof.w1),
+            (&ek2, &c2, &proof.a2, &proof.w2),
+        ];
+        for (ek, c, a, w) in keys {
+            let ace = BigInt::mod_mul(a, &BigInt::mod_pow(c, &e, &ek.nn), &ek.nn);
+            assert_eq!(commit(ek, &proof.z, w), ace);
+        }
+        assert!(proof.verify(&ek1, &ek2, &c1, &c2).is_err());
+
+        let result = NIEqualPlaintextProof::prove(&ek1, &ek2, &c1, &c2, &big, &r1, &r2);
+        assert_eq!(result, Err(CiphertextProofError));
+    }
+}
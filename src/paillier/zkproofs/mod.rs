@@ -14,6 +14,11 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/zk-paillier/blob/master/LICENSE>
 */
 
+mod ciphertext_proofs;
+pub use self::ciphertext_proofs::{
+    CiphertextProofError, NICorrectDecryptionProof, NIEqualPlaintextProof,
+    NIKnownPlaintextProof, NIZeroEncryptionProof,
+};
 mod correct_key_ni;
 pub use self::correct_key_ni::CorrectKeyProofError;
 pub use self::correct_key_ni::NICorrectKeyProof;
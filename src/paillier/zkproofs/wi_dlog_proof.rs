@@ -12,17 +12,15 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/zk-paillier/blob/master/LICENSE>
 */
 
-use std::iter;
-
 use crate::num_integer::Integer;
 use crate::curv::arithmetic::traits::*;
 use crate::curv::arithmetic::num_bigint::BigInt;
+use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 use crate::num_traits::One;
 use crate::num_traits::Pow;
-use sha2::Sha256;
-use digest::Digest;
+use crate::curv::cryptographic_primitives::hashing::transcript::Transcript;
 
 use std::error::Error;
 use std::fmt;
@@ -38,26 +36,12 @@ impl fmt::Display for IncorrectProof {
 
 impl Error for IncorrectProof {}
 
-use std::borrow::Borrow;
-pub fn compute_digest<IT>(it: IT) -> BigInt
-where
-    IT: Iterator,
-    IT::Item: Borrow<BigInt>,
-{
-    let mut hasher = Sha256::new();
-    for value in it {
-        let bytes: Vec<u8> = value.borrow().to_bytes_be();
-        hasher.update(&bytes);
-    }
-
-    let result_bytes = hasher.finalize();
-    BigInt::from_bytes(&result_bytes[..])
-}
-
-
 const K: usize = 128;
 const K_PRIME: usize = 128;
 const SAMPLE_S: usize = 256;
+/// Bit length of the challenge `e`.
+const CHALLENGE_BITS: usize = 256;
+const COMPOSITE_DLOG_PROOF_LABEL: &[u8] = b"emerald-city/composite-dlog-proof";
 
 /// Witness Indistinguishable Proof of knowledge of discrete log with composite modulus.
 ///
@@ -78,7 +62,50 @@ pub struct DLogStatement {
 }
 
 impl CompositeDLogProof {
+    fn challenge(transcript: &mut Transcript, statement: &DLogStatement, x: &BigInt) -> BigInt {
+        transcript.append_message(b"proof", COMPOSITE_DLOG_PROOF_LABEL);
+        transcript.append_bigint(b"N", &statement.N);
+        transcript.append_bigint(b"g", &statement.g);
+        transcript.append_bigint(b"ni", &statement.ni);
+        transcript.append_bigint(b"x", x);
+        transcript.challenge_bigint(b"challenge", CHALLENGE_BITS)
+    }
+
     pub fn prove(statement: &DLogStatement, secret: &BigInt) -> CompositeDLogProof {
+        Self::prove_with_rng(statement, secret, &mut os_rng())
+    }
+
+    pub fn prove_with_rng(
+        statement: &DLogStatement,
+        secret: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> CompositeDLogProof {
+        Self::prove_with_transcript_and_rng(
+            &mut Transcript::new(COMPOSITE_DLOG_PROOF_LABEL),
+            statement,
+            secret,
+            rng,
+        )
+    }
+
+    pub fn verify(&self, statement: &DLogStatement) -> Result<(), IncorrectProof> {
+        self.verify_with_transcript(&mut Transcript::new(COMPOSITE_DLOG_PROOF_LABEL), statement)
+    }
+
+    pub fn prove_with_transcript(
+        transcript: &mut Transcript,
+        statement: &DLogStatement,
+        secret: &BigInt,
+    ) -> CompositeDLogProof {
+        Self::prove_with_transcript_and_rng(transcript, statement, secret, &mut os_rng())
+    }
+
+    pub fn prove_with_transcript_and_rng(
+        transcript: &mut Transcript,
+        statement: &DLogStatement,
+        secret: &BigInt,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> CompositeDLogProof {
         //   pub fn prove(statement: &DLogStatement, secret: &BigInt, dk: &DecryptionKey) -> DLogProof{
 
         //   let one = BigInt::one();
@@ -86,20 +113,19 @@ impl CompositeDLogProof {
         //   let r = BigInt::sample_below(&phi);
 
         let R = BigInt::from(2u32).pow((K + K_PRIME + SAMPLE_S) as u32);
-        let r = BigInt::sample_below(&R);
+        let r = BigInt::sample_below_with_rng(&R, rng);
         let x = BigInt::mod_pow(&statement.g, &r, &statement.N);
-        let e = compute_digest(
-            iter::once(&x)
-                .chain(iter::once(&statement.g))
-                .chain(iter::once(&statement.N))
-                .chain(iter::once(&statement.ni)),
-        );
+        let e = Self::challenge(transcript, statement, &x);
         let y = &r + &e * secret;
 
         CompositeDLogProof { x, y }
     }
 
-    pub fn verify(&self, statement: &DLogStatement) -> Result<(), IncorrectProof> {
+    pub fn verify_with_transcript(
+        &self,
+        transcript: &mut Transcript,
+        statement: &DLogStatement,
+    ) -> Result<(), IncorrectProof> {
         //assert N > 2^k
         assert!(statement.N > BigInt::from(2u32).pow(K as u32));
 
@@ -107,12 +133,7 @@ impl CompositeDLogProof {
         assert_eq!(statement.g.gcd(&statement.N), BigInt::one());
         assert_eq!(statement.ni.gcd(&statement.N), BigInt::one());
 
-        let e = compute_digest(
-            iter::once(&self.x)
-                .chain(iter::once(&statement.g))
-                .chain(iter::once(&statement.N))
-                .chain(iter::once(&statement.ni)),
-        );
+        let e = Self::challenge(transcript, statement, &self.x);
         let ni_e = BigInt::mod_pow(&statement.ni, &e, &statement.N);
         let g_y = BigInt::mod_pow(&statement.g, &self.y, &statement.N);
         let g_y_ni_e = BigInt::mod_mul(&g_y, &ni_e, &statement.N);
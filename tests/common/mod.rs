@@ -20,52 +20,63 @@ pub const BENCH_SAMPLE_SIZE: usize = 10;
 */
 extern crate emerald_city;
 
-use self::emerald_city::curv::arithmetic::num_bigint::from;
+use self::emerald_city::curv::arithmetic::num_bigint::{from, BigInt};
 use self::emerald_city::curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 
 use self::emerald_city::curv::cryptographic_primitives::hashing::traits::Hash;
+use self::emerald_city::curv::cryptographic_primitives::hashing::transcript::Transcript;
 use self::emerald_city::curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
 use self::emerald_city::curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
-use self::emerald_city::curv::elliptic::curves::secp256_k1::{FE, GE};
+use self::emerald_city::curv::elliptic::curves::secp256_k1::GE;
 use self::emerald_city::curv::elliptic::curves::traits::*;
 
 use self::emerald_city::gg_2018::mta::*;
 use self::emerald_city::gg_2018::party_i::*;
 
-pub fn keygen_t_n_parties(
+const SESSION_ID: &[u8] = b"test-session";
+
+pub fn keygen_t_n_parties<P: ECPoint>(
     t: usize,
     n: usize,
-) -> (Vec<Keys>, Vec<SharedKeys>, Vec<GE>, GE, VerifiableSS) {
+) -> (Vec<Keys<P>>, Vec<SharedKeys<P>>, Vec<P>, P, VerifiableSS<P>) {
+    let party_keys_vec = (0..n.clone())
+        .map(|i| Keys::create(i + 1))
+        .collect::<Vec<Keys<P>>>();
+    keygen_t_n_parties_with_keys(t, party_keys_vec)
+}
+
+pub fn keygen_t_n_parties_with_keys<P: ECPoint>(
+    t: usize,
+    party_keys_vec: Vec<Keys<P>>,
+) -> (Vec<Keys<P>>, Vec<SharedKeys<P>>, Vec<P>, P, VerifiableSS<P>) {
+    let n = party_keys_vec.len();
     let parames = Parameters {
         threshold: t,
         share_count: n.clone(),
     };
-    let party_keys_vec = (0..n.clone())
-        .map(|i| Keys::create(i))
-        .collect::<Vec<Keys>>();
 
     let mut bc1_vec = Vec::new();
     let mut decom_vec = Vec::new();
     for i in 0..n.clone() {
-        let (bc1, decom1) = party_keys_vec[i].phase1_broadcast_phase3_proof_of_correct_key();
+        let (bc1, decom1) = party_keys_vec[i].phase1_broadcast_phase3_proof_of_correct_key(SESSION_ID);
         bc1_vec.push(bc1);
         decom_vec.push(decom1);
     }
 
     let y_vec = (0..n.clone())
         .map(|i| decom_vec[i].y_i.clone())
-        .collect::<Vec<GE>>();
+        .collect::<Vec<P>>();
     let mut y_vec_iter = y_vec.iter();
     let head = y_vec_iter.next().unwrap();
     let tail = y_vec_iter;
-    let y_sum = tail.fold(head.clone(), |acc, x| acc + x);
+    let y_sum = tail.fold(head.clone(), |acc, x| acc + x.clone());
     let mut vss_scheme_vec = Vec::new();
     let mut secret_shares_vec = Vec::new();
     let mut index_vec = Vec::new();
     for i in 0..n.clone() {
         let (vss_scheme, secret_shares, index) = party_keys_vec[i]
             .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
-                &parames, &decom_vec, &bc1_vec,
+                &parames, SESSION_ID, &decom_vec, &bc1_vec,
             )
             .expect("invalid key");
         vss_scheme_vec.push(vss_scheme);
@@ -81,9 +92,9 @@ pub fn keygen_t_n_parties(
                     let vec_j = &secret_shares_vec[j];
                     vec_j[i].clone()
                 })
-                .collect::<Vec<FE>>()
+                .collect::<Vec<P::Scalar>>()
         })
-        .collect::<Vec<Vec<FE>>>();
+        .collect::<Vec<Vec<P::Scalar>>>();
 
     let mut shared_keys_vec = Vec::new();
     let mut dlog_proof_vec = Vec::new();
@@ -94,7 +105,7 @@ pub fn keygen_t_n_parties(
                 &y_vec,
                 &party_shares[i],
                 &vss_scheme_vec,
-                &(&index_vec[i] + 1),
+                &index_vec[i],
             )
             .expect("invalid vss");
         shared_keys_vec.push(shared_keys);
@@ -103,7 +114,7 @@ pub fn keygen_t_n_parties(
 
     let pk_vec = (0..n.clone())
         .map(|i| dlog_proof_vec[i].pk.clone())
-        .collect::<Vec<GE>>();
+        .collect::<Vec<P>>();
 
     //both parties run:
     Keys::verify_dlog_proofs(&parames, &dlog_proof_vec, &y_vec).expect("bad dlog proof");
@@ -111,13 +122,12 @@ pub fn keygen_t_n_parties(
     //test
     let xi_vec = (0..t.clone() + 1)
         .map(|i| shared_keys_vec[i].x_i.clone())
-        .collect::<Vec<FE>>();
-    let x = vss_scheme_for_test[0]
-        .clone()
-        .reconstruct(&index_vec[0..t.clone() + 1], &xi_vec);
+        .collect::<Vec<P::Scalar>>();
+    let indices = (0..t.clone() + 1).collect::<Vec<usize>>();
+    let x = vss_scheme_for_test[0].clone().reconstruct(&indices, &xi_vec);
     let sum_u_i = party_keys_vec
         .iter()
-        .fold(FE::zero(), |acc, x| acc + &x.u_i);
+        .fold(P::Scalar::zero(), |acc, x| acc + x.u_i.clone());
     assert_eq!(x, sum_u_i);
 
     (
@@ -129,15 +139,111 @@ pub fn keygen_t_n_parties(
     )
 }
 
+#[allow(dead_code)]
+pub fn keygen_t_n_parties_pvss<P: ECPoint>(
+    t: usize,
+    n: usize,
+) -> (Vec<Keys<P>>, Vec<SharedKeys<P>>, P) {
+    let parames = Parameters {
+        threshold: t,
+        share_count: n.clone(),
+    };
+    let party_keys_vec = (0..n.clone())
+        .map(|i| Keys::create(i + 1))
+        .collect::<Vec<Keys<P>>>();
+
+    let mut bc1_vec = Vec::new();
+    let mut decom_vec = Vec::new();
+    for i in 0..n.clone() {
+        let (bc1, decom1) = party_keys_vec[i].phase1_broadcast_phase3_proof_of_correct_key(SESSION_ID);
+        bc1_vec.push(bc1);
+        decom_vec.push(decom1);
+    }
+    let y_vec = (0..n.clone())
+        .map(|i| decom_vec[i].y_i.clone())
+        .collect::<Vec<P>>();
+    let y_sum = y_vec.iter().sum::<P>();
+
+    // each party publishes a key to receive its shares under, apart from u_i
+    let (pvss_dk_vec, pvss_ek_vec): (Vec<P::Scalar>, Vec<P>) =
+        (0..n).map(|_| Keys::<P>::create_pvss_keypair()).unzip();
+
+    // the dealings are broadcast: no private channels are needed
+    let mut pvss_scheme_vec = Vec::new();
+    let mut index_vec = Vec::new();
+    for i in 0..n.clone() {
+        let (pvss_scheme, index) = party_keys_vec[i]
+            .phase1_verify_com_phase3_verify_correct_key_phase2_distribute_pvss(
+                &parames, SESSION_ID, &decom_vec, &bc1_vec, &pvss_ek_vec,
+            )
+            .expect("invalid key");
+        pvss_scheme_vec.push(pvss_scheme);
+        index_vec.push(index);
+    }
+    // an observer can check every dealing
+    Keys::verify_pvss_dealings(&parames, &pvss_scheme_vec, &y_vec, &pvss_ek_vec)
+        .expect("invalid dealing");
+
+    let mut shared_keys_vec = Vec::new();
+    let mut dlog_proof_vec = Vec::new();
+    for i in 0..n.clone() {
+        let (shared_keys, dlog_proof) = party_keys_vec[i]
+            .phase2_verify_pvss_construct_keypair_phase3_pok_dlog(
+                &parames,
+                &y_vec,
+                &pvss_ek_vec,
+                &pvss_scheme_vec,
+                &index_vec[i],
+                &pvss_dk_vec[i],
+            )
+            .expect("invalid vss");
+        shared_keys_vec.push(shared_keys);
+        dlog_proof_vec.push(dlog_proof);
+    }
+    Keys::verify_dlog_proofs(&parames, &dlog_proof_vec, &y_vec).expect("bad dlog proof");
+
+    let xi_vec = (0..t.clone() + 1)
+        .map(|i| shared_keys_vec[i].x_i.clone())
+        .collect::<Vec<P::Scalar>>();
+    let indices = (0..t.clone() + 1).collect::<Vec<usize>>();
+    let x = pvss_scheme_vec[0].vss.reconstruct(&indices, &xi_vec);
+    let sum_u_i = party_keys_vec
+        .iter()
+        .fold(P::Scalar::zero(), |acc, x| acc + x.u_i.clone());
+    assert_eq!(x, sum_u_i);
+
+    (party_keys_vec, shared_keys_vec, y_sum)
+}
+
 #[allow(dead_code)]
 pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
+    let (sig, y, message_bn) = sign_on_curve::<GE>(t, n, ttag, s);
+
+    // cross-check against libsecp256k1
+    assert!(verify_with_libsecp256k1(&sig, &y, &message_bn).is_ok());
+    assert_eq!(recover_public_key(&sig, sig.recid, &message_bn).unwrap(), y);
+    assert_ne!(
+        recover_public_key(&sig, sig.recid ^ 1, &message_bn).unwrap(),
+        y
+    );
+}
+
+/// Runs keygen and a full signing round on the curve `P`, returning the signature together
+/// with the group public key and the signed message.
+#[allow(dead_code)]
+pub fn sign_on_curve<P: ECPoint>(
+    t: usize,
+    n: usize,
+    ttag: usize,
+    s: Vec<usize>,
+) -> (Signature<P::Scalar>, P, BigInt) {
     // full key gen emulation
     let (party_keys_vec, shared_keys_vec, _pk_vec, y, vss_scheme) =
-        keygen_t_n_parties(t.clone(), n);
+        keygen_t_n_parties::<P>(t.clone(), n);
 
     let private_vec = (0..shared_keys_vec.len())
         .map(|i| PartyPrivate::set_private(party_keys_vec[i].clone(), shared_keys_vec[i].clone()))
-        .collect::<Vec<PartyPrivate>>();
+        .collect::<Vec<PartyPrivate<P>>>();
     // make sure that we have t<t'<n and the group s contains id's for t' parties
     // TODO: make sure s has unique id's and they are all in range 0..n
     // TODO: make sure this code can run when id's are not in ascending order
@@ -149,7 +255,7 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     // throughout i will index parties
     let sign_keys_vec = (0..ttag)
         .map(|i| SignKeys::create(&private_vec[s[i]], &vss_scheme, s[i], &s))
-        .collect::<Vec<SignKeys>>();
+        .collect::<Vec<SignKeys<P>>>();
 
     // each party computes [Ci,Di] = com(g^gamma_i) and broadcast the commitments
     let mut bc1_vec = Vec::new();
@@ -164,7 +270,12 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     // m_a_vec = [ma_0;ma_1;,...]
     let mut m_a_vec = Vec::new();
     for i in 0..ttag.clone() {
-        let (m_a_k, _) = MessageA::a(&sign_keys_vec[i].k_i, &party_keys_vec[s[i]].ek, &[]);
+        let (m_a_k, _) = MessageA::a(
+            &mta_transcript(s[i]),
+            &sign_keys_vec[i].k_i,
+            &party_keys_vec[s[i]].ek,
+            &[],
+        );
 
         m_a_vec.push(m_a_k);
     }
@@ -187,13 +298,17 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
         for j in 0..ttag - 1 {
             let ind = if j < i { j } else { j + 1 };
 
-            let (m_b_gamma, beta_gamma, _, _) = MessageB::b(
+            let (m_b_gamma, beta_gamma, _, _) = MessageB::<P>::b(
+                &mta_transcript(s[ind]),
+                &mta_transcript(s[i]),
                 &sign_keys_vec[i].gamma_i,
                 &party_keys_vec[s[ind]].ek,
                 m_a_vec[ind].clone(),
                 &[],
             ).unwrap();
-            let (m_b_w, beta_wi, _, _) = MessageB::b(
+            let (m_b_w, beta_wi, _, _) = MessageB::<P>::b(
+                &mta_transcript(s[ind]),
+                &mta_transcript(s[i]),
                 &sign_keys_vec[i].w_i,
                 &party_keys_vec[s[ind]].ek,
                 m_a_vec[ind].clone(),
@@ -231,11 +346,19 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
             let m_b = m_b_gamma_vec_i[j].clone();
 
             let alpha_ij_gamma = m_b
-                .verify_proofs_get_alpha(&party_keys_vec[s[ind]].dk, &sign_keys_vec[ind].k_i)
+                .verify_proofs_get_alpha(
+                    &mta_transcript(s[i]),
+                    &party_keys_vec[s[ind]].dk,
+                    &sign_keys_vec[ind].k_i,
+                )
                 .expect("wrong dlog or m_b");
             let m_b = m_b_w_vec_i[j].clone();
             let alpha_ij_wi = m_b
-                .verify_proofs_get_alpha(&party_keys_vec[s[ind]].dk, &sign_keys_vec[ind].k_i)
+                .verify_proofs_get_alpha(
+                    &mta_transcript(s[i]),
+                    &party_keys_vec[s[ind]].dk,
+                    &sign_keys_vec[ind].k_i,
+                )
                 .expect("wrong dlog or m_b");
 
             // since we actually run two MtAwc each party needs to make sure that the values B are the same as the public values
@@ -262,14 +385,14 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     }
 
     // all parties broadcast delta_i and compute delta_i ^(-1)
-    let delta_inv = SignKeys::phase3_reconstruct_delta(&delta_vec);
+    let delta_inv = SignKeys::<P>::phase3_reconstruct_delta(&delta_vec);
 
     // de-commit to g^gamma_i from phase1, test comm correctness, and that it is the same value used in MtA.
     // Return R
 
     let _g_gamma_i_vec = (0..ttag)
         .map(|i| sign_keys_vec[i].g_gamma_i.clone())
-        .collect::<Vec<GE>>();
+        .collect::<Vec<P>>();
 
     let R_vec = (0..ttag)
         .map(|_| {
@@ -279,12 +402,12 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
                     let b_gamma_vec = &m_b_gamma_vec_all[j];
                     &b_gamma_vec[0].b_proof
                 })
-                .collect::<Vec<&DLogProof>>();
+                .collect::<Vec<&DLogProof<P>>>();
             let R = SignKeys::phase4(&delta_inv, &b_proof_vec, decommit_vec1.clone(), &bc1_vec)
                 .expect("bad gamma_i decommit");
             R
         })
-        .collect::<Vec<GE>>();
+        .collect::<Vec<P>>();
 
     let message: [u8; 4] = [79, 77, 69, 82];
 
@@ -304,13 +427,13 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     }
 
     let mut phase5_com_vec: Vec<Phase5Com1> = Vec::new();
-    let mut phase_5a_decom_vec: Vec<Phase5ADecom1> = Vec::new();
+    let mut phase_5a_decom_vec: Vec<Phase5ADecom1<P>> = Vec::new();
     let mut helgamal_proof_vec = Vec::new();
     // we notice that the proof for V= R^sg^l, B = A^l is a general form of homomorphic elgamal.
     let mut dlog_proof_rho_vec = Vec::new();
     for i in 0..ttag.clone() {
         let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
-            local_sig_vec[i].phase5a_broadcast_5b_zkproof();
+            local_sig_vec[i].phase5a_broadcast_5b_zkproof(SESSION_ID, s[i]);
         phase5_com_vec.push(phase5_com);
         phase_5a_decom_vec.push(phase_5a_decom);
         helgamal_proof_vec.push(helgamal_proof);
@@ -329,9 +452,26 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
         let _com_i = phase_5a_com_vec_clone.remove(i);
         let _elgamal_i = phase_5b_elgamal_vec_clone.remove(i);
         let _dlog_proof_rho = phase_5a_dlog_vec_clone.remove(i);
+        let mut indices = s.clone();
+        indices.remove(i);
+        // a missing elgamal proof is reported rather than indexed past
+        assert!(local_sig_vec[i]
+            .phase5c(
+                SESSION_ID,
+                &indices,
+                &phase_5a_decom_vec_clone,
+                &phase_5a_com_vec_clone,
+                &phase_5b_elgamal_vec_clone[1..].to_vec(),
+                &phase_5a_dlog_vec_clone,
+                &phase_5a_decom_vec[i].V_i,
+                &R_vec[0],
+            )
+            .is_err());
         //        for j in 0..s_minus_i.len() {
         let (phase5_com2, phase_5d_decom2) = local_sig_vec[i]
             .phase5c(
+                SESSION_ID,
+                &indices,
                 &phase_5a_decom_vec_clone,
                 &phase_5a_com_vec_clone,
                 &phase_5b_elgamal_vec_clone,
@@ -346,7 +486,7 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
     }
 
     // assuming phase5 checks passes each party sends s_i and compute sum_i{s_i}
-    let mut s_vec: Vec<FE> = Vec::new();
+    let mut s_vec: Vec<P::Scalar> = Vec::new();
     for i in 0..ttag.clone() {
         let s_i = local_sig_vec[i]
             .phase5d(&phase_5d_decom2_vec, &phase5_com2_vec, &phase_5a_decom_vec)
@@ -356,7 +496,30 @@ pub fn sign(t: usize, n: usize, ttag: usize, s: Vec<usize>) {
 
     // here we compute the signature only of party i=0 to demonstrate correctness.
     s_vec.remove(0);
-    let _sig = local_sig_vec[0]
+    let sig = local_sig_vec[0]
         .output_signature(&s_vec)
         .expect("verification failed");
+
+    // output is normalized to low-S and carries a recovery id
+    let half_q = P::Scalar::q() / BigInt::from(2u32);
+    assert!(sig.s.to_big_int() <= half_q);
+    assert!(sig.recid < 4);
+
+    let compact = sig.to_compact();
+    assert_eq!(compact.len(), 64);
+    assert_eq!(BigInt::from_bytes_be(&compact[..32]), sig.r.to_big_int());
+    assert_eq!(BigInt::from_bytes_be(&compact[32..]), sig.s.to_big_int());
+    let rsv = sig.to_rsv();
+    assert_eq!(rsv.len(), 65);
+    assert_eq!(rsv[64], sig.recid);
+    let der = sig.to_der();
+    assert_eq!(der[0], 0x30);
+    assert_eq!(der[1] as usize, der.len() - 2);
+
+    (sig, y, message_bn)
+}
+
+/// Transcript of the MtA messages sent by the signer holding key share `index`.
+fn mta_transcript(index: usize) -> Transcript {
+    Transcript::for_party(b"gg18/sign/mta", SESSION_ID, index as u64)
 }
@@ -10,35 +10,114 @@ extern crate wasm_bindgen_test;
 
 mod common;
 
+use emerald_city::curv::arithmetic::num_bigint::BigInt;
+use emerald_city::curv::arithmetic::traits::Modulo;
+use emerald_city::curv::elliptic::curves::p256;
+use emerald_city::curv::elliptic::curves::secp256_k1::GE;
+use emerald_city::gg_2018::party_i::{Keys, PreParams};
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_test::*;
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
 #[test]
 fn test_keygen_t1_n2() {
-    common::keygen_t_n_parties(1, 2);
+    common::keygen_t_n_parties::<GE>(1, 2);
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
 #[test]
 fn test_keygen_t2_n3() {
-    common::keygen_t_n_parties(2, 3);
+    common::keygen_t_n_parties::<GE>(2, 3);
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
 #[test]
 fn test_keygen_t2_n4() {
-    common::keygen_t_n_parties(2, 4);
+    common::keygen_t_n_parties::<GE>(2, 4);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_keygen_t1_n3_p256() {
+    common::keygen_t_n_parties::<p256::GE>(1, 3);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_keygen_pvss_t1_n3() {
+    common::keygen_t_n_parties_pvss::<GE>(1, 3);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_keygen_with_preparams_t1_n2() {
+    let preparams_vec = (0..2)
+        .map(|_| PreParams::generate_with_modulus_size(1024))
+        .collect::<Vec<PreParams>>();
+    // pre-parameters survive being stored and loaded again
+    let stored = serde_json::to_string(&preparams_vec).expect("Failed in serialization");
+    let loaded: Vec<PreParams> = serde_json::from_str(&stored).expect("Failed in deserialization");
+    assert_eq!(loaded, preparams_vec);
+
+    let party_keys_vec = loaded
+        .iter()
+        .enumerate()
+        .map(|(i, preparams)| {
+            let statement = preparams.dlog_statement();
+            assert_eq!(BigInt::mod_pow(&statement.g, &preparams.xhi, &statement.N), statement.ni);
+            assert_eq!(BigInt::mod_pow(&statement.ni, &preparams.xhi_inv, &statement.N), statement.g);
+            Keys::create_with_preparams(i + 1, preparams)
+        })
+        .collect::<Vec<Keys<GE>>>();
+    for (keys, preparams) in party_keys_vec.iter().zip(preparams_vec.iter()) {
+        assert_eq!(keys.ek.n, &preparams.keypair.p * &preparams.keypair.q);
+    }
+    common::keygen_t_n_parties_with_keys(1, party_keys_vec);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_generate_preparams_async() {
+    use emerald_city::paillier::{yield_now, KeyGenProgress};
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let mut fractions = Vec::new();
+    let preparams = {
+        let on_progress = |p: &KeyGenProgress| fractions.push(p.fraction());
+        let mut future = Box::pin(PreParams::generate_async(1024, on_progress, yield_now));
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(preparams) = future.as_mut().poll(&mut cx) {
+                break preparams;
+            }
+        }
+    };
+    // one search for the Paillier key and one for N_tilde, each running up to 1
+    assert_eq!(fractions.iter().filter(|f| **f == 1.0).count(), 2);
+    let statement = preparams.dlog_statement();
+    assert_eq!(statement.N.bits(), 1024);
+    assert_eq!(BigInt::mod_pow(&statement.g, &preparams.xhi, &statement.N), statement.ni);
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod bench {
+    use super::GE;
     use criterion::Criterion;
 
     pub fn bench_full_keygen_party_one_two(c: &mut Criterion) {
         c.bench_function("keygen t=1 n=2", move |b| {
             b.iter(|| {
-                super::common::keygen_t_n_parties(1, 2);
+                super::common::keygen_t_n_parties::<GE>(1, 2);
             })
         });
     }
@@ -46,7 +125,7 @@ pub mod bench {
     pub fn bench_full_keygen_party_two_three(c: &mut Criterion) {
         c.bench_function("keygen t=2 n=3", move |b| {
             b.iter(|| {
-                super::common::keygen_t_n_parties(2, 3);
+                super::common::keygen_t_n_parties::<GE>(2, 3);
             })
         });
     }
@@ -23,10 +23,16 @@ extern crate wasm_bindgen_test;
 
 extern crate emerald_city;
 
-use emerald_city::curv::elliptic::curves::secp256_k1::FE;
+use emerald_city::curv::arithmetic::num_bigint::BigInt;
+use emerald_city::curv::arithmetic::traits::Samplable;
+use emerald_city::curv::cryptographic_primitives::hashing::transcript::Transcript;
+use emerald_city::curv::elliptic::curves::p256;
+use emerald_city::curv::elliptic::curves::secp256_k1::{FE, GE};
 use emerald_city::curv::elliptic::curves::traits::*;
 
 use emerald_city::gg_2018::mta::*;
+use emerald_city::gg_2018::party_i::PreParams;
+use emerald_city::gg_2018::range_proofs::AliceProof;
 use emerald_city::paillier::*;
 
 #[cfg(target_arch = "wasm32")]
@@ -45,13 +51,86 @@ fn test_mta() {
             let (ek_alice, dk_alice) = keypair.keys();
     */
     let bob_input: FE = ECScalar::new_random();
-    let (m_a, _) = MessageA::a(&alice_input, &ek_alice, &[]);
-    let (m_b, beta, _, _) = MessageB::b(&bob_input, &ek_alice, m_a, &[]).unwrap();
+    let alice_transcript = Transcript::for_party(b"test/mta", b"test-session", 1);
+    let bob_transcript = Transcript::for_party(b"test/mta", b"test-session", 2);
+    let (m_a, _) = MessageA::a(&alice_transcript, &alice_input, &ek_alice, &[]);
+    let (m_b, beta, _, _) =
+        MessageB::<GE>::b(&alice_transcript, &bob_transcript, &bob_input, &ek_alice, m_a, &[])
+            .unwrap();
+    // the dlog proofs do not verify as if another party had sent them
+    assert!(m_b.verify_proofs_get_alpha(&alice_transcript, &dk_alice, &alice_input).is_err());
     let alpha = m_b
-        .verify_proofs_get_alpha(&dk_alice, &alice_input)
+        .verify_proofs_get_alpha(&bob_transcript, &dk_alice, &alice_input)
         .expect("wrong dlog or m_b");
 
     let left = alpha.0 + beta;
     let right = alice_input * bob_input;
     assert_eq!(left.get_element(), right.get_element());
 }
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_mta_p256_range_proofs() {
+    let (ek_alice, dk_alice) = Paillier::keypair().keys();
+    let bob_statement = PreParams::generate_with_modulus_size(1024).dlog_statement();
+    let alice_transcript = Transcript::for_party(b"test/mta", b"test-session", 1);
+    let bob_transcript = Transcript::for_party(b"test/mta", b"test-session", 2);
+
+    let alice_input: p256::FE = ECScalar::new_random();
+    let bob_input: p256::FE = ECScalar::new_random();
+    let statements = [bob_statement.clone()];
+    let (m_a, _) = MessageA::a(&alice_transcript, &alice_input, &ek_alice, &statements);
+    let (m_b, beta, _, _) = MessageB::<p256::GE>::b(
+        &alice_transcript,
+        &bob_transcript,
+        &bob_input,
+        &ek_alice,
+        m_a,
+        &statements,
+    )
+    .unwrap();
+    let (alpha, _) = m_b
+        .verify_proofs_get_alpha(&bob_transcript, &dk_alice, &alice_input)
+        .expect("wrong dlog or m_b");
+    let left = alpha + beta;
+    let right = alice_input.clone() * bob_input.clone();
+    assert_eq!(left.get_element(), right.get_element());
+
+    // a plaintext of q^3 for the P-256 order takes the response s1 past the bound
+    let q = p256::FE::q();
+    let too_big = &q * &q * &q;
+    let r = BigInt::sample_below(&ek_alice.n);
+    let c = Paillier::encrypt_with_chosen_randomness(
+        &ek_alice,
+        RawPlaintext::from(&too_big),
+        &Randomness::from(&r),
+    );
+    let c: BigInt = c.into();
+    let proof = AliceProof::<p256::FE>::generate_with_transcript(
+        &mut alice_transcript.clone(),
+        &too_big,
+        &c,
+        &ek_alice,
+        &bob_statement,
+        &r,
+    );
+    assert!(!proof.verify_with_transcript(
+        &mut alice_transcript.clone(),
+        &c,
+        &ek_alice,
+        &bob_statement
+    ));
+    let m_a = MessageA {
+        c,
+        range_proofs: vec![proof],
+    };
+    assert!(MessageB::<p256::GE>::b(
+        &alice_transcript,
+        &bob_transcript,
+        &bob_input,
+        &ek_alice,
+        m_a,
+        &statements,
+    )
+    .is_err());
+}
@@ -10,6 +10,11 @@ extern crate wasm_bindgen_test;
 
 mod common;
 
+use emerald_city::curv::elliptic::curves::p256;
+use emerald_city::curv::elliptic::curves::secp256_k1::{FE, GE};
+use emerald_city::curv::elliptic::curves::traits::*;
+use emerald_city::gg_2018::party_i::verify;
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_test::*;
 
@@ -37,6 +42,29 @@ fn test_sign_n8_t4_ttag6() {
     common::sign(4, 8, 6, vec![0, 1, 2, 4, 6, 7])
 }
 
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_sign_n3_t1_ttag2_p256() {
+    let (sig, y, message_bn) = common::sign_on_curve::<p256::GE>(1, 3, 2, vec![0, 2]);
+    assert!(verify(&sig, &y, &message_bn).is_ok());
+    assert!(verify(&sig, &y, &(message_bn + 1u32)).is_err());
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[test]
+fn test_verify_rejects_degenerate_inputs() {
+    let (sig, y, message_bn) = common::sign_on_curve::<GE>(1, 3, 2, vec![0, 1]);
+    assert!(verify(&sig, &y, &message_bn).is_ok());
+
+    let mut zero_s = sig.clone();
+    zero_s.s = FE::zero();
+    assert!(verify(&zero_s, &y, &message_bn).is_err());
+    let mut zero_r = sig.clone();
+    zero_r.r = FE::zero();
+    assert!(verify(&zero_r, &y, &message_bn).is_err());
+    assert!(verify(&sig, &GE::identity(), &message_bn).is_err());
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod bench {
     use criterion::Criterion;